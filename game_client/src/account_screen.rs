@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+
+use game_core::{
+    account::{self, AVATARS},
+    api::{ApiClient, ApiError},
+    dto::{AccountDeleteDto, FieldErrorDto, PasswordChangeDto, ProfileUpdateDto, UserTokenDto},
+};
+use godot::{
+    classes::{
+        control::LayoutPreset, Button, CanvasLayer, Control, GridContainer, HBoxContainer,
+        ICanvasLayer, Label, LineEdit, OptionButton, PanelContainer, VBoxContainer,
+    },
+    prelude::*,
+};
+use log::{error, info};
+
+struct AccountControls {
+    display_name: Gd<LineEdit>,
+    avatar: Gd<OptionButton>,
+    old_pass: Gd<LineEdit>,
+    new_pass: Gd<LineEdit>,
+    confirm: Gd<LineEdit>,
+    delete_pass: Gd<LineEdit>,
+    status: Gd<Label>,
+    /// Error labels under the inputs by the field name of the requests
+    errors: HashMap<&'static str, Gd<Label>>,
+}
+
+/// Profile, password change and account deletion of the signed in user
+#[derive(GodotClass)]
+#[class(base=CanvasLayer)]
+pub struct AccountScreen {
+    api: ApiClient,
+    token: Option<UserTokenDto>,
+    controls: Option<AccountControls>,
+    base: Base<CanvasLayer>,
+}
+
+/// Title of the button ending a section and the method it calls
+type SectionAction = (&'static str, &'static str);
+
+fn secret_input() -> Gd<LineEdit> {
+    let mut input = LineEdit::new_alloc();
+    input.set_secret(true);
+    input
+}
+
+fn button(title: &str) -> Gd<Button> {
+    let mut button = Button::new_alloc();
+    button.set_text(title);
+    button
+}
+
+#[godot_api]
+impl AccountScreen {
+    /// The account was deleted, the user is signed out
+    #[signal]
+    fn account_deleted();
+
+    /// Add a hidden account screen to the node
+    pub fn attach(mut node: Gd<Node>) -> Gd<AccountScreen> {
+        let account_screen = AccountScreen::new_alloc();
+        node.add_child(&account_screen);
+        account_screen
+    }
+
+    /// Show the screen with the profile of the user
+    pub fn open(&mut self, api: ApiClient, token: UserTokenDto) {
+        let profile = api.profile(token.user_id);
+        self.api = api;
+        self.token = Some(token);
+        self.show_errors(&[]);
+        if let Some(controls) = self.controls.as_mut() {
+            for input in [
+                &mut controls.old_pass,
+                &mut controls.new_pass,
+                &mut controls.confirm,
+                &mut controls.delete_pass,
+            ] {
+                input.clear();
+            }
+            match profile {
+                Ok(profile) => {
+                    let avatar = profile
+                        .avatar
+                        .and_then(|a| AVATARS.iter().position(|known| *known == a));
+                    controls
+                        .display_name
+                        .set_text(&profile.display_name.unwrap_or_default());
+                    controls.avatar.select(avatar.unwrap_or(0) as i32);
+                    controls.status.set_text("");
+                }
+                Err(e) => {
+                    error!("Failed to load the profile: {}", e);
+                    controls.status.set_text("Failed to load the profile.");
+                }
+            }
+        }
+        self.base_mut().set_visible(true);
+    }
+
+    #[func]
+    fn on_save_profile(&mut self) {
+        let (token, controls) = match (self.token.clone(), self.controls.as_ref()) {
+            (Some(token), Some(controls)) => (token, controls),
+            _ => return,
+        };
+        let display_name = controls
+            .display_name
+            .get_text()
+            .to_string()
+            .trim()
+            .to_string();
+        let avatar = AVATARS[controls.avatar.get_selected().max(0) as usize];
+        if let Some(message) = account::validate_display_name(&display_name) {
+            return self.show_errors(&[account::field_error(account::FIELD_DISPLAY_NAME, message)]);
+        }
+        let request = ProfileUpdateDto {
+            token,
+            display_name: Some(display_name),
+            avatar: Some(avatar.to_string()),
+        };
+        let result = self.api.update_profile(&request).map(|_| ());
+        self.show_result(result, "The profile is saved.");
+    }
+
+    #[func]
+    fn on_change_password(&mut self) {
+        let (token, controls) = match (self.token.clone(), self.controls.as_ref()) {
+            (Some(token), Some(controls)) => (token, controls),
+            _ => return,
+        };
+        let old_pass = controls.old_pass.get_text().to_string();
+        let new_pass = controls.new_pass.get_text().to_string();
+        let confirm = controls.confirm.get_text().to_string();
+        let errors = account::validate_pass_change(&old_pass, &new_pass, &confirm);
+        if !errors.is_empty() {
+            return self.show_errors(&errors);
+        }
+        let request = PasswordChangeDto {
+            token,
+            old_pass,
+            new_pass,
+        };
+        let result = self.api.change_password(&request);
+        self.show_result(result, "The password is changed.");
+    }
+
+    #[func]
+    fn on_delete_account(&mut self) {
+        let (token, controls) = match (self.token.clone(), self.controls.as_ref()) {
+            (Some(token), Some(controls)) => (token, controls),
+            _ => return,
+        };
+        let pass = controls.delete_pass.get_text().to_string();
+        if pass.is_empty() {
+            return self.show_errors(&[account::field_error(
+                account::FIELD_PASS,
+                "Enter the password to confirm",
+            )]);
+        }
+        let login = token.login.clone();
+        match self.api.delete_account(&AccountDeleteDto { token, pass }) {
+            Ok(_) => {
+                info!("Account {} is deleted", login);
+                self.token = None;
+                self.base_mut().set_visible(false);
+                self.base_mut().emit_signal("account_deleted", &[]);
+            }
+            Err(e) => self.show_result(Err(e), ""),
+        }
+    }
+
+    #[func]
+    fn on_close(&mut self) {
+        self.base_mut().set_visible(false);
+    }
+
+    /// Field errors go under the inputs, other errors to the status line
+    fn show_result(&mut self, result: Result<(), ApiError>, done: &str) {
+        let status = match result {
+            Ok(_) => {
+                self.show_errors(&[]);
+                done.to_string()
+            }
+            Err(ApiError::Validation(errors)) => {
+                self.show_errors(&errors);
+                String::new()
+            }
+            Err(e) => {
+                error!("Account request failed: {}", e);
+                self.show_errors(&[]);
+                format!("The request failed: {}", e)
+            }
+        };
+        if let Some(controls) = self.controls.as_mut() {
+            controls.status.set_text(&status);
+        }
+    }
+
+    fn show_errors(&mut self, errors: &[FieldErrorDto]) {
+        if let Some(controls) = self.controls.as_mut() {
+            for (field, label) in controls.errors.iter_mut() {
+                label.set_text(account::error_for(errors, field).unwrap_or_default());
+            }
+        }
+    }
+}
+
+#[godot_api]
+impl ICanvasLayer for AccountScreen {
+    fn init(base: Base<CanvasLayer>) -> Self {
+        Self {
+            api: ApiClient::default(),
+            token: None,
+            controls: None,
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        self.base_mut().set_layer(50);
+        self.base_mut().set_visible(false);
+
+        let mut avatar = OptionButton::new_alloc();
+        for name in AVATARS {
+            avatar.add_item(name);
+        }
+        let mut controls = AccountControls {
+            display_name: LineEdit::new_alloc(),
+            avatar,
+            old_pass: secret_input(),
+            new_pass: secret_input(),
+            confirm: secret_input(),
+            delete_pass: secret_input(),
+            status: Label::new_alloc(),
+            errors: HashMap::new(),
+        };
+
+        let mut grid = GridContainer::new_alloc();
+        grid.set_columns(3);
+        let rows: [(&str, &'static str, Gd<Control>, Option<SectionAction>); 6] = [
+            (
+                "Display name",
+                account::FIELD_DISPLAY_NAME,
+                controls.display_name.clone().upcast(),
+                None,
+            ),
+            (
+                "Avatar",
+                account::FIELD_AVATAR,
+                controls.avatar.clone().upcast(),
+                Some(("Save profile", "on_save_profile")),
+            ),
+            (
+                "Current password",
+                account::FIELD_OLD_PASS,
+                controls.old_pass.clone().upcast(),
+                None,
+            ),
+            (
+                "New password",
+                account::FIELD_NEW_PASS,
+                controls.new_pass.clone().upcast(),
+                None,
+            ),
+            (
+                "Repeat the password",
+                account::FIELD_CONFIRM,
+                controls.confirm.clone().upcast(),
+                Some(("Change password", "on_change_password")),
+            ),
+            (
+                "Password to delete the account",
+                account::FIELD_PASS,
+                controls.delete_pass.clone().upcast(),
+                Some(("Delete account", "on_delete_account")),
+            ),
+        ];
+        for (title, field, input, action) in rows {
+            let mut label = Label::new_alloc();
+            label.set_text(title);
+            grid.add_child(&label);
+            grid.add_child(&input);
+            let error_label = Label::new_alloc();
+            grid.add_child(&error_label);
+            controls.errors.insert(field, error_label);
+            // The action of the section goes on its own row under the last input
+            if let Some((title, method)) = action {
+                let mut action_button = button(title);
+                action_button.connect("pressed", &self.base().callable(method));
+                grid.add_child(&Control::new_alloc());
+                grid.add_child(&action_button);
+                grid.add_child(&Control::new_alloc());
+            }
+        }
+
+        let mut close = button("Close");
+        close.connect("pressed", &self.base().callable("on_close"));
+        let mut buttons = HBoxContainer::new_alloc();
+        buttons.add_child(&close);
+
+        let mut column = VBoxContainer::new_alloc();
+        column.add_child(&grid);
+        column.add_child(&controls.status);
+        column.add_child(&buttons);
+        let mut panel = PanelContainer::new_alloc();
+        panel.set_anchors_and_offsets_preset(LayoutPreset::CENTER);
+        panel.add_child(&column);
+        self.base_mut().add_child(&panel);
+        self.controls = Some(controls);
+    }
+}
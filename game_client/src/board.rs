@@ -1,15 +1,38 @@
 use std::collections::HashMap;
 
-use godot::classes::{Area2D, ISprite2D, Sprite2D, Texture2D};
+use game_core::preferences::StoneSet;
+use go_gtp::vertex::COLUMNS;
+use godot::classes::{Area2D, ISprite2D, Label, ResourceLoader, Sprite2D, Texture2D};
 use godot::prelude::*;
+use log::{debug, warn};
 
 use crate::master_scene::MasterScene;
+use crate::settings_screen::{preferences, PREFERENCES_GROUP};
 use crate::stone_place::StonePlace;
 
+/// GTP column letter, boards wider than GTP go on with double letters like "AA"
+fn column_label(col: usize) -> String {
+    let letters = COLUMNS.as_bytes();
+    match col.checked_sub(letters.len()) {
+        None => (letters[col] as char).to_string(),
+        Some(col) => format!(
+            "{}{}",
+            letters[col / letters.len() % letters.len()] as char,
+            letters[col % letters.len()] as char
+        ),
+    }
+}
+
 #[derive(GodotClass)]
 #[class(base=Sprite2D)]
 pub struct Board {
     pub stone_place_vec: HashMap<i32, HashMap<i32, Gd<StonePlace>>>,
+    stones: HashMap<(i32, i32), (bool, Gd<Sprite2D>)>,
+    territory_marks: HashMap<(i32, i32), Gd<Sprite2D>>,
+    hint: Option<Gd<Sprite2D>>,
+    coordinate_labels: Vec<Gd<Label>>,
+    stone_set: StoneSet,
+    size: i32,
     base: Base<Sprite2D>,
 }
 
@@ -28,6 +51,7 @@ impl Board {
             c = None;
         }
         if c.is_none() {
+            self.remove_stone(row, col);
             return;
         }
         let c = c.unwrap();
@@ -44,19 +68,127 @@ impl Board {
         {
             return;
         }
-        let stone_place = self
-            .stone_place_vec
-            .get(&(row as i32))
-            .unwrap()
-            .get(&(col as i32))
-            .unwrap();
-        let stone_path = match c {
-            true => "res://content/materials/black_stone.svg",
-            false => "res://content/materials/white_stone.svg",
+        if let Some((current, _)) = self.stones.get(&(row as i32, col as i32)) {
+            if *current == c {
+                return;
+            }
+        }
+        let stone_path = self.stone_set.texture_path(c);
+        let sprite = match self.make_sprite(row as i32, col as i32, &stone_path, 1.0) {
+            Some(sprite) => sprite,
+            None => return,
+        };
+        if let Some((_, mut old)) = self.stones.insert((row as i32, col as i32), (c, sprite)) {
+            old.queue_free();
+        }
+    }
+
+    /// Show a small territory marker of the owner color, "none" clears it
+    #[func]
+    fn on_mark_territory(&mut self, row: i32, col: i32, owner: String) {
+        if let Some(mut old) = self.territory_marks.remove(&(row, col)) {
+            old.queue_free();
+        }
+        let mark_path = match owner.as_str() {
+            "black" => self.stone_set.texture_path(true),
+            "white" => self.stone_set.texture_path(false),
+            _ => return,
+        };
+        if let Some(sprite) = self.make_sprite(row, col, &mark_path, 0.4) {
+            self.territory_marks.insert((row, col), sprite);
+        }
+    }
+
+    /// Fade the stone out while it is marked as dead in the scoring phase
+    #[func]
+    fn on_mark_dead(&mut self, row: i32, col: i32, dead: bool) {
+        if let Some((_, sprite)) = self.stones.get_mut(&(row, col)) {
+            let alpha = if dead { 0.4 } else { 1.0 };
+            sprite.set_modulate(Color::from_rgba(1.0, 1.0, 1.0, alpha));
+        }
+    }
+
+    /// Show a translucent stone of the suggested move, a negative row clears it
+    #[func]
+    fn on_show_hint(&mut self, row: i32, col: i32, color: bool) {
+        if let Some(mut old) = self.hint.take() {
+            old.queue_free();
+        }
+        if row < 0 || col < 0 {
+            return;
+        }
+        let stone_path = self.stone_set.texture_path(color);
+        if let Some(mut sprite) = self.make_sprite(row, col, &stone_path, 1.0) {
+            sprite.set_modulate(Color::from_rgba(1.0, 1.0, 1.0, 0.5));
+            self.hint = Some(sprite);
+        }
+    }
+
+    /// Called through `PREFERENCES_GROUP` after the settings are saved
+    #[func]
+    fn on_preferences_changed(&mut self) {
+        let preferences = preferences();
+        let (r, g, b) = preferences.board_theme.tint();
+        self.base_mut().set_self_modulate(Color::from_rgb(r, g, b));
+        if preferences.stone_set != self.stone_set {
+            self.stone_set = preferences.stone_set;
+            for (color, sprite) in self.stones.values_mut() {
+                let texture: Gd<Texture2D> = load(&self.stone_set.texture_path(*color));
+                sprite.set_texture(&texture);
+            }
+        }
+        self.set_coordinates_visible(preferences.show_coordinates);
+    }
+
+    /// GTP style labels: letters above the board, line numbers counted from the bottom on the left
+    fn set_coordinates_visible(&mut self, visible: bool) {
+        for mut label in self.coordinate_labels.drain(..) {
+            label.queue_free();
+        }
+        if !visible {
+            return;
+        }
+        let mut area = self.base().get_node_as::<Area2D>("Area2D");
+        for i in 0..self.size {
+            let letter = column_label(i as usize);
+            let number = (self.size - i).to_string();
+            for (text, position) in [
+                (letter, Vector2::new(i as f32 * 50.0 - 6.0, -60.0)),
+                (number, Vector2::new(-60.0, i as f32 * 50.0 - 12.0)),
+            ] {
+                let mut label = Label::new_alloc();
+                label.set_text(&text);
+                label.set_position(position);
+                area.add_child(&label);
+                self.coordinate_labels.push(label);
+            }
+        }
+    }
+
+    fn remove_stone(&mut self, row: i32, col: i32) {
+        if let Some((_, mut sprite)) = self.stones.remove(&(row, col)) {
+            sprite.queue_free();
+        }
+    }
+
+    /// `None` for a point outside of the board, the server may send a bigger board
+    fn make_sprite(
+        &mut self,
+        row: i32,
+        col: i32,
+        texture_path: &str,
+        scale: f32,
+    ) -> Option<Gd<Sprite2D>> {
+        let stone_place = match self.stone_place_vec.get(&row).and_then(|r| r.get(&col)) {
+            Some(stone_place) => stone_place,
+            None => {
+                warn!("No stone place at {}:{}, the mark is skipped", row, col);
+                return None;
+            }
         };
         let mut sprite = Sprite2D::new_alloc();
-        let texture: Gd<Texture2D> = load(stone_path);
-        sprite.set_global_scale(Vector2::new(1.0, 1.0));
+        let texture: Gd<Texture2D> = load(texture_path);
+        sprite.set_global_scale(Vector2::new(scale, scale));
         sprite.set_texture(&texture);
         let position = Vector2::new(
             stone_place.get_position().y * 2.0,
@@ -65,27 +197,22 @@ impl Board {
         sprite.set_position(position);
         let mut area = self.base().get_node_as::<Area2D>("Area2D");
         area.add_child(&sprite);
-        // area.remove_child(stone_place);
-        // self.stone_place_vec
-        //     .get_mut(&(row as i32))
-        //     .unwrap()
-        //     .remove(&(col as i32));
-        godot_print!("Added stone. Remove from map");
-        for item in &self.stone_place_vec {
-            let count = item.1.iter().count();
-            if count != 19 {
-                godot_print!("{}: {}", item.0, item.1.iter().count());
-            }
-        }
+        Some(sprite)
     }
 }
 
 #[godot_api]
 impl ISprite2D for Board {
     fn init(base: Base<Self::Base>) -> Self {
-        godot_print!("Make a board...");
+        debug!("Make a board...");
         Self {
             stone_place_vec: HashMap::new(),
+            stones: HashMap::new(),
+            territory_marks: HashMap::new(),
+            hint: None,
+            coordinate_labels: vec![],
+            stone_set: StoneSet::default(),
+            size: 19,
             base,
         }
     }
@@ -97,9 +224,15 @@ impl ISprite2D for Board {
             .get_parent()
             .and_then(|p| Option::from(p.cast::<MasterScene>()))
             .expect("Master scene can't be get from board");
-        for row in 0..19 {
+        self.size = m_scn.bind().get_board_size();
+        let texture_path = format!("res://content/materials/board_{}.svg", self.size);
+        if ResourceLoader::singleton().exists(&texture_path) {
+            let texture: Gd<Texture2D> = load(&texture_path);
+            self.base_mut().set_texture(&texture);
+        }
+        for row in 0..self.size {
             let mut col_vec: HashMap<i32, Gd<StonePlace>> = HashMap::new();
-            for col in 0..19 {
+            for col in 0..self.size {
                 let stone_place_scene: Gd<PackedScene> =
                     load("res://content/framework/StonePlace.tscn");
                 let mut stone_place_item = stone_place_scene.instantiate_as::<StonePlace>();
@@ -108,7 +241,7 @@ impl ISprite2D for Board {
                 stone_place_item.set_global_scale(Vector2::new(1.0, 1.0));
                 let position = Vector2::new(row as f32 * 25.0, col as f32 * 25.0);
                 stone_place_item.set_global_position(position);
-                godot_print!(
+                debug!(
                     "Added child position: {}",
                     stone_place_item.get_global_position()
                 );
@@ -118,6 +251,8 @@ impl ISprite2D for Board {
             }
             self.stone_place_vec.insert(row, col_vec);
         }
-        // godot_print!("put vec to stone_place_vec: {:?}", self.stone_place_vec);
+        // debug!("put vec to stone_place_vec: {:?}", self.stone_place_vec);
+        self.base_mut().add_to_group(PREFERENCES_GROUP);
+        self.on_preferences_changed();
     }
 }
@@ -0,0 +1,126 @@
+use std::{
+    backtrace::Backtrace,
+    fs,
+    panic::{self, PanicHookInfo},
+    path::PathBuf,
+    sync::{Mutex, Once},
+    thread,
+};
+
+use game_core::api;
+use log::{error, info};
+
+use crate::{
+    logging::{self, LOG_DIR},
+    util::{get_format_time, globalize_path},
+};
+
+/// Written by the panic hook, turned into the recovery dialog on the next launch
+pub const CRASH_REPORT_PATH: &str = "user://crash_report.txt";
+
+const LOG_TAIL_LINES: usize = 100;
+
+/// The extension may be initialized again on a hot reload, the hook is set only once
+static INSTALL: Once = Once::new();
+
+/// What the client was doing, updated by the scenes
+#[derive(Debug, Default)]
+struct ClientState {
+    scene: String,
+    session_id: Option<String>,
+}
+
+static CLIENT_STATE: Mutex<ClientState> = Mutex::new(ClientState {
+    scene: String::new(),
+    session_id: None,
+});
+
+fn client_state() -> std::sync::MutexGuard<'static, ClientState> {
+    CLIENT_STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn set_scene(scene: &str) {
+    client_state().scene = scene.to_string();
+}
+
+pub fn set_session_id(session_id: Option<String>) {
+    client_state().session_id = session_id;
+}
+
+/// Write a crash report on a panic of the main thread, the default hook still prints the message.
+/// Godot may catch the panic of a `#[func]` and go on, the report only says something went wrong.
+/// The paths are resolved here, Godot may be unusable in the middle of a panic.
+pub fn install() {
+    INSTALL.call_once(|| {
+        let report_path = globalize_path(CRASH_REPORT_PATH);
+        let main_thread = thread::current().id();
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if thread::current().id() == main_thread {
+                let report = crash_report(info);
+                match fs::write(&report_path, &report) {
+                    Ok(_) => error!("Crash report saved to {}", report_path.display()),
+                    Err(e) => error!("Failed to save the crash report: {}\n{}", e, report),
+                }
+            }
+            default_hook(info);
+        }));
+    });
+}
+
+fn crash_report(info: &PanicHookInfo) -> String {
+    let state = client_state();
+    let mut report = format!(
+        "Crash at {}\n{}\n\nScene: {}\nSession: {}\n\nBacktrace:\n{}\n",
+        get_format_time(None),
+        info,
+        state.scene,
+        state.session_id.as_deref().unwrap_or("-"),
+        Backtrace::force_capture()
+    );
+    report.push_str("\nLast API responses:\n");
+    for line in api::recent_responses() {
+        report.push_str(&line);
+        report.push('\n');
+    }
+    report.push_str("\nLog:\n");
+    for line in logging::file_log_tail(LOG_TAIL_LINES) {
+        report.push_str(&line);
+        report.push('\n');
+    }
+    report
+}
+
+/// Move the report of the last crash next to the logs, `None` when the last run was fine
+pub fn take_report() -> Option<PathBuf> {
+    archive_report("crash")
+}
+
+/// The client closes normally, so a report of this run is for a panic Godot caught.
+/// It is kept with the logs, the next launch doesn't offer the recovery.
+pub fn finish() {
+    if let Some(saved_path) = archive_report("error") {
+        info!("The error report is saved to {}", saved_path.display());
+    }
+}
+
+fn archive_report(prefix: &str) -> Option<PathBuf> {
+    let report_path = globalize_path(CRASH_REPORT_PATH);
+    if !report_path.exists() {
+        return None;
+    }
+    let log_dir = globalize_path(LOG_DIR);
+    let saved_path = log_dir.join(format!(
+        "{}_{}.txt",
+        prefix,
+        get_format_time(Some("%Y%m%d_%H%M%S"))
+    ));
+    let result = fs::create_dir_all(&log_dir).and_then(|_| fs::rename(&report_path, &saved_path));
+    match result {
+        Ok(_) => Some(saved_path),
+        Err(e) => {
+            error!("Failed to move the crash report: {}", e);
+            Some(report_path)
+        }
+    }
+}
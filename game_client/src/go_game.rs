@@ -0,0 +1,293 @@
+use std::collections::HashSet;
+
+use godot::{
+    classes::{file_access::ModeFlags, FileAccess, IResource},
+    global::Error,
+    prelude::*,
+};
+
+use game_core::{
+    rules::{self, Move},
+    scoring::{self, Point},
+    sgf,
+};
+
+use log::error;
+
+/// The Go rules for GDScript. Points are `Vector2i` with the column in `x` and the row in `y`,
+/// stones are "black", "white" or "none" like in the `Board` signals.
+#[derive(GodotClass)]
+#[class(base=Resource, rename=GoGame)]
+pub struct GoGameResource {
+    game: rules::GoGame,
+    base: Base<Resource>,
+}
+
+fn to_point(point: Vector2i) -> Option<Point> {
+    (point.x >= 0 && point.y >= 0).then_some((point.y as usize, point.x as usize))
+}
+
+fn to_vector(point: &Point) -> Vector2i {
+    Vector2i::new(point.1 as i32, point.0 as i32)
+}
+
+fn to_vectors(points: &[Point]) -> Array<Vector2i> {
+    points.iter().map(to_vector).collect()
+}
+
+fn color_name(color: Option<bool>) -> GString {
+    match color {
+        Some(true) => "black".into(),
+        Some(false) => "white".into(),
+        None => "none".into(),
+    }
+}
+
+#[godot_api]
+impl GoGameResource {
+    /// A stone was played, a pass comes with `Vector2i(-1, -1)`
+    #[signal]
+    fn move_played(point: Vector2i, color: GString);
+
+    /// Stones of `color` were removed from the board
+    #[signal]
+    fn stones_captured(points: Array<Vector2i>, color: GString);
+
+    /// The last move was taken back
+    #[signal]
+    fn move_undone(point: Vector2i, color: GString);
+
+    /// Both players passed, the score counts no dead stones
+    #[signal]
+    fn game_ended(black_score: f32, white_score: f32);
+
+    /// A new empty game, handicap stones go to the star points.
+    /// Null for a size outside 1 to 52, the biggest board SGF can record.
+    #[func]
+    fn create(size: i32, komi: f32, handicap: i32) -> Option<Gd<Self>> {
+        let size = match usize::try_from(size) {
+            Ok(size) if (1..=sgf::MAX_SGF_SIZE).contains(&size) => size,
+            _ => {
+                error!("Unsupported board size {}", size);
+                return None;
+            }
+        };
+        let points = game_core::handicap::fixed_handicap_points(size, handicap.max(0) as usize)
+            .unwrap_or_default();
+        Some(Gd::from_init_fn(|base| Self {
+            game: rules::GoGame::with_handicap(size, komi, &points),
+            base,
+        }))
+    }
+
+    #[func]
+    fn get_size(&self) -> i32 {
+        self.game.size() as i32
+    }
+
+    #[func]
+    fn get_komi(&self) -> f32 {
+        self.game.komi()
+    }
+
+    /// "black" or "white"
+    #[func]
+    fn get_to_move(&self) -> GString {
+        color_name(Some(self.game.to_move()))
+    }
+
+    #[func]
+    fn get_stone(&self, point: Vector2i) -> GString {
+        color_name(to_point(point).and_then(|point| scoring::get(self.game.board(), point)))
+    }
+
+    #[func]
+    fn get_move_count(&self) -> i32 {
+        self.game.moves().len() as i32
+    }
+
+    /// Stones captured by each color as `{"black": n, "white": n}`
+    #[func]
+    fn get_captures(&self) -> Dictionary {
+        let captures = self.game.captures();
+        dict! { "black": captures.black, "white": captures.white }
+    }
+
+    #[func]
+    fn is_over(&self) -> bool {
+        self.game.is_over()
+    }
+
+    /// Why the move is illegal, empty for a legal move
+    #[func]
+    fn get_move_error(&self, point: Vector2i) -> GString {
+        let result = match to_point(point) {
+            Some(point) => self.game.is_legal(point),
+            None => Err(rules::MoveError::OutOfBoard),
+        };
+        match result {
+            Ok(_) => GString::new(),
+            Err(e) => e.to_string().into(),
+        }
+    }
+
+    #[func]
+    fn legal_moves(&self) -> Array<Vector2i> {
+        to_vectors(&self.game.legal_moves())
+    }
+
+    /// Stones of the group at the point, empty for an empty point
+    #[func]
+    fn group_at(&self, point: Vector2i) -> Array<Vector2i> {
+        match to_point(point).filter(|p| scoring::get(self.game.board(), *p).is_some()) {
+            Some(point) => to_vectors(&scoring::group_at(self.game.board(), point)),
+            None => Array::new(),
+        }
+    }
+
+    #[func]
+    fn group_liberties(&self, point: Vector2i) -> i32 {
+        match to_point(point).filter(|p| scoring::get(self.game.board(), *p).is_some()) {
+            Some(point) => self.game.liberties(point) as i32,
+            None => 0,
+        }
+    }
+
+    /// Area of each color with komi and captures, `{"black": n, "white": n}`
+    #[func]
+    fn score_estimate(&self, dead_stones: Array<Vector2i>) -> Dictionary {
+        let dead: HashSet<Point> = dead_stones.iter_shared().filter_map(to_point).collect();
+        let (black, white) = self.game.score(&dead);
+        dict! { "black": black, "white": white }
+    }
+
+    /// Play for the side to move, returns false for an illegal move
+    #[func]
+    fn play(&mut self, point: Vector2i) -> bool {
+        let color = self.game.to_move();
+        let captured = match to_point(point).map(|p| self.game.play(p)) {
+            Some(Ok(captured)) => captured,
+            _ => return false,
+        };
+        self.base_mut().emit_signal(
+            "move_played",
+            &[point.to_variant(), color_name(Some(color)).to_variant()],
+        );
+        if !captured.is_empty() {
+            self.base_mut().emit_signal(
+                "stones_captured",
+                &[
+                    to_vectors(&captured).to_variant(),
+                    color_name(Some(!color)).to_variant(),
+                ],
+            );
+        }
+        true
+    }
+
+    /// `pass` is a GDScript keyword
+    #[func]
+    fn pass_turn(&mut self) -> bool {
+        let color = self.game.to_move();
+        if self.game.pass().is_err() {
+            return false;
+        }
+        self.base_mut().emit_signal(
+            "move_played",
+            &[
+                Vector2i::new(-1, -1).to_variant(),
+                color_name(Some(color)).to_variant(),
+            ],
+        );
+        if self.game.is_over() {
+            let (black, white) = self.game.score(&HashSet::new());
+            self.base_mut()
+                .emit_signal("game_ended", &[black.to_variant(), white.to_variant()]);
+        }
+        true
+    }
+
+    #[func]
+    fn undo(&mut self) -> bool {
+        let (point, color) = match self.game.undo() {
+            Some(Move::Play { point, color }) => (to_vector(&point), color),
+            Some(Move::Pass { color }) => (Vector2i::new(-1, -1), color),
+            None => return false,
+        };
+        self.base_mut().emit_signal(
+            "move_undone",
+            &[point.to_variant(), color_name(Some(color)).to_variant()],
+        );
+        true
+    }
+
+    /// Replace the game with the main line of an SGF record
+    #[func]
+    fn load_sgf(&mut self, content: GString) -> bool {
+        match sgf::parse_sgf(&content.to_string()).and_then(|record| sgf::load_game(&record)) {
+            Ok(game) => {
+                self.game = game;
+                true
+            }
+            Err(e) => {
+                error!("Failed to load the SGF: {}", e);
+                false
+            }
+        }
+    }
+
+    #[func]
+    fn to_sgf(&self) -> GString {
+        sgf::to_sgf(&self.game).into()
+    }
+
+    /// Load an SGF file, `user://` and `res://` paths are supported
+    #[func]
+    fn load_sgf_file(&mut self, path: GString) -> bool {
+        match FileAccess::open(&path, ModeFlags::READ) {
+            Some(file) => self.load_sgf(file.get_as_text()),
+            None => {
+                error!(
+                    "Failed to open {}: {:?}",
+                    path,
+                    FileAccess::get_open_error()
+                );
+                false
+            }
+        }
+    }
+
+    /// Save the main line, `res://` is read-only in exported games
+    #[func]
+    fn save_sgf_file(&self, path: GString) -> bool {
+        let mut file = match FileAccess::open(&path, ModeFlags::WRITE) {
+            Some(file) => file,
+            None => {
+                error!(
+                    "Failed to open {}: {:?}",
+                    path,
+                    FileAccess::get_open_error()
+                );
+                return false;
+            }
+        };
+        file.store_string(&sgf::to_sgf(&self.game));
+        match file.get_error() {
+            Error::OK => true,
+            e => {
+                error!("Failed to save {}: {:?}", path, e);
+                false
+            }
+        }
+    }
+}
+
+#[godot_api]
+impl IResource for GoGameResource {
+    fn init(base: Base<Resource>) -> Self {
+        Self {
+            game: rules::GoGame::new(19, 6.5),
+            base,
+        }
+    }
+}
@@ -1,9 +1,17 @@
+// `#[godot_api]` expands to closures returning the large `CallError`
+#![allow(clippy::result_large_err)]
+
 use godot::prelude::*;
 
+pub mod account_screen;
 pub mod board;
-pub mod dto;
+pub mod crash;
 pub mod game_data;
+pub mod go_game;
+pub mod logging;
 pub mod master_scene;
+pub mod matchmaking_screen;
+pub mod settings_screen;
 pub mod start_game_hud;
 pub mod stone_place;
 pub mod util;
@@ -11,4 +19,17 @@ pub mod util;
 struct GoClient;
 
 #[gdextension]
-unsafe impl ExtensionLibrary for GoClient {}
+unsafe impl ExtensionLibrary for GoClient {
+    fn on_level_init(level: InitLevel) {
+        if level == InitLevel::Scene {
+            logging::init();
+            crash::install();
+        }
+    }
+
+    fn on_level_deinit(level: InitLevel) {
+        if level == InitLevel::Scene {
+            crash::finish();
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use std::{
+    collections::VecDeque,
+    fs,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use godot::{
+    classes::{
+        control::LayoutPreset, CanvasLayer, ICanvasLayer, InputEvent, InputEventKey, RichTextLabel,
+    },
+    global::Key,
+    prelude::*,
+};
+use log::{Level, LevelFilter, Record};
+use log4rs::{
+    append::Append,
+    config::{Appender, Deserialize, Deserializers, Root},
+    Config,
+};
+use serde::Deserialize as SerdeDeserialize;
+
+use crate::util::globalize_path;
+
+/// log4rs configuration, written with the defaults on the first launch.
+/// It is checked for changes while the game runs.
+pub const LOG_CONFIG_PATH: &str = "user://log4rs.yaml";
+pub const LOG_DIR: &str = "user://logs";
+/// The current file of the rolling appender in `LOG_DIR`
+const LOG_FILE_NAME: &str = "client.log";
+
+const DEFAULT_CONFIG: &str = r#"refresh_rate: 30 seconds
+appenders:
+  godot:
+    kind: godot
+  overlay:
+    kind: overlay
+    lines: 200
+  file:
+    kind: rolling_file
+    path: "{log_dir}/client.log"
+    encoder:
+      pattern: "{d(%Y-%m-%d %H:%M:%S%.3f)} {l:<5} {t} - {m}{n}"
+    policy:
+      trigger:
+        kind: size
+        limit: 1 mb
+      roller:
+        kind: fixed_window
+        pattern: "{log_dir}/client.{}.log"
+        count: 5
+root:
+  level: info
+  appenders: [godot, overlay, file]
+loggers:
+  gc_rust::board:
+    level: warn
+  gc_rust::stone_place:
+    level: warn
+  reqwest:
+    level: warn
+"#;
+
+/// The last lines kept by the overlay appender, shown by `LogConsole`
+static LOG_TAIL: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+/// Set when the configuration has an overlay appender
+static OVERLAY_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Route the `log` macros to the Godot output, the log files and the overlay.
+/// A broken configuration falls back to the Godot output only.
+pub fn init() {
+    let config_path = globalize_path(LOG_CONFIG_PATH);
+    if !config_path.exists() {
+        if let Err(e) = write_default_config(&config_path) {
+            godot_error!("Failed to write {}: {}", config_path.display(), e);
+        }
+    }
+    let mut deserializers = Deserializers::default();
+    deserializers.insert("godot", GodotAppenderDeserializer);
+    deserializers.insert("overlay", OverlayAppenderDeserializer);
+    if let Err(e) = log4rs::init_file(&config_path, deserializers) {
+        godot_error!("Failed to load {}: {}", config_path.display(), e);
+        let config = Config::builder()
+            .appender(Appender::builder().build("godot", Box::new(GodotAppender)))
+            .build(Root::builder().appender("godot").build(LevelFilter::Info));
+        if let Ok(config) = config {
+            let _ = log4rs::init_config(config);
+        }
+    }
+}
+
+fn write_default_config(path: &Path) -> std::io::Result<()> {
+    let log_dir = globalize_path(LOG_DIR);
+    fs::create_dir_all(&log_dir)?;
+    let log_dir = log_dir.to_string_lossy().replace('\\', "/");
+    fs::write(path, DEFAULT_CONFIG.replace("{log_dir}", &log_dir))
+}
+
+/// Up to `count` of the newest lines of the log file, the overlay lines without the file.
+/// The file of the default configuration is read, a custom path isn't known here.
+pub fn file_log_tail(count: usize) -> Vec<String> {
+    match fs::read_to_string(globalize_path(LOG_DIR).join(LOG_FILE_NAME)) {
+        Ok(content) => {
+            let lines: Vec<&str> = content.lines().collect();
+            lines[lines.len().saturating_sub(count)..]
+                .iter()
+                .map(|line| line.to_string())
+                .collect()
+        }
+        Err(_) => log_tail(count),
+    }
+}
+
+/// Up to `count` of the newest overlay lines, oldest first
+pub fn log_tail(count: usize) -> Vec<String> {
+    let tail = LOG_TAIL.lock().unwrap_or_else(|e| e.into_inner());
+    tail.iter()
+        .skip(tail.len().saturating_sub(count))
+        .cloned()
+        .collect()
+}
+
+fn format_record(record: &Record) -> String {
+    format!(
+        "[{}] {}: {}",
+        record.level(),
+        record.target(),
+        record.args()
+    )
+}
+
+/// Prints to the Godot output, errors and warnings go to the debugger too
+#[derive(Debug)]
+struct GodotAppender;
+
+impl Append for GodotAppender {
+    fn append(&self, record: &Record) -> anyhow::Result<()> {
+        let line = format_record(record);
+        match record.level() {
+            Level::Error => godot_error!("{}", line),
+            Level::Warn => godot_warn!("{}", line),
+            _ => godot_print!("{}", line),
+        }
+        Ok(())
+    }
+
+    fn flush(&self) {}
+}
+
+#[derive(SerdeDeserialize)]
+struct GodotAppenderConfig {}
+
+struct GodotAppenderDeserializer;
+
+impl Deserialize for GodotAppenderDeserializer {
+    type Trait = dyn Append;
+    type Config = GodotAppenderConfig;
+
+    fn deserialize(
+        &self,
+        _: GodotAppenderConfig,
+        _: &Deserializers,
+    ) -> anyhow::Result<Box<dyn Append>> {
+        Ok(Box::new(GodotAppender))
+    }
+}
+
+/// Keeps the last lines in memory for the in-game console
+#[derive(Debug)]
+struct OverlayAppender {
+    lines: usize,
+}
+
+impl Append for OverlayAppender {
+    fn append(&self, record: &Record) -> anyhow::Result<()> {
+        let mut tail = LOG_TAIL.lock().unwrap_or_else(|e| e.into_inner());
+        tail.push_back(format_record(record));
+        while tail.len() > self.lines {
+            tail.pop_front();
+        }
+        Ok(())
+    }
+
+    fn flush(&self) {}
+}
+
+#[derive(SerdeDeserialize)]
+struct OverlayAppenderConfig {
+    #[serde(default = "default_overlay_lines")]
+    lines: usize,
+}
+
+fn default_overlay_lines() -> usize {
+    200
+}
+
+struct OverlayAppenderDeserializer;
+
+impl Deserialize for OverlayAppenderDeserializer {
+    type Trait = dyn Append;
+    type Config = OverlayAppenderConfig;
+
+    fn deserialize(
+        &self,
+        config: OverlayAppenderConfig,
+        _: &Deserializers,
+    ) -> anyhow::Result<Box<dyn Append>> {
+        OVERLAY_ENABLED.store(true, Ordering::Relaxed);
+        Ok(Box::new(OverlayAppender {
+            lines: config.lines,
+        }))
+    }
+}
+
+/// The recent log lines over the game, toggled with F12.
+/// It is only added to the scenes when the overlay appender is configured.
+#[derive(GodotClass)]
+#[class(base=CanvasLayer)]
+pub struct LogConsole {
+    text: Option<Gd<RichTextLabel>>,
+    base: Base<CanvasLayer>,
+}
+
+impl LogConsole {
+    /// Add the console to the node when it's enabled in the log configuration
+    pub fn attach(mut node: Gd<Node>) {
+        if OVERLAY_ENABLED.load(Ordering::Relaxed) {
+            node.add_child(&LogConsole::new_alloc());
+        }
+    }
+
+    fn refresh(&mut self) {
+        if let Some(text) = self.text.as_mut() {
+            text.set_text(&log_tail(usize::MAX).join("\n"));
+        }
+    }
+}
+
+#[godot_api]
+impl ICanvasLayer for LogConsole {
+    fn init(base: Base<CanvasLayer>) -> Self {
+        Self { text: None, base }
+    }
+
+    fn ready(&mut self) {
+        self.base_mut().set_layer(100);
+        self.base_mut().set_visible(false);
+        let mut text = RichTextLabel::new_alloc();
+        text.set_anchors_and_offsets_preset(LayoutPreset::FULL_RECT);
+        text.set_scroll_follow(true);
+        text.set_self_modulate(Color::from_rgba(1.0, 1.0, 1.0, 0.85));
+        self.base_mut().add_child(&text);
+        self.text = Some(text);
+    }
+
+    fn unhandled_input(&mut self, event: Gd<InputEvent>) {
+        let key = match event.try_cast::<InputEventKey>() {
+            Ok(key) => key,
+            Err(_) => return,
+        };
+        if key.is_pressed() && !key.is_echo() && key.get_keycode() == Key::F12 {
+            let visible = !self.base().is_visible();
+            self.base_mut().set_visible(visible);
+            if visible {
+                self.refresh();
+            }
+        }
+    }
+
+    fn process(&mut self, _delta: f64) {
+        if self.base().is_visible() {
+            self.refresh();
+        }
+    }
+}
@@ -1,30 +1,87 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
 
 use godot::{
-    classes::{CanvasLayer, Control, Label, Timer},
+    classes::{CanvasLayer, Control, DisplayServer, ItemList, Label, LineEdit, TextEdit, Timer},
     prelude::*,
 };
 
-use crate::{
+use game_core::{
+    ai::{AiPlayer, Difficulty},
+    api::ApiClient,
+    chat::{self, ChatHistory},
+    clock::GameClock,
     dto::{
-        GameScore, GameState, GameStateDto, StepDto, UserSessionDto, UserSessionRequestDto,
-        UserSessionStepDto, UserStepRequestDto, UserTokenDto,
+        ChatChannel, ChatRequestDto, ColorPreference, GamePhase, GameRecordDto, GameResultDto,
+        GameResultReason, GameScore, GameSettingsDto, GameState, GameStateDto, HandicapPlacement,
+        StepDto, TimeControl, UndoRequestDto, UndoStatus, UserScoringRequestDto, UserSessionDto,
+        UserStepRequestDto, UserTokenDto, UserUndoAnswerDto, UserUndoRequestDto,
     },
-    util::get_format_time,
+    gtp_bridge::{EngineJob, EngineMove, GtpBridge},
+    handicap,
+    history::{self, PositionHistory},
+    lobby,
+    resume::{self, ActiveGame, ACTIVE_GAME_PATH},
+    review::GameTree,
+    rules::{GoGame, Move},
+    scoring::{self, BoardGrid, Point},
+    session::{self, GameSession},
+    sgf,
 };
 
-use reqwest::blocking::Client;
+use log::{debug, error, info, warn};
+
+use crate::{
+    board::Board,
+    crash,
+    logging::LogConsole,
+    settings_screen::{preferences, SettingsScreen, PREFERENCES_GROUP},
+    util::{get_format_time, get_unix_time_ms, globalize_path},
+};
 
-const HOST: &str = "http://localhost:8080";
+const SAVED_GAMES_PATH: &str = "user://games";
 
 #[derive(GodotClass)]
 #[class(base=Node2D)]
 pub struct MasterScene {
-    session: Option<UserSessionDto>,
-    token: Option<UserTokenDto>,
-    session_request: Option<UserSessionRequestDto>,
-    user_color: Option<bool>,
-    client: Client,
+    online: GameSession,
+    chat: ChatHistory,
+    /// Positions of the online game for takebacks
+    positions: PositionHistory,
+    /// Move number and mover of the last game state
+    move_number: usize,
+    user_to_move: bool,
+    undo: Option<UndoRequestDto>,
+    /// Free handicap stones black still has to place before white's first move
+    handicap_left: usize,
+    phase: GamePhase,
+    board: BoardGrid,
+    dead_stones: HashSet<Point>,
+    /// What the `Board` node shows, only the changes are sent to it
+    shown_board: BoardGrid,
+    shown_territory: HashMap<Point, bool>,
+    shown_dead: HashSet<Point>,
+    clock: Option<GameClock>,
+    last_clock_tick: Option<Instant>,
+    timeout_claimed: bool,
+    reconnect_attempts: u32,
+    /// The in-process game of the offline hot-seat mode, no server requests are sent
+    local_game: Option<GoGame>,
+    ai: Option<AiPlayer>,
+    /// External GTP engine of the offline game, used for hints and as the opponent
+    engine: Option<GtpBridge>,
+    /// Color the engine plays, `None` when it only gives hints
+    engine_color: Option<bool>,
+    /// Result of the offline game when it didn't end by score
+    local_result: Option<GameResultDto>,
+    /// The game record shown in the review mode, board clicks create variations
+    review: Option<GameTree>,
+    /// Point shown by the first click when moves need a confirmation
+    pending_move: Option<Point>,
+    settings_screen: Option<Gd<SettingsScreen>>,
+    api: ApiClient,
     base: Base<Node2D>,
 }
 
@@ -33,26 +90,27 @@ impl MasterScene {
     #[signal]
     fn put_stone(row: i32, col: i32, color: bool);
 
+    /// Territory owner of the point in the scoring phase: "black", "white" or "none"
+    #[signal]
+    fn mark_territory(row: i32, col: i32, owner: GString);
+
+    #[signal]
+    fn mark_dead(row: i32, col: i32, dead: bool);
+
+    /// Engine suggestion for the next move, a negative row clears it
+    #[signal]
+    fn show_hint(row: i32, col: i32, color: bool);
+
     #[func]
     fn on_game_start(&mut self) {
-        if self.session.is_some() {
-            match self
-                .client
-                .get(format!(
-                    "{}/{}/{}",
-                    HOST,
-                    "session",
-                    self.session.clone().unwrap().session_id
-                ))
-                .header("Content-Type", "application/json")
-                .send()
-            {
-                Ok(response) => {
-                    let us =
-                        serde_json::from_str::<UserSessionDto>(response.text().unwrap().trim());
-                    self.session.as_mut().unwrap().user2 = us.unwrap().user2;
-                    godot_print!("Get User Session by Id sent");
-                    if self.session.as_mut().unwrap().user2.is_some() {
+        if let Some(session_id) = self.online.session_id() {
+            match self.api.session(&session_id) {
+                Ok(us) => {
+                    let session = self.online.session.as_mut().unwrap();
+                    session.user2 = us.user2;
+                    info!("Get User Session by Id sent");
+                    if session.user2.is_some() {
+                        self.refresh_invite();
                         let mut game_state_timer =
                             self.base().get_node_as::<Timer>("GameStateTimer");
                         game_state_timer.start();
@@ -61,30 +119,627 @@ impl MasterScene {
                         game_start_timer.stop();
                     }
                 }
-                Err(e) => godot_error!("Error: {:?}", e),
+                Err(e) => error!("Error: {:?}", e),
             };
         }
     }
 
     #[func]
     fn on_user_step(&mut self, row: i32, col: i32) {
+        if self.online.spectator {
+            return;
+        }
+        if self.review.is_some() {
+            if row >= 0 && col >= 0 {
+                self.review_play(Some((row as usize, col as usize)));
+            }
+            return;
+        }
+        match self.phase {
+            GamePhase::Play => {}
+            GamePhase::Scoring => return self.toggle_dead_group(row, col),
+            GamePhase::Finished => return,
+        }
+        if self.local_game.is_some() {
+            if row < 0 || col < 0 || !self.is_local_user_turn() {
+                return;
+            }
+            let game = self.local_game.as_mut().unwrap();
+            let point = (row as usize, col as usize);
+            if self.handicap_left > 0 {
+                match game.place_handicap(point) {
+                    Ok(_) => self.handicap_left -= 1,
+                    Err(e) => warn!("Illegal handicap stone: {}", e),
+                }
+                if self.handicap_left == 0 {
+                    game.start_after_handicap();
+                }
+                return self.after_local_move();
+            }
+            let color = game.to_move();
+            if !self.confirm_move(point, color) {
+                return;
+            }
+            let game = self.local_game.as_mut().unwrap();
+            if let Err(e) = game.play(point) {
+                warn!("Illegal move: {}", e);
+            }
+            return self.after_local_move();
+        }
+        if row >= 0 && col >= 0 {
+            let color = self.online.user_color.unwrap_or(true);
+            if !self.confirm_move((row as usize, col as usize), color) {
+                return;
+            }
+        }
         let step = StepDto { row, col };
-        let session = UserSessionStepDto {
-            session_id: self.get_session_id().unwrap(),
-            user_id: self.get_user_id(),
-        };
+        let session = self.online.session_step();
         let user_step_request = UserStepRequestDto { session, step };
-        let body_str = serde_json::to_string(&user_step_request).unwrap_or("{}".to_string());
-        match self
-            .client
-            .patch(format!("{}/{}", HOST, "game/state"))
-            .body(body_str)
-            .header("Content-Type", "application/json")
-            .send()
+        match self.api.play(&user_step_request) {
+            Ok(_) => info!("Step was sent"),
+            Err(e) => error!("Error: {:?}", e),
+        };
+    }
+
+    /// Copy the private session invite link to the clipboard
+    #[func]
+    fn on_copy_invite(&mut self) {
+        if let Some(code) = self
+            .online
+            .session
+            .as_ref()
+            .and_then(|s| s.join_code.clone())
+        {
+            DisplayServer::singleton().clipboard_set(&lobby::invite_link(&code, None));
+        }
+    }
+
+    /// Count down the running clock between game state updates
+    #[func]
+    fn on_clock_tick(&mut self) {
+        let now = Instant::now();
+        let elapsed_ms = self
+            .last_clock_tick
+            .map(|t| now.duration_since(t).as_millis() as i64)
+            .unwrap_or(0);
+        self.last_clock_tick = Some(now);
+        let clock = match self.clock.as_mut() {
+            Some(clock) => clock,
+            None => return,
+        };
+        if self.phase == GamePhase::Play {
+            clock.tick(elapsed_ms);
+        }
+        let black_text = clock.black.format(&clock.control);
+        let white_text = clock.white.format(&clock.control);
+        let flagged = clock.flagged();
+        let game_info = self
+            .base()
+            .get_node_as::<Control>("GameInfoControl")
+            .get_node_as::<CanvasLayer>("GameInfo");
+        game_info
+            .get_node_as::<Label>("BlackClockLabel")
+            .set_text(&black_text);
+        game_info
+            .get_node_as::<Label>("WhiteClockLabel")
+            .set_text(&white_text);
+        if flagged.is_some() && !self.timeout_claimed && !self.online.spectator {
+            self.claim_timeout();
+        }
+    }
+
+    /// Ask the server to check the flag, it decides about the time loss.
+    /// A failed claim is sent again on the next clock tick.
+    fn claim_timeout(&mut self) {
+        self.timeout_claimed = true;
+        match self.api.claim_timeout(&self.online.session_step()) {
+            Ok(_) => info!("Timeout claim was sent"),
+            Err(e) => {
+                error!("Error: {:?}", e);
+                self.timeout_claimed = false;
+            }
+        };
+    }
+
+    #[func]
+    fn on_pass(&mut self) {
+        if self.online.spectator {
+            return;
+        }
+        if self.review.is_some() {
+            return self.review_play(None);
+        }
+        if self.local_game.is_some() {
+            if self.phase != GamePhase::Play || !self.is_local_user_turn() {
+                return;
+            }
+            if self.local_game.as_mut().unwrap().pass().is_ok() {
+                self.after_local_move();
+            }
+            return;
+        }
+        match self.api.pass(&self.online.session_step()) {
+            Ok(_) => info!("Pass was sent"),
+            Err(e) => error!("Error: {:?}", e),
+        };
+    }
+
+    /// Accept the current dead stones selection. The game is finished by the server
+    /// once both players accepted the same selection.
+    #[func]
+    fn on_accept_score(&mut self) {
+        if self.phase != GamePhase::Scoring || self.online.spectator {
+            return;
+        }
+        if self.local_game.is_some() {
+            self.phase = GamePhase::Finished;
+            return self.refresh_local_game();
+        }
+        match self.api.accept_score(&self.online.session_step()) {
+            Ok(_) => info!("Score acceptance was sent"),
+            Err(e) => error!("Error: {:?}", e),
+        };
+    }
+
+    fn toggle_dead_group(&mut self, row: i32, col: i32) {
+        if row < 0 || col < 0 {
+            return;
+        }
+        scoring::toggle_dead_group(
+            &self.board,
+            &mut self.dead_stones,
+            (row as usize, col as usize),
+        );
+        self.refresh_scoring();
+        if self.local_game.is_some() {
+            return self.refresh_local_game();
+        }
+        let scoring_request = UserScoringRequestDto {
+            session: self.online.session_step(),
+            dead_stones: scoring::dead_to_steps(&self.dead_stones),
+        };
+        match self.api.mark_dead_stones(&scoring_request) {
+            Ok(_) => info!("Dead stones were sent"),
+            Err(e) => error!("Error: {:?}", e),
+        };
+    }
+
+    /// Take back the last move of the hot-seat game.
+    /// Against the computer the moves are taken back until it's the user's turn.
+    /// Online the opponent is asked to accept the takeback.
+    #[func]
+    fn on_undo(&mut self) {
+        if self.local_game.is_none() && self.review.is_none() {
+            return self.request_undo();
+        }
+        let engine_busy = self
+            .engine
+            .as_ref()
+            .is_some_and(|e| e.pending_job().is_some());
+        if engine_busy || self.is_computer_thinking() {
+            return;
+        }
+        let ai_color = self.computer_color();
+        let game = match self.local_game.as_mut() {
+            Some(game) => game,
+            None => return,
+        };
+        if self.phase == GamePhase::Finished || game.undo().is_none() {
+            return;
+        }
+        let mut replay_opening = false;
+        // Against the computer two plies go back: the undo above took the computer's answer,
+        // this one takes the user's move. Without a user's move it was the computer's opening.
+        if Some(game.to_move()) == ai_color {
+            let users_move = game.undo();
+            replay_opening = users_move.is_none();
+        }
+        self.phase = GamePhase::Play;
+        self.dead_stones.clear();
+        self.refresh_local_game();
+        self.clear_scoring();
+        if replay_opening {
+            // The computer moved first, let it replay the opening move
+            self.after_local_move();
+        }
+    }
+
+    fn request_undo(&mut self) {
+        if self.online.spectator || self.phase != GamePhase::Play || self.online.session.is_none() {
+            return;
+        }
+        if self
+            .undo
+            .as_ref()
+            .is_some_and(|u| u.status == UndoStatus::Pending)
         {
-            Ok(_) => godot_print!("Step was sent"),
-            Err(e) => godot_error!("Error: {:?}", e),
+            return;
+        }
+        let move_number = match history::undo_target(self.move_number, self.user_to_move) {
+            Some(move_number) => move_number,
+            None => return,
+        };
+        let undo_request = UserUndoRequestDto {
+            session: self.online.session_step(),
+            move_number,
+        };
+        match self.api.request_undo(&undo_request) {
+            Ok(_) => {
+                info!("Undo request was sent");
+                self.set_phase_text("Waiting for the opponent to accept the takeback...");
+            }
+            Err(e) => error!("Error: {:?}", e),
+        };
+    }
+
+    #[func]
+    fn on_undo_accept(&mut self) {
+        self.answer_undo(true);
+    }
+
+    #[func]
+    fn on_undo_decline(&mut self) {
+        self.answer_undo(false);
+    }
+
+    /// Answer the opponent's takeback, on accept the previous position is shown
+    /// right away without waiting for the next game state
+    fn answer_undo(&mut self, accept: bool) {
+        let undo = match self.undo.take() {
+            Some(undo) if undo.status == UndoStatus::Pending => undo,
+            _ => return,
+        };
+        self.set_undo_dialog_visible(false);
+        let answer = UserUndoAnswerDto {
+            session: self.online.session_step(),
+            accept,
+        };
+        match self.api.answer_undo(&answer) {
+            Ok(_) => info!("Undo answer was sent"),
+            Err(e) => return error!("Error: {:?}", e),
+        };
+        if !accept {
+            return;
+        }
+        if let Some((board, score)) = self.positions.get(undo.move_number).cloned() {
+            self.board = board.clone();
+            self.refresh_board(&board);
+            self.refresh_score(&score);
+        }
+    }
+
+    /// Show the takeback dialog to the opponent and the answer to the requester
+    fn refresh_undo(&mut self, game_state: &GameStateDto) {
+        let user_id = self.online.user_id();
+        self.move_number = game_state.game_state.move_number;
+        self.user_to_move = game_state.active_user_id == user_id;
+        self.positions.record(
+            self.move_number,
+            &game_state.game_state.board,
+            &game_state.game_state.score,
+        );
+        self.undo = game_state.game_state.undo.clone();
+        let (status, own) = match self.undo.as_ref() {
+            Some(undo) if !self.online.spectator => (undo.status, undo.requested_by == user_id),
+            _ => return self.set_undo_dialog_visible(false),
+        };
+        self.set_undo_dialog_visible(status == UndoStatus::Pending && !own);
+        match (status, own) {
+            (UndoStatus::Pending, true) => {
+                self.set_phase_text("Waiting for the opponent to accept the takeback...")
+            }
+            (UndoStatus::Declined, true) => self.set_phase_text("The takeback was declined"),
+            _ => {}
+        }
+    }
+
+    fn set_undo_dialog_visible(&mut self, visible: bool) {
+        let mut undo_layer = self.base().get_node_as::<CanvasLayer>("UndoLayer");
+        if visible && !undo_layer.is_visible() {
+            undo_layer
+                .get_node_as::<Label>("UndoLabel")
+                .set_text("Your opponent asks to take back the last move");
+        }
+        undo_layer.set_visible(visible);
+    }
+
+    /// Save the hot-seat game to `user://games` as SGF
+    #[func]
+    fn on_save_sgf(&mut self) {
+        let game = match self.local_game.as_ref() {
+            Some(game) => game,
+            None => return,
+        };
+        let dir = globalize_path(SAVED_GAMES_PATH);
+        let path = dir.join(format!("{}.sgf", get_format_time(Some("%Y%m%d_%H%M%S"))));
+        match std::fs::create_dir_all(&dir).and_then(|_| std::fs::write(&path, sgf::to_sgf(game))) {
+            Ok(_) => info!("Game saved to {}", path.display()),
+            Err(e) => error!("Failed to save the game: {}", e),
+        }
+    }
+
+    /// Start an offline game for two players at the same screen
+    /// Handicap stones are put on the star points or clicked by black one by one
+    pub fn init_local_game(&mut self, settings: &GameSettingsDto) {
+        let size = settings.board_size as usize;
+        let stones = settings.handicap.max(0) as usize;
+        let komi = handicap::handicap_komi(settings.komi, stones);
+        let fixed_points = handicap::fixed_handicap_points(size, stones);
+        self.local_game = Some(match (&fixed_points, settings.handicap_placement) {
+            (Some(points), HandicapPlacement::Fixed) => GoGame::with_handicap(size, komi, points),
+            _ => GoGame::new(size, komi),
+        });
+        self.handicap_left = match (fixed_points, settings.handicap_placement) {
+            (Some(_), HandicapPlacement::Free) => stones,
+            _ => 0,
+        };
+    }
+
+    /// Start an offline game against the computer, the user plays the preferred color
+    pub fn init_ai_game(&mut self, settings: &GameSettingsDto, difficulty: Difficulty) {
+        self.init_local_game(settings);
+        let user_color = Self::local_user_color(settings);
+        self.ai = Some(AiPlayer::new(!user_color, difficulty));
+        self.place_computer_handicap(settings);
+    }
+
+    /// Start an offline game against an external GTP engine, the user plays the preferred color
+    pub fn init_engine_game(&mut self, settings: &GameSettingsDto, engine: GtpBridge) {
+        self.init_local_game(settings);
+        let user_color = Self::local_user_color(settings);
+        self.engine = Some(engine);
+        self.engine_color = Some(!user_color);
+        self.place_computer_handicap(settings);
+    }
+
+    /// The computer playing black doesn't click, its free handicap goes to the star points
+    fn place_computer_handicap(&mut self, settings: &GameSettingsDto) {
+        if self.handicap_left == 0 || self.computer_color() != Some(true) {
+            return;
+        }
+        let game = self.local_game.as_ref().unwrap();
+        let points =
+            handicap::fixed_handicap_points(game.size(), self.handicap_left).unwrap_or_default();
+        let komi = handicap::handicap_komi(settings.komi, points.len());
+        self.local_game = Some(GoGame::with_handicap(game.size(), komi, &points));
+        self.handicap_left = 0;
+    }
+
+    /// Let the external engine suggest moves in the offline game
+    pub fn set_hint_engine(&mut self, engine: GtpBridge) {
+        self.engine = Some(engine);
+    }
+
+    fn local_user_color(settings: &GameSettingsDto) -> bool {
+        match settings.color {
+            ColorPreference::Black => true,
+            ColorPreference::White => false,
+            ColorPreference::Nigiri => get_unix_time_ms() % 2 == 0,
+        }
+    }
+
+    /// Apply the computer move once the background search or the engine is done
+    #[func]
+    fn on_ai_tick(&mut self) {
+        if let Some(reply) = self.engine.as_mut().and_then(|engine| engine.poll()) {
+            return match reply.job {
+                EngineJob::Play => match reply.result {
+                    Ok(EngineMove::Play(engine_move)) => self.apply_computer_move(engine_move),
+                    Ok(EngineMove::Resign { color }) => {
+                        self.local_result = Some(GameResultDto {
+                            winner: Some(!color),
+                            reason: GameResultReason::Resign,
+                        });
+                        self.phase = GamePhase::Finished;
+                        self.refresh_local_game();
+                    }
+                    Err(e) => {
+                        error!("Engine failed to move: {}", e);
+                        let color = self
+                            .local_game
+                            .as_ref()
+                            .map(|g| g.to_move())
+                            .unwrap_or(true);
+                        self.apply_computer_move(Move::Pass { color });
+                    }
+                },
+                EngineJob::Hint => self.show_engine_hint(reply.move_number, reply.result),
+            };
+        }
+        if let Some(ai_move) = self.ai.as_mut().and_then(|ai| ai.poll()) {
+            self.apply_computer_move(ai_move);
+        }
+    }
+
+    fn apply_computer_move(&mut self, computer_move: Move) {
+        let game = match self.local_game.as_mut() {
+            Some(game) => game,
+            None => return,
+        };
+        let result = match computer_move {
+            Move::Play { point, .. } => game.play(point).map(|_| ()),
+            Move::Pass { .. } => game.pass(),
+        };
+        if let Err(e) = result {
+            error!("Computer move {:?} is illegal: {}", computer_move, e);
+            let _ = game.pass();
+        }
+        self.after_local_move();
+    }
+
+    /// Ask the external engine for the best move of the side to move
+    #[func]
+    fn on_hint(&mut self) {
+        if self.phase != GamePhase::Play || self.handicap_left > 0 || !self.is_local_user_turn() {
+            return;
+        }
+        let (engine, game) = match (self.engine.as_mut(), self.local_game.as_ref()) {
+            (Some(engine), Some(game)) => (engine, game),
+            _ => return,
+        };
+        engine.request(EngineJob::Hint, game);
+        let text = format!("{} is looking for a move...", engine.name);
+        self.set_phase_text(&text);
+    }
+
+    /// Show the hint unless the position has changed since it was requested
+    fn show_engine_hint(
+        &mut self,
+        move_number: usize,
+        result: Result<EngineMove, go_gtp::engine::GtpError>,
+    ) {
+        let current = self.local_game.as_ref().map(|g| g.moves().len());
+        if current != Some(move_number) {
+            return;
+        }
+        self.refresh_local_game();
+        match result {
+            Ok(EngineMove::Play(Move::Play { point, color })) => {
+                self.emit_hint(point.0 as i32, point.1 as i32, color)
+            }
+            Ok(EngineMove::Play(Move::Pass { .. })) => self.set_phase_text("Hint: pass"),
+            Ok(EngineMove::Resign { .. }) => self.set_phase_text("Hint: resign"),
+            Err(e) => error!("Engine failed to give a hint: {}", e),
+        }
+    }
+
+    /// With the move confirmation on, the first click only shows the stone.
+    /// True when the move should be played.
+    fn confirm_move(&mut self, point: Point, color: bool) -> bool {
+        if !preferences().confirm_moves {
+            return true;
+        }
+        if self.pending_move.take() == Some(point) {
+            self.emit_hint(-1, -1, color);
+            return true;
+        }
+        self.pending_move = Some(point);
+        self.emit_hint(point.0 as i32, point.1 as i32, color);
+        false
+    }
+
+    #[func]
+    fn on_settings_open(&mut self) {
+        if let Some(settings_screen) = self.settings_screen.as_mut() {
+            settings_screen.bind_mut().open();
+        }
+    }
+
+    /// Called through `PREFERENCES_GROUP` after the settings are saved
+    #[func]
+    fn on_preferences_changed(&mut self) {
+        let preferences = preferences();
+        if self.api.host() != preferences.server_url.trim_end_matches('/') {
+            self.api = ApiClient::new(&preferences.server_url);
+        }
+        if !preferences.confirm_moves {
+            self.pending_move = None;
+        }
+        self.apply_poll_interval();
+    }
+
+    fn apply_poll_interval(&mut self) {
+        let interval = preferences().poll_interval_secs();
+        self.base()
+            .get_node_as::<Timer>("GameStateTimer")
+            .set_wait_time(interval);
+    }
+
+    fn emit_hint(&mut self, row: i32, col: i32, color: bool) {
+        self.base_mut().emit_signal(
+            "show_hint",
+            &[Variant::from(row), Variant::from(col), Variant::from(color)],
+        );
+    }
+
+    /// Color of the built-in AI or of the engine opponent
+    fn computer_color(&self) -> Option<bool> {
+        self.ai.as_ref().map(|ai| ai.color).or(self.engine_color)
+    }
+
+    fn is_computer_thinking(&self) -> bool {
+        self.ai.as_ref().is_some_and(|ai| ai.is_thinking())
+            || self
+                .engine
+                .as_ref()
+                .is_some_and(|e| e.pending_job() == Some(EngineJob::Play))
+    }
+
+    fn is_local_user_turn(&self) -> bool {
+        match (self.computer_color(), self.local_game.as_ref()) {
+            (Some(color), Some(game)) => !self.is_computer_thinking() && game.to_move() != color,
+            _ => true,
+        }
+    }
+
+    /// Move to the scoring phase after two passes or let the computer answer
+    fn after_local_move(&mut self) {
+        let game = match self.local_game.as_ref() {
+            Some(game) => game.clone(),
+            None => return,
+        };
+        if game.is_over() {
+            self.phase = GamePhase::Scoring;
+            self.dead_stones.clear();
+        } else if let Some(ai) = self.ai.as_mut() {
+            if game.to_move() == ai.color {
+                ai.start_thinking(&game);
+            }
+        } else if let (Some(engine), Some(color)) = (self.engine.as_mut(), self.engine_color) {
+            if game.to_move() == color {
+                engine.request(EngineJob::Play, &game);
+            }
+        }
+        self.refresh_local_game();
+    }
+
+    fn refresh_local_game(&mut self) {
+        let game = match self.local_game.as_ref() {
+            Some(game) => game.clone(),
+            None => return,
         };
+        self.board = game.board().clone();
+        self.emit_hint(-1, -1, true);
+        self.refresh_board(game.board());
+        self.refresh_score(game.captures());
+        let phase_text = match self.phase {
+            GamePhase::Play if self.is_computer_thinking() => match self.engine.as_ref() {
+                Some(engine) if self.ai.is_none() => format!("{} is thinking...", engine.name),
+                _ => "Computer is thinking...".to_string(),
+            },
+            GamePhase::Play if self.handicap_left > 0 => {
+                format!("Black places handicap stones: {} left", self.handicap_left)
+            }
+            GamePhase::Play => {
+                format!("{} to move", if game.to_move() { "Black" } else { "White" })
+            }
+            GamePhase::Scoring => "Mark dead stones and accept the score".to_string(),
+            GamePhase::Finished if self.local_result.is_some() => {
+                format!("Game over. {}", self.local_result.as_ref().unwrap())
+            }
+            GamePhase::Finished => {
+                let (black, white) = game.score(&self.dead_stones);
+                let result = GameResultDto {
+                    winner: match black.partial_cmp(&white) {
+                        Some(std::cmp::Ordering::Greater) => Some(true),
+                        Some(std::cmp::Ordering::Less) => Some(false),
+                        _ => None,
+                    },
+                    reason: GameResultReason::Score,
+                };
+                format!("Game over. {} ({} : {})", result, black, white)
+            }
+        };
+        self.set_phase_text(&phase_text);
+    }
+
+    fn set_phase_text(&mut self, text: &str) {
+        let mut phase_label = self
+            .base()
+            .get_node_as::<Control>("GameInfoControl")
+            .get_node_as::<CanvasLayer>("GameInfo")
+            .get_node_as::<Label>("PhaseLabel");
+        phase_label.set_text(text);
     }
 
     pub fn init_game_data(
@@ -92,69 +747,372 @@ impl MasterScene {
         user_session: Option<UserSessionDto>,
         token: Option<UserTokenDto>,
     ) {
-        godot_print!("Init session in main scene: Begin");
-        self.session = user_session;
-        self.token = token;
-        godot_print_rich!("{:?}", self.session);
+        info!("Init session in main scene: Begin");
+        self.online.session = user_session;
+        self.online.token = token;
+        crash::set_session_id(self.online.session_id());
+        self.timeout_claimed = false;
+        self.clock = self
+            .online
+            .session
+            .as_ref()
+            .and_then(|s| s.settings.as_ref())
+            .map(|s| s.time_control)
+            .filter(|tc| *tc != TimeControl::None)
+            .map(GameClock::new);
+        debug!("{:?}", self.online.session);
+        if self.online.spectator {
+            return;
+        }
+        if let (Some(session), Some(token)) =
+            (self.online.session.clone(), self.online.token.as_ref())
+        {
+            let active_game = ActiveGame {
+                session,
+                user_id: token.user_id,
+            };
+            if let Err(e) = active_game.save(&globalize_path(ACTIVE_GAME_PATH)) {
+                error!("Failed to save the active game: {}", e);
+            }
+        }
         // self.switch_scene(true);
-        godot_print!("Init session in main scene: Ok");
+        info!("Init session in main scene: Ok");
+    }
+
+    /// Watch a game of other players, see `spectator`
+    pub fn init_spectator(&mut self, session: UserSessionDto, token: Option<UserTokenDto>) {
+        self.online.spectator = true;
+        self.init_game_data(Some(session), token);
+    }
+
+    /// Show a game record loaded from an SGF file
+    pub fn init_review(&mut self, tree: GameTree) {
+        self.review = Some(tree);
+    }
+
+    /// Review the finished game: the hot-seat moves or the history from the server
+    #[func]
+    fn on_review(&mut self) {
+        if self.phase != GamePhase::Finished || self.review.is_some() {
+            return;
+        }
+        let tree = match self.local_game.as_ref() {
+            Some(game) => {
+                GameTree::from_moves(game.size(), game.komi(), game.handicap(), game.moves())
+            }
+            None => match self.request_game_record() {
+                Some(record) => GameTree::from_record(&record),
+                None => return,
+            },
+        };
+        self.review = Some(tree);
+        self.dead_stones.clear();
+        self.clear_scoring();
+        self.refresh_review();
+    }
+
+    fn request_game_record(&mut self) -> Option<GameRecordDto> {
+        let session_id = self.online.session_id()?;
+        match self.api.game_record(&session_id) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                error!("Error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    #[func]
+    fn on_review_back(&mut self) {
+        self.review_navigate(|tree| {
+            tree.back();
+        });
+    }
+
+    #[func]
+    fn on_review_forward(&mut self) {
+        self.review_navigate(|tree| {
+            tree.forward();
+        });
+    }
+
+    #[func]
+    fn on_review_start(&mut self) {
+        self.review_navigate(GameTree::to_start);
     }
 
-    fn get_user_id(&mut self) -> i64 {
-        self.token.clone().map(|t| t.user_id).unwrap_or(-1) //2
+    #[func]
+    fn on_review_end(&mut self) {
+        self.review_navigate(GameTree::to_end);
     }
 
-    fn get_session_id(&mut self) -> Option<String> {
-        self.session.clone().map(|t| t.session_id) //Some(String::from("8e2db1b1-6b1a-48ae-b44b-10fe5f47ffcd"))
+    /// Go to the move of the shown line, e.g. from the move slider
+    #[func]
+    fn on_review_jump(&mut self, move_number: i32) {
+        self.review_navigate(|tree| {
+            tree.jump(move_number.max(0) as usize);
+        });
     }
 
-    fn get_nick(&self) -> String {
-        self.token.as_ref().unwrap().login.clone()
+    /// Show the next alternative to the current move
+    #[func]
+    fn on_review_next_variation(&mut self) {
+        self.review_navigate(|tree| {
+            tree.next_variation();
+        });
     }
 
-    fn get_opponent_nick(&self) -> String {
-        let session = &self.session;
-        match self.token.clone().unwrap().login == session.clone().unwrap().user1.login {
-            true => session
-                .clone()
-                .unwrap()
-                .user2
-                .map(|us| us.login)
-                .unwrap_or("Wait...".to_string()),
-            false => session.clone().unwrap().user1.login,
+    /// Keep the text of `CommentInput` as the comment of the current move
+    #[func]
+    fn on_review_comment_changed(&mut self) {
+        let comment = self.get_comment_input().get_text().to_string();
+        if let Some(tree) = self.review.as_mut() {
+            tree.set_comment(&comment);
         }
     }
 
+    /// Save the reviewed game with variations and comments to `user://games`
+    #[func]
+    fn on_review_save(&mut self) {
+        let tree = match self.review.as_ref() {
+            Some(tree) => tree,
+            None => return,
+        };
+        let dir = globalize_path(SAVED_GAMES_PATH);
+        let path = dir.join(format!(
+            "review_{}.sgf",
+            get_format_time(Some("%Y%m%d_%H%M%S"))
+        ));
+        match std::fs::create_dir_all(&dir).and_then(|_| std::fs::write(&path, tree.to_sgf())) {
+            Ok(_) => info!("Review saved to {}", path.display()),
+            Err(e) => error!("Failed to save the review: {}", e),
+        }
+    }
+
+    fn review_navigate(&mut self, navigate: impl FnOnce(&mut GameTree)) {
+        if let Some(tree) = self.review.as_mut() {
+            navigate(tree);
+            self.refresh_review();
+        }
+    }
+
+    /// Play the point for the side to move, a pass for `None`, as a variation
+    fn review_play(&mut self, point: Option<Point>) {
+        let tree = match self.review.as_mut() {
+            Some(tree) => tree,
+            None => return,
+        };
+        let color = tree.game().to_move();
+        let m = match point {
+            Some(point) => Move::Play { point, color },
+            None => Move::Pass { color },
+        };
+        if let Err(e) = tree.play(m) {
+            warn!("Illegal move: {}", e);
+        }
+        self.refresh_review();
+    }
+
+    fn refresh_review(&mut self) {
+        let tree = match self.review.as_ref() {
+            Some(tree) => tree.clone(),
+            None => return,
+        };
+        let game = tree.game();
+        self.board = game.board().clone();
+        self.refresh_board(game.board());
+        self.refresh_score(game.captures());
+        let (variation, variations) = tree.variation();
+        let mut text = format!("Move {}/{}", tree.move_number(), tree.line_length());
+        if variations > 1 {
+            text.push_str(&format!(", variation {}/{}", variation + 1, variations));
+        }
+        self.set_phase_text(&text);
+        let mut comment_input = self.get_comment_input();
+        if comment_input.get_text().to_string() != tree.current().comment {
+            comment_input.set_text(&tree.current().comment);
+        }
+    }
+
+    fn get_comment_input(&self) -> Gd<TextEdit> {
+        self.base()
+            .get_node_as::<Control>("GameInfoControl")
+            .get_node_as::<CanvasLayer>("GameInfo")
+            .get_node_as::<TextEdit>("CommentInput")
+    }
+
+    /// Board size of the session, 19 when the server didn't send settings
+    pub fn get_board_size(&self) -> i32 {
+        if let Some(tree) = self.review.as_ref() {
+            return tree.size() as i32;
+        }
+        if let Some(game) = self.local_game.as_ref() {
+            return game.size() as i32;
+        }
+        self.online
+            .session
+            .as_ref()
+            .and_then(|s| s.settings.as_ref())
+            .map(|s| s.board_size)
+            .unwrap_or(19)
+    }
+
     #[func]
     pub fn on_game_state_tick(&mut self) {
-        // godot_print!("Send game state request: Begin");
-        let body_str = serde_json::to_string(&self.session_request).unwrap_or("{}".to_string());
-        match self
-            .client
-            .post(format!("{}/{}", HOST, "game/state"))
-            .body(body_str)
-            .header("Content-Type", "application/json")
-            .send()
-        {
-            Ok(response) => {
-                let game_state = serde_json::from_str::<GameStateDto>(
-                    response.text().unwrap_or("{}".to_string()).trim(),
-                )
-                .unwrap();
-                self.refresh_colors(&game_state.game_state.colors);
+        if !self.request_game_state() {
+            return self.schedule_reconnect();
+        }
+        self.request_chat();
+    }
+
+    /// Send the text of `ChatInput` to the players channel, spectators write to their own
+    #[func]
+    fn on_chat_send(&mut self) {
+        let mut chat_input = self.get_chat_node::<LineEdit>("ChatInput");
+        let text = chat_input.get_text().to_string().trim().to_string();
+        if text.is_empty() || self.online.session.is_none() {
+            return;
+        }
+        let chat_request = ChatRequestDto {
+            session: self.online.session_step(),
+            text,
+            channel: if self.online.spectator {
+                ChatChannel::Spectators
+            } else {
+                ChatChannel::Players
+            },
+            sent_at: get_format_time(Some("%T")),
+        };
+        match self.api.send_chat(&chat_request) {
+            Ok(_) => {
+                chat_input.clear();
+                self.request_chat();
+            }
+            Err(e) => error!("Error: {:?}", e),
+        };
+    }
+
+    /// Mute or unmute the author of the message selected in `ChatList`
+    #[func]
+    fn on_chat_mute(&mut self) {
+        if self.online.session.is_none() {
+            return;
+        }
+        let mut chat_list = self.get_chat_node::<ItemList>("ChatList");
+        let login = match chat_list.get_selected_items().as_slice().first() {
+            Some(index) => chat_list.get_item_metadata(*index).to_string(),
+            None => return,
+        };
+        if login == self.online.nick() {
+            return;
+        }
+        let muted = self.chat.toggle_mute(&login);
+        info!("{} is {}", login, if muted { "muted" } else { "unmuted" });
+        self.refresh_chat();
+    }
+
+    /// Fetch the messages after the last known one
+    fn request_chat(&mut self) {
+        let session_id = match self.online.session_id() {
+            Some(session_id) => session_id,
+            None => return,
+        };
+        let after = self.chat.last_message_id().unwrap_or(0);
+        let messages = match self.api.chat(&session_id, after) {
+            Ok(messages) => messages,
+            Err(e) => return error!("Error: {:?}", e),
+        };
+        if self.chat.merge(messages) {
+            self.refresh_chat();
+        }
+    }
+
+    fn refresh_chat(&mut self) {
+        let mut chat_list = self.get_chat_node::<ItemList>("ChatList");
+        chat_list.clear();
+        for message in self.chat.visible(self.online.spectator) {
+            let index = chat_list.add_item(&chat::format_message(message));
+            chat_list.set_item_metadata(index, &Variant::from(message.login.as_str()));
+        }
+        // Keep the latest message in view
+        if let Some(mut scroll_bar) = chat_list.get_v_scroll_bar() {
+            let max = scroll_bar.get_max();
+            scroll_bar.set_value(max);
+        }
+    }
+
+    fn get_chat_node<T: Inherits<godot::classes::Node>>(&self, name: &str) -> Gd<T> {
+        self.base()
+            .get_node_as::<Control>("GameInfoControl")
+            .get_node_as::<CanvasLayer>("ChatPanel")
+            .get_node_as::<T>(name)
+    }
+
+    /// Retry the game state request after a network drop, see `schedule_reconnect`
+    #[func]
+    fn on_reconnect_tick(&mut self) {
+        if !self.request_game_state() {
+            self.schedule_reconnect();
+            return;
+        }
+        info!("Reconnected after {} attempts", self.reconnect_attempts);
+        self.reconnect_attempts = 0;
+        self.set_reconnect_text("");
+        let mut game_state_timer = self.base().get_node_as::<Timer>("GameStateTimer");
+        game_state_timer.start();
+    }
+
+    /// Pause the game state polling and retry with an exponential backoff
+    fn schedule_reconnect(&mut self) {
+        let delay = resume::reconnect_delay_secs(self.reconnect_attempts);
+        self.reconnect_attempts += 1;
+        let mut game_state_timer = self.base().get_node_as::<Timer>("GameStateTimer");
+        game_state_timer.stop();
+        let mut reconnect_timer = self.base().get_node_as::<Timer>("ReconnectTimer");
+        reconnect_timer.set_one_shot(true);
+        reconnect_timer.start_ex().time_sec(delay).done();
+        self.set_reconnect_text(&format!("Connection lost. Reconnecting in {}s...", delay));
+    }
+
+    fn set_reconnect_text(&mut self, text: &str) {
+        let mut reconnect_label = self
+            .base()
+            .get_node_as::<Control>("GameInfoControl")
+            .get_node_as::<CanvasLayer>("GameInfo")
+            .get_node_as::<Label>("ReconnectLabel");
+        reconnect_label.set_text(text);
+    }
+
+    /// Fetch and render the game state. Returns false when the server is unreachable.
+    fn request_game_state(&mut self) -> bool {
+        // info!("Send game state request: Begin");
+        let request_time = Instant::now();
+        match self.api.game_state(&self.online.session_request()) {
+            Ok(game_state) => {
+                let rtt_ms = request_time.elapsed().as_millis() as i64;
+                if let (Some(clock), Some(clock_dto)) =
+                    (self.clock.as_mut(), game_state.game_state.clock.as_ref())
+                {
+                    clock.sync(clock_dto, get_unix_time_ms(), rtt_ms);
+                    // The server didn't end the game yet, a new flag is claimed again
+                    if clock.flagged().is_none() {
+                        self.timeout_claimed = false;
+                    }
+                }
+                self.online.set_colors(&game_state.game_state.colors);
                 self.refresh_time(get_format_time(Some("%T")));
+                self.refresh_board(&game_state.game_state.board);
+                self.refresh_phase(&game_state.game_state);
+                self.refresh_undo(&game_state);
                 self.refresh_score(&game_state.game_state.score);
-                self.refresh_board(&game_state.game_state);
+                true
             }
             Err(e) => {
-                godot_error!("Error: {:?}", e);
+                error!("Error: {:?}", e);
+                false
             }
         }
-        // godot_print!("{}:\tSend game state request: Ok", get_format_time(None));
-    }
-
-    fn refresh_colors(&mut self, colors: &HashMap<i64, bool>) {
-        self.user_color = Some(*colors.get(&self.get_user_id()).unwrap());
+        // info!("{}:\tSend game state request: Ok", get_format_time(None));
     }
 
     #[func]
@@ -167,6 +1125,145 @@ impl MasterScene {
         time_label.set_text(&game_state_refresh_time);
     }
 
+    /// Show the join code to the host of a private session until the opponent joins
+    fn refresh_invite(&mut self) {
+        let mut invite_label = self
+            .base()
+            .get_node_as::<Control>("GameInfoControl")
+            .get_node_as::<CanvasLayer>("GameInfo")
+            .get_node_as::<Label>("InviteLabel");
+        match self.online.session.as_ref() {
+            Some(session) if session.user2.is_none() && session.join_code.is_some() => {
+                invite_label.set_text(&format!(
+                    "Invite code: {}",
+                    session.join_code.clone().unwrap()
+                ));
+                invite_label.set_visible(true);
+            }
+            _ => invite_label.set_visible(false),
+        }
+    }
+
+    fn refresh_phase(&mut self, game_state: &GameState) {
+        let previous_phase = self.phase;
+        self.phase = game_state.phase;
+        self.board = game_state.board.clone();
+        let scoring = game_state.scoring.clone().unwrap_or_default();
+        if self.phase != GamePhase::Play {
+            self.dead_stones = scoring::dead_from_steps(&scoring.dead_stones);
+            self.refresh_scoring();
+        } else if previous_phase != GamePhase::Play {
+            // Scoring was resumed to play
+            self.dead_stones.clear();
+            self.clear_scoring();
+        }
+        let phase_text = match self.phase {
+            GamePhase::Play if self.online.spectator => format!(
+                "Watching {} vs {}",
+                self.online
+                    .player_logins
+                    .get(&true)
+                    .map(String::as_str)
+                    .unwrap_or("Black"),
+                self.online
+                    .player_logins
+                    .get(&false)
+                    .map(String::as_str)
+                    .unwrap_or("White")
+            ),
+            GamePhase::Play if game_state.handicap_left > 0 => format!(
+                "Black places handicap stones: {} left",
+                game_state.handicap_left
+            ),
+            GamePhase::Play => "".to_string(),
+            GamePhase::Scoring => {
+                format!("Mark dead stones. Accepted: {}/2", scoring.accepted.len())
+            }
+            GamePhase::Finished => game_state
+                .result
+                .as_ref()
+                .map(|r| format!("Game over. {}", r))
+                .unwrap_or("Game over".to_string()),
+        };
+        self.set_phase_text(&phase_text);
+        if self.phase == GamePhase::Finished && previous_phase != GamePhase::Finished {
+            let mut game_state_timer = self.base().get_node_as::<Timer>("GameStateTimer");
+            game_state_timer.stop();
+            let mut clock_timer = self.base().get_node_as::<Timer>("ClockTimer");
+            clock_timer.stop();
+            if self.online.spectator {
+                return;
+            }
+            if let Err(e) = ActiveGame::clear(&globalize_path(ACTIVE_GAME_PATH)) {
+                error!("Failed to clear the active game: {}", e);
+            }
+        }
+    }
+
+    /// Re-render dead stones and territory markers from the local selection
+    fn refresh_scoring(&mut self) {
+        let owners = scoring::territory(&self.board, &self.dead_stones);
+        let territory = owners
+            .iter()
+            .enumerate()
+            .flat_map(|(row, owners)| {
+                owners
+                    .iter()
+                    .enumerate()
+                    .filter_map(move |(col, owner)| owner.map(|owner| ((row, col), owner)))
+            })
+            .collect();
+        self.show_scoring(territory, self.dead_stones.clone());
+    }
+
+    /// Remove the territory and dead stone marks outside of the scoring phase
+    fn clear_scoring(&mut self) {
+        self.show_scoring(HashMap::new(), HashSet::new());
+    }
+
+    /// Send the marks that differ from the shown ones
+    fn show_scoring(&mut self, territory: HashMap<Point, bool>, dead: HashSet<Point>) {
+        let mut territory_changes: Vec<(Point, &str)> = self
+            .shown_territory
+            .keys()
+            .filter(|point| !territory.contains_key(point))
+            .map(|point| (*point, "none"))
+            .collect();
+        territory_changes.extend(
+            territory
+                .iter()
+                .filter(|(point, owner)| self.shown_territory.get(point) != Some(owner))
+                .map(|(point, owner)| (*point, if *owner { "black" } else { "white" })),
+        );
+        let dead_changes: Vec<(Point, bool)> = self
+            .shown_dead
+            .symmetric_difference(&dead)
+            .map(|point| (*point, dead.contains(point)))
+            .collect();
+        for ((row, col), owner) in territory_changes {
+            self.base_mut().emit_signal(
+                "mark_territory",
+                &[
+                    Variant::from(row as i32),
+                    Variant::from(col as i32),
+                    Variant::from(GString::from(owner)),
+                ],
+            );
+        }
+        for ((row, col), is_dead) in dead_changes {
+            self.base_mut().emit_signal(
+                "mark_dead",
+                &[
+                    Variant::from(row as i32),
+                    Variant::from(col as i32),
+                    Variant::from(is_dead),
+                ],
+            );
+        }
+        self.shown_territory = territory;
+        self.shown_dead = dead;
+    }
+
     fn refresh_score(&mut self, score: &GameScore) {
         let game_info = self
             .base()
@@ -178,36 +1275,45 @@ impl MasterScene {
         let mut white_score_label = game_info
             .get_node_as::<Label>("WhiteTitleLabel")
             .get_node_as::<Label>("WhiteScoreLabel");
-        let black_score_text = self.get_black_score_label_text(score.black);
-        let white_score_text = self.get_white_score_label_text(score.white);
+        let (black, white) = match self.phase {
+            _ if self.review.is_some() => (score.black, score.white),
+            GamePhase::Play => (score.black, score.white),
+            _ => {
+                let estimate = scoring::estimate(&self.board, &self.dead_stones);
+                (estimate.black(score.black), estimate.white(score.white))
+            }
+        };
+        let black_score_text = self.get_score_label_text(true, black);
+        let white_score_text = self.get_score_label_text(false, white);
         black_score_label.set_text(&black_score_text);
         white_score_label.set_text(&white_score_text);
     }
 
-    fn get_black_score_label_text(&mut self, score: i32) -> String {
-        match self.user_color {
-            Some(color) => match color {
-                true => format!("{}: {}", self.get_nick(), score),
-                false => format!("{}: {}", self.get_opponent_nick(), score),
-            },
-            None => format!("{}: {}", "Wait...", score),
-        }
-    }
-
-    fn get_white_score_label_text(&mut self, score: i32) -> String {
-        match self.user_color {
-            Some(color) => match color {
-                false => format!("{}: {}", self.get_nick(), score),
-                true => format!("{}: {}", self.get_opponent_nick(), score),
-            },
-            None => format!("{}: {}", "Wait...", score),
-        }
+    fn get_score_label_text(&self, color: bool, score: i32) -> String {
+        let player = match (self.local_game.as_ref(), self.review.as_ref()) {
+            (Some(_), _) => session::color_name(color).to_string(),
+            (None, Some(tree)) => tree
+                .player_name(color)
+                .unwrap_or(session::color_name(color))
+                .to_string(),
+            (None, None) => self.online.player_name(color),
+        };
+        session::score_label_text(&player, score)
     }
 
-    fn refresh_board(&mut self, game_state: &GameState) {
-        // let mut board = self.base().get_node_as::<Board>("Board");
-        for (row_num, row) in game_state.board.iter().enumerate() {
+    /// Send the points that differ from the shown board
+    fn refresh_board(&mut self, board: &BoardGrid) {
+        for (row_num, row) in board.iter().enumerate() {
             for (col_num, col) in row.iter().enumerate() {
+                let shown = self
+                    .shown_board
+                    .get(row_num)
+                    .and_then(|r| r.get(col_num))
+                    .copied()
+                    .flatten();
+                if shown == *col {
+                    continue;
+                }
                 let color = match col {
                     Some(b) => {
                         if *b {
@@ -228,32 +1334,79 @@ impl MasterScene {
                 );
             }
         }
+        self.shown_board = board.clone();
     }
 }
 
 #[godot_api]
 impl INode2D for MasterScene {
     fn init(base: Base<Self::Base>) -> Self {
-        let client = reqwest::blocking::Client::new();
         Self {
-            session: None,
-            token: None,
-            session_request: None,
-            user_color: None,
-            client,
+            online: GameSession::default(),
+            chat: ChatHistory::default(),
+            positions: PositionHistory::default(),
+            move_number: 0,
+            user_to_move: false,
+            undo: None,
+            handicap_left: 0,
+            phase: GamePhase::Play,
+            board: vec![],
+            dead_stones: HashSet::new(),
+            shown_board: vec![],
+            shown_territory: HashMap::new(),
+            shown_dead: HashSet::new(),
+            clock: None,
+            last_clock_tick: None,
+            timeout_claimed: false,
+            reconnect_attempts: 0,
+            local_game: None,
+            ai: None,
+            engine: None,
+            engine_color: None,
+            local_result: None,
+            review: None,
+            pending_move: None,
+            settings_screen: None,
+            api: ApiClient::new(&preferences().server_url),
             base,
         }
     }
 
     fn ready(&mut self) {
-        self.session_request = Some(UserSessionRequestDto {
-            user_id: self.get_user_id(),
-            session_id: self.get_session_id(),
-        });
+        let board = self.base().get_node_as::<Board>("Board");
+        self.base_mut()
+            .connect("mark_territory", &board.callable("on_mark_territory"));
+        self.base_mut()
+            .connect("mark_dead", &board.callable("on_mark_dead"));
+        self.base_mut()
+            .connect("show_hint", &board.callable("on_show_hint"));
+        crash::set_scene("Master");
+        LogConsole::attach(self.base().clone().upcast());
+        self.settings_screen = Some(SettingsScreen::attach(self.base().clone().upcast()));
+        self.base_mut().add_to_group(PREFERENCES_GROUP);
+        self.apply_poll_interval();
+        if self.review.is_some() {
+            self.refresh_review();
+            return;
+        }
+        if self.local_game.is_some() {
+            if self.ai.is_some() || self.engine.is_some() {
+                let mut ai_timer = self.base().get_node_as::<Timer>("AiTimer");
+                ai_timer.connect("timeout", &self.base().callable("on_ai_tick"));
+                ai_timer.start();
+            }
+            self.after_local_move();
+            return;
+        }
         let mut game_start_timer = self.base().get_node_as::<Timer>("GameStartTimer");
         game_start_timer.start();
-        // let board = self.base().get_node_as::<Board>("Board");
-        // self.base_mut()
-        //     .connect("put_stone", &board.callable("on_put_stone"));
+        self.refresh_invite();
+        let mut clock_timer = self.base().get_node_as::<Timer>("ClockTimer");
+        clock_timer.connect("timeout", &self.base().callable("on_clock_tick"));
+        if self.clock.is_some() {
+            clock_timer.start();
+        }
+        let mut reconnect_timer = self.base().get_node_as::<Timer>("ReconnectTimer");
+        reconnect_timer.connect("timeout", &self.base().callable("on_reconnect_tick"));
     }
 }
@@ -0,0 +1,329 @@
+use game_core::{
+    api::{ApiClient, ApiError},
+    dto::{
+        MatchPreferencesDto, MatchmakingRequestDto, MatchmakingStatus, MatchmakingTicketDto,
+        TimeControl, UserTokenDto,
+    },
+    matchmaking::{MATCH_BOARD_SIZES, MATCH_TIME_CONTROLS},
+    rating::{self, MAX_DAN, MAX_KYU},
+};
+use godot::{
+    classes::{
+        control::LayoutPreset, Button, CanvasLayer, Control, GridContainer, HBoxContainer,
+        ICanvasLayer, Label, OptionButton, PanelContainer, Timer, VBoxContainer,
+    },
+    prelude::*,
+};
+use log::{error, info, warn};
+
+use crate::{settings_screen::preferences, util::get_unix_time};
+
+struct MatchmakingControls {
+    board_size: Gd<OptionButton>,
+    time_control: Gd<OptionButton>,
+    min_rank: Gd<OptionButton>,
+    max_rank: Gd<OptionButton>,
+    find: Gd<Button>,
+    cancel: Gd<Button>,
+    status: Gd<Label>,
+    poll_timer: Gd<Timer>,
+}
+
+/// "Find game": the preferences, then a waiting indicator until the server pairs the user
+#[derive(GodotClass)]
+#[class(base=CanvasLayer)]
+pub struct MatchmakingScreen {
+    api: ApiClient,
+    token: Option<UserTokenDto>,
+    /// Unix time the user joined the queue, `None` when not searching
+    queued_at: Option<i64>,
+    controls: Option<MatchmakingControls>,
+    base: Base<CanvasLayer>,
+}
+
+fn time_control_name(time_control: &TimeControl) -> String {
+    match time_control {
+        TimeControl::None => "No clock".to_string(),
+        time_control => time_control.to_string(),
+    }
+}
+
+/// "Any" followed by the ranks from 30k to 9d
+fn rank_option() -> Gd<OptionButton> {
+    let mut option = OptionButton::new_alloc();
+    option.add_item("Any");
+    for rank_value in -MAX_KYU..MAX_DAN {
+        option.add_item(&rating::rank_name(rank_value));
+    }
+    option
+}
+
+/// Rank value of the item picked in `rank_option`, `None` for "Any"
+fn selected_rank(option: &Gd<OptionButton>) -> Option<i32> {
+    match option.get_selected() {
+        index if index > 0 => Some(index - 1 - MAX_KYU),
+        _ => None,
+    }
+}
+
+#[godot_api]
+impl MatchmakingScreen {
+    /// An opponent is found, the session is started by the server
+    #[signal]
+    fn matched(session_id: GString);
+
+    /// Add a hidden matchmaking screen to the node
+    pub fn attach(mut node: Gd<Node>) -> Gd<MatchmakingScreen> {
+        let matchmaking_screen = MatchmakingScreen::new_alloc();
+        node.add_child(&matchmaking_screen);
+        matchmaking_screen
+    }
+
+    /// Show the preferences, a search left running by the user is picked up again.
+    /// A match is only reported by the polling, the caller is still borrowed here.
+    pub fn open(&mut self, api: ApiClient, token: UserTokenDto) {
+        let queued_at = api
+            .matchmaking_ticket(&token)
+            .ok()
+            .filter(|ticket| ticket.status == MatchmakingStatus::Searching)
+            .map(|ticket| ticket.queued_at);
+        self.api = api;
+        self.token = Some(token);
+        self.base_mut().set_visible(true);
+        self.set_searching(queued_at);
+    }
+
+    #[func]
+    fn on_find(&mut self) {
+        let (token, controls) = match (self.token.clone(), self.controls.as_ref()) {
+            (Some(token), Some(controls)) => (token, controls),
+            _ => return,
+        };
+        let preferences = MatchPreferencesDto {
+            board_size: MATCH_BOARD_SIZES[controls.board_size.get_selected().max(0) as usize],
+            time_control: MATCH_TIME_CONTROLS[controls.time_control.get_selected().max(0) as usize],
+            min_rank: selected_rank(&controls.min_rank),
+            max_rank: selected_rank(&controls.max_rank),
+        };
+        if let (Some(min_rank), Some(max_rank)) = (preferences.min_rank, preferences.max_rank) {
+            if min_rank > max_rank {
+                if let Some(controls) = self.controls.as_mut() {
+                    controls
+                        .status
+                        .set_text("The weakest opponent can't be stronger than the strongest");
+                }
+                return;
+            }
+        }
+        info!("Find game: {:?}", preferences);
+        match self
+            .api
+            .find_game(&MatchmakingRequestDto { token, preferences })
+        {
+            Ok(ticket) => self.handle_ticket(ticket),
+            Err(e) => self.show_failure(e),
+        }
+    }
+
+    #[func]
+    fn on_poll(&mut self) {
+        let token = match self.token.clone() {
+            Some(token) if self.queued_at.is_some() => token,
+            _ => return,
+        };
+        match self.api.matchmaking_ticket(&token) {
+            Ok(ticket) => self.handle_ticket(ticket),
+            Err(e) => self.show_failure(e),
+        }
+    }
+
+    /// Leave the queue, a match made in the meantime is still joined
+    #[func]
+    fn on_cancel(&mut self) {
+        let token = match self.token.clone() {
+            Some(token) if self.queued_at.is_some() => token,
+            _ => return,
+        };
+        match self.api.cancel_matchmaking(&token) {
+            Ok(_) => {
+                info!("Matchmaking cancelled");
+                self.set_searching(None);
+            }
+            Err(e) if e.status_code() == Some(409) => self.on_poll(),
+            Err(e) => self.show_failure(e),
+        }
+    }
+
+    /// Leave the queue quietly and hide, the user signs out
+    pub fn leave(&mut self) {
+        if let (Some(token), Some(_)) = (self.token.take(), self.queued_at) {
+            if let Err(e) = self.api.cancel_matchmaking(&token) {
+                warn!("Failed to leave the matchmaking queue: {}", e);
+            }
+        }
+        self.set_searching(None);
+        self.base_mut().set_visible(false);
+    }
+
+    #[func]
+    fn on_close(&mut self) {
+        self.on_cancel();
+        if self.queued_at.is_none() {
+            self.base_mut().set_visible(false);
+        }
+    }
+
+    fn handle_ticket(&mut self, ticket: MatchmakingTicketDto) {
+        match (ticket.status, ticket.session) {
+            (MatchmakingStatus::Matched, Some(session)) => {
+                info!("Matched into {}", session.session_id);
+                self.set_searching(None);
+                self.base_mut().set_visible(false);
+                self.base_mut()
+                    .emit_signal("matched", &[session.session_id.to_variant()]);
+            }
+            _ => self.set_searching(Some(ticket.queued_at)),
+        }
+    }
+
+    /// Switch between the preferences and the waiting indicator
+    fn set_searching(&mut self, queued_at: Option<i64>) {
+        self.queued_at = queued_at;
+        let controls = match self.controls.as_mut() {
+            Some(controls) => controls,
+            None => return,
+        };
+        let searching = queued_at.is_some();
+        controls.board_size.set_disabled(searching);
+        controls.time_control.set_disabled(searching);
+        controls.min_rank.set_disabled(searching);
+        controls.max_rank.set_disabled(searching);
+        controls.find.set_visible(!searching);
+        controls.cancel.set_visible(searching);
+        if searching {
+            if controls.poll_timer.is_stopped() {
+                controls
+                    .poll_timer
+                    .set_wait_time(preferences().poll_interval_secs());
+                controls.poll_timer.start();
+            }
+        } else {
+            controls.poll_timer.stop();
+            controls.status.set_text("");
+        }
+        self.show_waiting();
+    }
+
+    fn show_waiting(&mut self) {
+        let queued_at = match self.queued_at {
+            Some(queued_at) => queued_at,
+            None => return,
+        };
+        let waited = (get_unix_time() - queued_at).max(0);
+        if let Some(controls) = self.controls.as_mut() {
+            controls.status.set_text(&format!(
+                "Searching for an opponent... {}:{:02}",
+                waited / 60,
+                waited % 60
+            ));
+        }
+    }
+
+    fn show_failure(&mut self, e: ApiError) {
+        error!("Matchmaking failed: {}", e);
+        self.set_searching(None);
+        if let Some(controls) = self.controls.as_mut() {
+            controls
+                .status
+                .set_text(&format!("The request failed: {}", e));
+        }
+    }
+}
+
+#[godot_api]
+impl ICanvasLayer for MatchmakingScreen {
+    fn init(base: Base<CanvasLayer>) -> Self {
+        Self {
+            api: ApiClient::default(),
+            token: None,
+            queued_at: None,
+            controls: None,
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        self.base_mut().set_layer(50);
+        self.base_mut().set_visible(false);
+
+        let mut board_size = OptionButton::new_alloc();
+        for size in MATCH_BOARD_SIZES {
+            board_size.add_item(&format!("{}x{}", size, size));
+        }
+        board_size.select(MATCH_BOARD_SIZES.len() as i32 - 1);
+        let mut time_control = OptionButton::new_alloc();
+        for preset in MATCH_TIME_CONTROLS.iter() {
+            time_control.add_item(&time_control_name(preset));
+        }
+        let mut find = Button::new_alloc();
+        find.set_text("Find game");
+        find.connect("pressed", &self.base().callable("on_find"));
+        let mut cancel = Button::new_alloc();
+        cancel.set_text("Cancel");
+        cancel.connect("pressed", &self.base().callable("on_cancel"));
+        let mut close = Button::new_alloc();
+        close.set_text("Close");
+        close.connect("pressed", &self.base().callable("on_close"));
+        let mut poll_timer = Timer::new_alloc();
+        poll_timer.connect("timeout", &self.base().callable("on_poll"));
+        self.base_mut().add_child(&poll_timer);
+        let mut controls = MatchmakingControls {
+            board_size,
+            time_control,
+            min_rank: rank_option(),
+            max_rank: rank_option(),
+            find,
+            cancel,
+            status: Label::new_alloc(),
+            poll_timer,
+        };
+        controls.cancel.set_visible(false);
+
+        let mut grid = GridContainer::new_alloc();
+        grid.set_columns(2);
+        let rows: [(&str, Gd<Control>); 4] = [
+            ("Board", controls.board_size.clone().upcast()),
+            ("Time", controls.time_control.clone().upcast()),
+            ("Weakest opponent", controls.min_rank.clone().upcast()),
+            ("Strongest opponent", controls.max_rank.clone().upcast()),
+        ];
+        for (title, control) in rows {
+            let mut label = Label::new_alloc();
+            label.set_text(title);
+            grid.add_child(&label);
+            grid.add_child(&control);
+        }
+
+        let mut buttons = HBoxContainer::new_alloc();
+        buttons.add_child(&controls.find);
+        buttons.add_child(&controls.cancel);
+        buttons.add_child(&close);
+
+        let mut column = VBoxContainer::new_alloc();
+        column.add_child(&grid);
+        column.add_child(&controls.status);
+        column.add_child(&buttons);
+        let mut panel = PanelContainer::new_alloc();
+        panel.set_anchors_and_offsets_preset(LayoutPreset::CENTER);
+        panel.add_child(&column);
+        self.base_mut().add_child(&panel);
+        self.controls = Some(controls);
+    }
+
+    /// Keep the waited time ticking between the polls
+    fn process(&mut self, _delta: f64) {
+        if self.queued_at.is_some() {
+            self.show_waiting();
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use std::sync::{Mutex, MutexGuard};
+
+use game_core::preferences::{
+    BoardTheme, Preferences, StoneSet, LANGUAGES, MAX_POLL_INTERVAL_MS, MIN_POLL_INTERVAL_MS,
+    PREFERENCES_PATH,
+};
+use godot::{
+    classes::{
+        control::LayoutPreset, AudioServer, Button, CanvasLayer, CheckBox, Control, GridContainer,
+        HBoxContainer, ICanvasLayer, Label, LineEdit, OptionButton, PanelContainer, SpinBox,
+        TranslationServer, VBoxContainer,
+    },
+    prelude::*,
+};
+use log::{error, info};
+
+use crate::util::globalize_path;
+
+/// Nodes in this group get `on_preferences_changed` called after the settings are saved
+pub const PREFERENCES_GROUP: &str = "preferences_listeners";
+
+static PREFERENCES: Mutex<Option<Preferences>> = Mutex::new(None);
+
+fn stored() -> MutexGuard<'static, Option<Preferences>> {
+    PREFERENCES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The preferences in use, read from the user directory on the first call
+pub fn preferences() -> Preferences {
+    stored()
+        .get_or_insert_with(|| Preferences::load(&globalize_path(PREFERENCES_PATH)))
+        .clone()
+}
+
+/// Sound and language are global to the engine, the scenes apply the rest themselves
+pub fn apply_global(preferences: &Preferences) {
+    AudioServer::singleton().set_bus_mute(0, !preferences.sound);
+    TranslationServer::singleton().set_locale(&preferences.language);
+}
+
+fn save_preferences(preferences: Preferences) {
+    if let Err(e) = preferences.save(&globalize_path(PREFERENCES_PATH)) {
+        error!("Failed to save the preferences: {}", e);
+    }
+    apply_global(&preferences);
+    *stored() = Some(preferences);
+}
+
+struct SettingsControls {
+    server_url: Gd<LineEdit>,
+    board_theme: Gd<OptionButton>,
+    stone_set: Gd<OptionButton>,
+    show_coordinates: Gd<CheckBox>,
+    confirm_moves: Gd<CheckBox>,
+    sound: Gd<CheckBox>,
+    language: Gd<OptionButton>,
+    poll_interval: Gd<SpinBox>,
+}
+
+/// Edits the `Preferences`, hidden until `open` is called
+#[derive(GodotClass)]
+#[class(base=CanvasLayer)]
+pub struct SettingsScreen {
+    controls: Option<SettingsControls>,
+    base: Base<CanvasLayer>,
+}
+
+fn option_button(items: &[&str]) -> Gd<OptionButton> {
+    let mut button = OptionButton::new_alloc();
+    for item in items {
+        button.add_item(*item);
+    }
+    button
+}
+
+fn check_box() -> Gd<CheckBox> {
+    CheckBox::new_alloc()
+}
+
+#[godot_api]
+impl SettingsScreen {
+    /// Add a hidden settings screen to the node
+    pub fn attach(mut node: Gd<Node>) -> Gd<SettingsScreen> {
+        let settings_screen = SettingsScreen::new_alloc();
+        node.add_child(&settings_screen);
+        settings_screen
+    }
+
+    /// Show the screen with the preferences in use
+    #[func]
+    pub fn open(&mut self) {
+        let preferences = preferences();
+        if let Some(controls) = self.controls.as_mut() {
+            controls.server_url.set_text(&preferences.server_url);
+            let theme = BoardTheme::ALL
+                .iter()
+                .position(|t| *t == preferences.board_theme);
+            controls.board_theme.select(theme.unwrap_or(0) as i32);
+            let stones = StoneSet::ALL
+                .iter()
+                .position(|s| *s == preferences.stone_set);
+            controls.stone_set.select(stones.unwrap_or(0) as i32);
+            controls
+                .show_coordinates
+                .set_pressed(preferences.show_coordinates);
+            controls
+                .confirm_moves
+                .set_pressed(preferences.confirm_moves);
+            controls.sound.set_pressed(preferences.sound);
+            let language = LANGUAGES.iter().position(|l| *l == preferences.language);
+            controls.language.select(language.unwrap_or(0) as i32);
+            controls
+                .poll_interval
+                .set_value(preferences.poll_interval_ms as f64);
+        }
+        self.base_mut().set_visible(true);
+    }
+
+    #[func]
+    fn on_save(&mut self) {
+        let controls = match self.controls.as_ref() {
+            Some(controls) => controls,
+            None => return,
+        };
+        let preferences = Preferences {
+            server_url: controls.server_url.get_text().to_string(),
+            board_theme: BoardTheme::ALL[controls.board_theme.get_selected().max(0) as usize],
+            stone_set: StoneSet::ALL[controls.stone_set.get_selected().max(0) as usize],
+            show_coordinates: controls.show_coordinates.is_pressed(),
+            confirm_moves: controls.confirm_moves.is_pressed(),
+            sound: controls.sound.is_pressed(),
+            language: LANGUAGES[controls.language.get_selected().max(0) as usize].to_string(),
+            poll_interval_ms: controls.poll_interval.get_value() as u64,
+        }
+        .normalized();
+        info!("Preferences saved: {:?}", preferences);
+        save_preferences(preferences);
+        self.base_mut().set_visible(false);
+        if let Some(mut tree) = self.base().get_tree() {
+            tree.call_group(PREFERENCES_GROUP, "on_preferences_changed", &[]);
+        }
+    }
+
+    #[func]
+    fn on_cancel(&mut self) {
+        self.base_mut().set_visible(false);
+    }
+}
+
+#[godot_api]
+impl ICanvasLayer for SettingsScreen {
+    fn init(base: Base<CanvasLayer>) -> Self {
+        Self {
+            controls: None,
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        self.base_mut().set_layer(50);
+        self.base_mut().set_visible(false);
+
+        let mut poll_interval = SpinBox::new_alloc();
+        poll_interval.set_min(MIN_POLL_INTERVAL_MS as f64);
+        poll_interval.set_max(MAX_POLL_INTERVAL_MS as f64);
+        poll_interval.set_step(50.0);
+        poll_interval.set_suffix("ms");
+        let theme_names: Vec<&str> = BoardTheme::ALL.iter().map(BoardTheme::name).collect();
+        let stone_names: Vec<&str> = StoneSet::ALL.iter().map(StoneSet::name).collect();
+        let controls = SettingsControls {
+            server_url: LineEdit::new_alloc(),
+            board_theme: option_button(&theme_names),
+            stone_set: option_button(&stone_names),
+            show_coordinates: check_box(),
+            confirm_moves: check_box(),
+            sound: check_box(),
+            language: option_button(&LANGUAGES),
+            poll_interval,
+        };
+
+        let mut grid = GridContainer::new_alloc();
+        grid.set_columns(2);
+        let rows: [(&str, Gd<Control>); 8] = [
+            ("Server URL", controls.server_url.clone().upcast()),
+            ("Board theme", controls.board_theme.clone().upcast()),
+            ("Stones", controls.stone_set.clone().upcast()),
+            ("Coordinates", controls.show_coordinates.clone().upcast()),
+            ("Confirm moves", controls.confirm_moves.clone().upcast()),
+            ("Sound", controls.sound.clone().upcast()),
+            ("Language", controls.language.clone().upcast()),
+            ("Polling interval", controls.poll_interval.clone().upcast()),
+        ];
+        for (title, control) in rows {
+            let mut label = Label::new_alloc();
+            label.set_text(title);
+            grid.add_child(&label);
+            grid.add_child(&control);
+        }
+
+        let mut buttons = HBoxContainer::new_alloc();
+        for (title, method) in [("Save", "on_save"), ("Cancel", "on_cancel")] {
+            let mut button = Button::new_alloc();
+            button.set_text(title);
+            button.connect("pressed", &self.base().callable(method));
+            buttons.add_child(&button);
+        }
+
+        let mut column = VBoxContainer::new_alloc();
+        column.add_child(&grid);
+        column.add_child(&buttons);
+        let mut panel = PanelContainer::new_alloc();
+        panel.set_anchors_and_offsets_preset(LayoutPreset::CENTER);
+        panel.add_child(&column);
+        self.base_mut().add_child(&panel);
+        self.controls = Some(controls);
+    }
+}
@@ -1,27 +1,70 @@
-use std::{collections::HashMap, io::Read};
+use std::path::Path;
 
+use game_core::{
+    account,
+    ai::Difficulty,
+    api::{ApiClient, ApiError},
+    credentials::{TokenStore, REMEMBERED_TOKEN_PATH, TOKEN_SECRET_PATH},
+    dto::{
+        ChallengeAnswerDto, ChallengeDto, ChallengeRequestDto, ChallengeStatus, ColorPreference,
+        FieldErrorDto, GameSettingsDto, HandicapPlacement, TimeControl, UserSessionDto,
+        UserSessionRequestDto, UserStatusDto, UserTokenDto,
+    },
+    gtp_bridge::{EngineSettings, GtpBridge, ENGINE_SETTINGS_PATH},
+    handicap,
+    lobby::{self, SessionFilter, SessionSort, LOBBY_COLUMNS},
+    resume::{ActiveGame, ACTIVE_GAME_PATH},
+    review::GameTree,
+    sgf,
+};
+use go_gtp::vertex::MAX_GTP_SIZE;
 use godot::{
-    classes::{Button, CanvasLayer, Control, IControl, ItemList, Label, LineEdit},
-    global::Error,
+    classes::{
+        Button, CanvasLayer, CheckBox, ConfirmationDialog, Control, IControl, ItemList, Label,
+        LineEdit, OptionButton, SpinBox, Timer,
+    },
     prelude::*,
 };
-use reqwest::{
-    blocking::{Client, Response},
-    StatusCode,
-};
+
+use log::{debug, error, info, warn};
 
 use crate::{
-    dto::{UserSessionDto, UserSessionRequestDto, UserTokenDto},
+    account_screen::AccountScreen,
+    crash,
+    logging::LogConsole,
     master_scene::MasterScene,
+    matchmaking_screen::MatchmakingScreen,
+    settings_screen::{apply_global, preferences, SettingsScreen, PREFERENCES_GROUP},
+    util::{get_unix_time, globalize_path},
 };
 
-const HOST: &str = "http://localhost:8080";
+/// Who plays against the user in an offline game
+enum LocalOpponent {
+    /// Hot-seat game, the engine is only used for hints when it's configured
+    Human(Option<GtpBridge>),
+    Computer(Difficulty),
+    Engine(GtpBridge),
+}
 
 #[derive(GodotClass)]
 #[class(base=Control)]
 pub struct StartGameHud {
-    client: Client,
+    api: ApiClient,
     user_token: Option<UserTokenDto>,
+    sessions: Vec<UserSessionDto>,
+    session_filter: SessionFilter,
+    session_sort: SessionSort,
+    online_players: Vec<UserStatusDto>,
+    /// Games of other players shown in `ActiveGamesList`
+    active_sessions: Vec<UserSessionDto>,
+    incoming_challenge: Option<ChallengeDto>,
+    outgoing_challenge: Option<ChallengeDto>,
+    active_game: Option<ActiveGame>,
+    settings_screen: Option<Gd<SettingsScreen>>,
+    account_screen: Option<Gd<AccountScreen>>,
+    matchmaking_screen: Option<Gd<MatchmakingScreen>>,
+    /// The token of "remember me"
+    token_store: TokenStore,
     base: Base<Control>,
 }
 
@@ -29,151 +72,226 @@ pub struct StartGameHud {
 impl StartGameHud {
     #[func]
     fn on_signup(&mut self) {
-        godot_print_rich!("Signup pressed");
+        debug!("Signup pressed");
         let auth_layer = self.base_mut().get_node_as::<CanvasLayer>("AuthLayer");
         let mut sign_error_label = auth_layer.get_node_as::<Label>("SignError");
+        sign_error_label.set_text("");
 
-        let login = self.get_login();
-        let pass = self.get_pass();
-        if login.is_err() || pass.is_err() {
+        let login = self.auth_input("LoginInput");
+        let pass = self.auth_input("PassInput");
+        let confirm = self.auth_input("PassConfirmInput");
+        let errors = account::validate_signup(&login, &pass, &confirm);
+        self.show_auth_errors(&errors);
+        if !errors.is_empty() {
             return;
         }
-        match self.send_auth_request(login.unwrap(), pass.unwrap(), "signup") {
-            Ok(mut response) => match response.status() {
-                StatusCode::OK => self.handle_ok_response(&mut response, &mut sign_error_label),
-                default => sign_error_label.set_text(
-                    format!(
-                        "Failed to signup. Try another credentials. Status: {}",
-                        default
-                    )
-                    .trim(),
-                ),
-            },
+        match self.api.signup(&login, &pass) {
+            Ok(token) => self.handle_ok_response(token, &mut sign_error_label),
+            Err(ApiError::Validation(errors)) => self.show_auth_errors(&errors),
+            Err(e) if e.status_code() == Some(409) => {
+                self.show_auth_errors(&[account::field_error(
+                    account::FIELD_LOGIN,
+                    "This login is already taken",
+                )])
+            }
+            Err(ApiError::Status(status)) => {
+                sign_error_label.set_text(format!("Failed to signup. Status: {}", status).trim())
+            }
             Err(e) => {
-                sign_error_label.set_text("Failed to signup. Try another credentials.");
-                godot_error!("Error on signup: {}", e)
+                sign_error_label.set_text("Failed to signup. Try again later.");
+                error!("Error on signup: {}", e)
             }
         };
     }
 
     #[func]
     fn on_signin(&mut self) {
-        godot_print_rich!("Signin pressed");
+        debug!("Signin pressed");
         let auth_layer = self.base_mut().get_node_as::<CanvasLayer>("AuthLayer");
         let mut sign_error_label = auth_layer.get_node_as::<Label>("SignError");
+        sign_error_label.set_text("");
 
-        let login = self.get_login();
-        let pass = self.get_pass();
-        if login.is_err() || pass.is_err() {
+        let login = self.auth_input("LoginInput");
+        let pass = self.auth_input("PassInput");
+        let errors = account::validate_signin(&login, &pass);
+        self.show_auth_errors(&errors);
+        if !errors.is_empty() {
             return;
         }
-        match self.send_auth_request(login.unwrap(), pass.unwrap(), "signin") {
-            Ok(mut response) => match response.status() {
-                StatusCode::OK => self.handle_ok_response(&mut response, &mut sign_error_label),
-                default => sign_error_label.set_text(
-                    format!(
-                        "Failed to signin. Try another credentials. Status: {}",
-                        default
-                    )
-                    .trim(),
-                ),
-            },
+        match self.api.signin(&login, &pass) {
+            Ok(token) => self.handle_ok_response(token, &mut sign_error_label),
+            Err(ApiError::Validation(errors)) => self.show_auth_errors(&errors),
+            Err(e) if e.is_unauthorized() => {
+                sign_error_label.set_text("Wrong login or password.");
+            }
+            Err(ApiError::Status(status)) => {
+                sign_error_label.set_text(format!("Failed to sign in. Status: {}", status).trim())
+            }
             Err(e) => {
-                sign_error_label.set_text("Failed to sign in. Try another credentials.");
-                godot_error!("Error on signin: {}", e)
+                sign_error_label.set_text("Failed to sign in. Try again later.");
+                error!("Error on signin: {}", e)
             }
         };
     }
 
+    /// Open the profile, password and account deletion screen of the signed in user
+    #[func]
+    fn on_account_open(&mut self) {
+        let token = match self.user_token.clone() {
+            Some(token) => token,
+            None => return,
+        };
+        let api = self.api.clone();
+        if let Some(account_screen) = self.account_screen.as_mut() {
+            account_screen.bind_mut().open(api, token);
+        }
+    }
+
+    /// "Find game" instead of picking a vacant session
+    #[func]
+    fn on_find_game_open(&mut self) {
+        let token = match self.user_token.clone() {
+            Some(token) => token,
+            None => return,
+        };
+        let api = self.api.clone();
+        if let Some(matchmaking_screen) = self.matchmaking_screen.as_mut() {
+            matchmaking_screen.bind_mut().open(api, token);
+        }
+    }
+
+    /// The server paired the user, the session is already started
+    #[func]
+    fn on_match_found(&mut self, session_id: GString) {
+        match self.api.session(&session_id.to_string()) {
+            Ok(user_session) => self.goto_game(Some(user_session)),
+            Err(e) => error!("Failed to load the matched session: {}", e),
+        }
+    }
+
     #[func]
     fn on_vacant_sessions_request(&mut self) {
-        godot_print_rich!("Get Vacant sessions pressed");
-        let player_list_layer = self
-            .base_mut()
-            .get_node_as::<CanvasLayer>("PlayersListLayer");
-        let mut player_item_list = player_list_layer.get_node_as::<ItemList>("PlayerList");
+        debug!("Get Vacant sessions pressed");
         let token = self.user_token.clone().unwrap();
-        let body = serde_json::to_string(&token).unwrap_or("{}".to_string());
-        let res = self
-            .client
-            .get(format!("{}/{}", HOST, "session"))
-            .body(body)
-            .header("Content-Type", "application/json")
-            .send();
-        let player_list: Vec<UserSessionDto> = match res {
-            Ok(mut response) => {
-                let mut body: String = String::new();
-                let _ = response.read_to_string(&mut body);
-                godot_print!("{}", &body);
-                serde_json::from_str(&body)
-                    .map_err(|e| godot_print!("{}", e))
-                    .unwrap_or(vec![])
-            }
+        let session_list = match self.api.vacant_sessions(&token) {
+            Ok(session_list) => session_list,
             Err(e) => {
-                godot_error!("{}", e);
-                vec![]
+                error!("{}", e);
+                return;
             }
         };
-        if player_list.is_empty() {
-            return;
+        self.sessions = lobby::dedupe_sessions(session_list);
+        self.refresh_session_list();
+    }
+
+    #[func]
+    fn on_online_only_toggled(&mut self, toggled: bool) {
+        self.session_filter.online_only = toggled;
+        self.refresh_session_list();
+    }
+
+    /// Board size filter items are "Any", "9x9", "13x13", "19x19"
+    #[func]
+    fn on_board_size_filter_selected(&mut self, index: i32) {
+        self.session_filter.board_size = match index {
+            1 => Some(9),
+            2 => Some(13),
+            3 => Some(19),
+            _ => None,
+        };
+        self.refresh_session_list();
+    }
+
+    #[func]
+    fn on_rank_filter_changed(&mut self, _text: String) {
+        let player_list_layer = self
+            .base_mut()
+            .get_node_as::<CanvasLayer>("PlayersListLayer");
+        let min_rank = player_list_layer
+            .get_node_as::<LineEdit>("MinRankInput")
+            .get_text()
+            .to_string();
+        let max_rank = player_list_layer
+            .get_node_as::<LineEdit>("MaxRankInput")
+            .get_text()
+            .to_string();
+        self.session_filter.min_rank = lobby::rank_value(&min_rank);
+        self.session_filter.max_rank = lobby::rank_value(&max_rank);
+        self.refresh_session_list();
+    }
+
+    #[func]
+    fn on_session_sort_selected(&mut self, index: i32) {
+        self.session_sort = SessionSort::from(index);
+        self.refresh_session_list();
+    }
+
+    fn refresh_session_list(&mut self) {
+        let player_list_layer = self
+            .base_mut()
+            .get_node_as::<CanvasLayer>("PlayersListLayer");
+        let mut player_item_list = player_list_layer.get_node_as::<ItemList>("PlayerList");
+        player_item_list.clear();
+        player_item_list.set_max_columns(LOBBY_COLUMNS.len() as i32);
+        for title in LOBBY_COLUMNS {
+            let index = player_item_list.add_item(title);
+            player_item_list.set_item_selectable(index, false);
         }
-        for player in player_list.iter() {
-            godot_print!("{:?}", &player);
-            player_item_list.add_item(
-                format!(
-                    "{}=>{}=>{}",
-                    player.user1.login,
-                    if player.user1.is_online {
-                        "online"
-                    } else {
-                        "offline"
-                    },
-                    player.session_id
-                )
-                .trim(),
-            );
+        let visible =
+            lobby::visible_sessions(&self.sessions, &self.session_filter, self.session_sort);
+        for session in visible.iter() {
+            for column in lobby::session_columns(session) {
+                let index = player_item_list.add_item(&column);
+                player_item_list
+                    .set_item_metadata(index, &Variant::from(session.session_id.as_str()));
+            }
         }
     }
 
+    #[func]
+    fn on_create_game_dialog_open(&mut self) {
+        let mut create_game_layer = self
+            .base_mut()
+            .get_node_as::<CanvasLayer>("CreateGameLayer");
+        create_game_layer.set_visible(true);
+    }
+
+    #[func]
+    fn on_create_game_dialog_cancel(&mut self) {
+        let mut create_game_layer = self
+            .base_mut()
+            .get_node_as::<CanvasLayer>("CreateGameLayer");
+        create_game_layer.set_visible(false);
+    }
+
     #[func]
     fn on_create_sessions_request(&mut self) {
-        godot_print_rich!("Get Vacant sessions pressed");
-        let player_list_layer = self
+        debug!("Create session pressed");
+        let settings = self.get_game_settings();
+        let password = Some(
+            self.base_mut()
+                .get_node_as::<CanvasLayer>("CreateGameLayer")
+                .get_node_as::<LineEdit>("PasswordInput")
+                .get_text()
+                .to_string(),
+        )
+        .filter(|p| settings.is_private && !p.is_empty());
+        let mut create_game_layer = self
             .base_mut()
-            .get_node_as::<CanvasLayer>("PlayersListLayer");
+            .get_node_as::<CanvasLayer>("CreateGameLayer");
+        create_game_layer.set_visible(false);
         let user_id = self.user_token.clone().unwrap().user_id;
         let body = UserSessionRequestDto {
             user_id,
             session_id: None,
+            settings: Some(settings),
+            password,
+            ..Default::default()
         };
-        let body = serde_json::to_string(&body).unwrap_or("{}".to_string());
-        let res = self
-            .client
-            .post(format!("{}/{}", HOST, "session"))
-            .body(body)
-            .header("Content-Type", "application/json")
-            .send();
-        match res {
-            Ok(user_session_response) =>
+        match self.api.create_session(&body) {
             /*go to game scene*/
-            {
-                let master_scene: Gd<PackedScene> = load("res://content/scenes/Master.tscn");
-                let mut master_scene = master_scene.instantiate_as::<MasterScene>();
-                master_scene.bind_mut().init_game_data(
-                    serde_json::from_str::<Option<UserSessionDto>>(
-                        user_session_response.text().unwrap().as_str(),
-                    )
-                    .unwrap(),
-                    self.user_token.clone(),
-                );
-                self.base()
-                    .get_tree()
-                    .and_then(|t| t.get_root())
-                    .unwrap()
-                    .add_child(&master_scene);
-                player_list_layer.clone().set_visible(false);
-            }
-            Err(e) => godot_error!("{}", e),
+            Ok(user_session) => self.goto_game(Some(user_session)),
+            Err(e) => error!("{}", e),
         };
     }
 
@@ -184,13 +302,17 @@ impl StartGameHud {
         _at_position: Vector2,
         _mouse_button_index: i32,
     ) {
-        godot_print_rich!("Join to session clicked");
+        debug!("Join to session clicked");
         let player_list_layer = self
             .base_mut()
             .get_node_as::<CanvasLayer>("PlayersListLayer");
         let player_item_list = player_list_layer.get_node_as::<ItemList>("PlayerList");
-        let item = player_item_list.get_item_text(index);
-        let session_id = item.split("=>").get(2).map(|s| s.to_string());
+        let session_id = player_item_list
+            .get_item_metadata(index)
+            .try_to::<GString>()
+            .ok()
+            .map(|id| id.to_string())
+            .filter(|id| self.sessions.iter().any(|s| &s.session_id == id));
         if session_id.is_none() {
             return;
         }
@@ -198,36 +320,665 @@ impl StartGameHud {
         let body = UserSessionRequestDto {
             user_id,
             session_id,
+            ..Default::default()
         };
-        let body = serde_json::to_string(&body).unwrap_or("{}".to_string());
-        let res = self
-            .client
-            .patch(format!("{}/{}", HOST, "session"))
-            .body(body)
-            .header("Content-Type", "application/json")
-            .send();
-        match res {
-            Ok(user_session_response) =>
+        match self.api.join_session(&body) {
             /*go to game scene*/
-            {
-                let master_scene: Gd<PackedScene> = load("res://content/scenes/Master.tscn");
-                let mut master_scene = master_scene.instantiate_as::<MasterScene>();
-                master_scene.bind_mut().init_game_data(
-                    serde_json::from_str::<Option<UserSessionDto>>(
-                        user_session_response.text().unwrap().as_str(),
-                    )
-                    .unwrap(),
-                    self.user_token.clone(),
-                );
-                self.base()
-                    .get_tree()
-                    .and_then(|t| t.get_root())
-                    .unwrap()
-                    .add_child(&master_scene);
-                player_list_layer.clone().set_visible(false);
+            Ok(user_session) => self.goto_game(Some(user_session)),
+            Err(e) => error!("{}", e),
+        };
+    }
+
+    /// Join a private session by a pasted join code or invite link
+    #[func]
+    fn on_join_by_code(&mut self) {
+        debug!("Join by code pressed");
+        let player_list_layer = self
+            .base_mut()
+            .get_node_as::<CanvasLayer>("PlayersListLayer");
+        let mut join_code_input = player_list_layer.get_node_as::<LineEdit>("JoinCodeInput");
+        let password = player_list_layer
+            .get_node_as::<LineEdit>("JoinPasswordInput")
+            .get_text()
+            .to_string();
+        let invite = match lobby::parse_invite(&join_code_input.get_text().to_string()) {
+            Some(invite) => invite,
+            None => {
+                join_code_input.clear();
+                join_code_input.set_placeholder("Invalid join code");
+                return;
+            }
+        };
+        let user_id = self.user_token.clone().unwrap().user_id;
+        let body = UserSessionRequestDto {
+            user_id,
+            join_code: Some(invite.code),
+            password: invite.password.or(Some(password).filter(|p| !p.is_empty())),
+            ..Default::default()
+        };
+        match self.api.join_session(&body) {
+            Ok(user_session) => self.goto_game(Some(user_session)),
+            Err(ApiError::Status(status)) => {
+                join_code_input.clear();
+                join_code_input.set_placeholder(&format!("Failed to join. Status: {}", status));
+            }
+            Err(e) => error!("{}", e),
+        };
+    }
+
+    /// Refresh everything shown in the lobby, called by `SessionsRefreshTimer`
+    #[func]
+    fn on_lobby_refresh(&mut self) {
+        self.on_vacant_sessions_request();
+        self.refresh_online_players();
+        self.refresh_active_games();
+        self.refresh_challenges();
+    }
+
+    fn refresh_active_games(&mut self) {
+        let token = self.user_token.clone().unwrap();
+        let sessions = match self.api.active_sessions(&token) {
+            Ok(sessions) => sessions,
+            Err(e) => {
+                error!("{}", e);
+                return;
+            }
+        };
+        let mut active_games_list = self
+            .base_mut()
+            .get_node_as::<CanvasLayer>("PlayersListLayer")
+            .get_node_as::<ItemList>("ActiveGamesList");
+        // The list is rebuilt on every `SessionsRefreshTimer` tick, keep the selected game
+        let selected = active_games_list
+            .get_selected_items()
+            .get(0)
+            .and_then(|index| self.active_sessions.get(index as usize))
+            .map(|s| s.session_id.clone());
+        self.active_sessions =
+            lobby::watchable_sessions(&lobby::dedupe_sessions(sessions), &token.login);
+        active_games_list.clear();
+        for session in self.active_sessions.iter() {
+            active_games_list.add_item(&lobby::active_game_title(session));
+        }
+        if let Some(index) = self
+            .active_sessions
+            .iter()
+            .position(|s| Some(&s.session_id) == selected.as_ref())
+        {
+            active_games_list.select(index as i32);
+        }
+    }
+
+    /// Watch the active game double clicked in `ActiveGamesList`
+    #[func]
+    fn on_watch_game(&mut self, index: i32, _at_position: Vector2, _mouse_button_index: i32) {
+        debug!("Watch game clicked");
+        let session = match self.active_sessions.get(index as usize) {
+            Some(session) => session.clone(),
+            None => return,
+        };
+        let master_scene: Gd<PackedScene> = load("res://content/scenes/Master.tscn");
+        let mut master_scene = master_scene.instantiate_as::<MasterScene>();
+        master_scene
+            .bind_mut()
+            .init_spectator(session, self.user_token.clone());
+        self.base()
+            .get_tree()
+            .and_then(|t| t.get_root())
+            .unwrap()
+            .add_child(&master_scene);
+        let mut player_list_layer = self
+            .base_mut()
+            .get_node_as::<CanvasLayer>("PlayersListLayer");
+        player_list_layer
+            .get_node_as::<Timer>("SessionsRefreshTimer")
+            .stop();
+        player_list_layer.set_visible(false);
+    }
+
+    /// Challenge the selected online player with the settings of the create game dialog
+    #[func]
+    fn on_challenge_player(&mut self) {
+        debug!("Challenge pressed");
+        let player_list_layer = self
+            .base_mut()
+            .get_node_as::<CanvasLayer>("PlayersListLayer");
+        let mut online_list = player_list_layer.get_node_as::<ItemList>("OnlinePlayersList");
+        let mut status_label = player_list_layer.get_node_as::<Label>("ChallengeStatusLabel");
+        let selected = online_list.get_selected_items();
+        let opponent = match selected
+            .get(0)
+            .and_then(|index| self.online_players.get(index as usize))
+        {
+            Some(opponent) => opponent.clone(),
+            None => {
+                status_label.set_text("Select an online player to challenge");
+                return;
+            }
+        };
+        let body = ChallengeRequestDto {
+            user_id: self.user_token.clone().unwrap().user_id,
+            opponent_login: opponent.login.clone(),
+            settings: self.get_game_settings(),
+        };
+        match self.api.challenge(&body) {
+            Ok(challenge) => {
+                self.outgoing_challenge = Some(challenge);
+                status_label.set_text(&format!("Waiting for {}...", opponent.login));
+            }
+            Err(e) => error!("{}", e),
+        };
+    }
+
+    /// Accept the shown invitation: create the session from its settings and
+    /// report the session id back so the challenger can join it
+    #[func]
+    fn on_accept_challenge(&mut self) {
+        let challenge = match self.incoming_challenge.take() {
+            Some(challenge) => challenge,
+            None => return,
+        };
+        self.hide_invitation();
+        if lobby::is_challenge_expired(&challenge, get_unix_time()) {
+            return;
+        }
+        let body = UserSessionRequestDto {
+            user_id: self.user_token.clone().unwrap().user_id,
+            settings: Some(lobby::accepted_challenge_settings(&challenge)),
+            ..Default::default()
+        };
+        let user_session = match self.api.create_session(&body) {
+            Ok(user_session) => Some(user_session),
+            Err(e) => {
+                error!("{}", e);
+                return;
+            }
+        };
+        let session_id = user_session.as_ref().map(|s| s.session_id.clone());
+        self.answer_challenge(&challenge, ChallengeStatus::Accepted, session_id);
+        self.goto_game(user_session);
+    }
+
+    #[func]
+    fn on_decline_challenge(&mut self) {
+        if let Some(challenge) = self.incoming_challenge.take() {
+            self.answer_challenge(&challenge, ChallengeStatus::Declined, None);
+        }
+        self.hide_invitation();
+    }
+
+    fn answer_challenge(
+        &mut self,
+        challenge: &ChallengeDto,
+        status: ChallengeStatus,
+        session_id: Option<String>,
+    ) {
+        let body = ChallengeAnswerDto {
+            user_id: self.user_token.clone().unwrap().user_id,
+            challenge_id: challenge.challenge_id.clone(),
+            status,
+            session_id,
+        };
+        match self.api.answer_challenge(&body) {
+            Ok(_) => info!("Challenge answer was sent"),
+            Err(e) => error!("{}", e),
+        };
+    }
+
+    fn refresh_online_players(&mut self) {
+        let token = self.user_token.clone().unwrap();
+        let players = match self.api.online_users(&token) {
+            Ok(players) => players,
+            Err(e) => {
+                error!("{}", e);
+                return;
+            }
+        };
+        self.online_players = players
+            .into_iter()
+            .filter(|p| p.is_online && p.login != token.login)
+            .collect();
+        let mut online_list = self
+            .base_mut()
+            .get_node_as::<CanvasLayer>("PlayersListLayer")
+            .get_node_as::<ItemList>("OnlinePlayersList");
+        online_list.clear();
+        for player in self.online_players.iter() {
+            let rank = lobby::rank_label(player);
+            online_list.add_item(&format!("{} ({})", player.login, rank));
+        }
+    }
+
+    fn refresh_challenges(&mut self) {
+        let token = self.user_token.clone().unwrap();
+        let challenges = match self.api.challenges(&token) {
+            Ok(challenges) => challenges,
+            Err(e) => {
+                error!("{}", e);
+                return;
+            }
+        };
+        let now = get_unix_time();
+        if self.incoming_challenge.is_none() {
+            self.incoming_challenge = lobby::incoming_challenge(&challenges, &token.login, now);
+            if let Some(challenge) = self.incoming_challenge.clone() {
+                self.show_invitation(&challenge);
+            }
+        } else if self
+            .incoming_challenge
+            .as_ref()
+            .is_some_and(|c| lobby::is_challenge_expired(c, now))
+        {
+            self.incoming_challenge = None;
+            self.hide_invitation();
+        }
+        if let Some(outgoing) = self.outgoing_challenge.clone() {
+            let current = challenges
+                .into_iter()
+                .find(|c| c.challenge_id == outgoing.challenge_id)
+                .unwrap_or(outgoing);
+            self.handle_outgoing_challenge(current, now);
+        }
+    }
+
+    fn handle_outgoing_challenge(&mut self, challenge: ChallengeDto, now: i64) {
+        let mut status_label = self
+            .base_mut()
+            .get_node_as::<CanvasLayer>("PlayersListLayer")
+            .get_node_as::<Label>("ChallengeStatusLabel");
+        if lobby::is_challenge_expired(&challenge, now) {
+            status_label.set_text(&format!("{} did not answer", challenge.opponent_login));
+            self.outgoing_challenge = None;
+            return;
+        }
+        match challenge.status {
+            ChallengeStatus::Accepted => {
+                self.outgoing_challenge = None;
+                status_label.set_text("");
+                let body = UserSessionRequestDto {
+                    user_id: self.user_token.clone().unwrap().user_id,
+                    session_id: challenge.session_id,
+                    ..Default::default()
+                };
+                match self.api.join_session(&body) {
+                    Ok(user_session) => self.goto_game(Some(user_session)),
+                    Err(e) => error!("{}", e),
+                }
+            }
+            ChallengeStatus::Declined => {
+                status_label.set_text(&format!("{} declined", challenge.opponent_login));
+                self.outgoing_challenge = None;
+            }
+            _ => self.outgoing_challenge = Some(challenge),
+        }
+    }
+
+    fn show_invitation(&mut self, challenge: &ChallengeDto) {
+        let mut invitation_layer = self
+            .base_mut()
+            .get_node_as::<CanvasLayer>("InvitationLayer");
+        let mut invitation_label = invitation_layer.get_node_as::<Label>("InvitationLabel");
+        let settings = &challenge.settings;
+        invitation_label.set_text(&format!(
+            "{} challenges you: {}x{}, komi {}, handicap {}, time {}",
+            challenge.challenger.login,
+            settings.board_size,
+            settings.board_size,
+            settings.komi,
+            settings.handicap,
+            settings.time_control
+        ));
+        invitation_layer.set_visible(true);
+    }
+
+    fn hide_invitation(&mut self) {
+        let mut invitation_layer = self
+            .base_mut()
+            .get_node_as::<CanvasLayer>("InvitationLayer");
+        invitation_layer.set_visible(false);
+    }
+
+    /// Rejoin the game found on startup, the board is restored by the game state polling
+    #[func]
+    fn on_resume_game(&mut self) {
+        let active_game = match self.active_game.take() {
+            Some(active_game) => active_game,
+            None => return,
+        };
+        self.set_resume_visible(false);
+        self.base_mut()
+            .get_node_as::<CanvasLayer>("AuthLayer")
+            .set_visible(false);
+        self.goto_game(Some(active_game.session));
+    }
+
+    #[func]
+    fn on_discard_resume(&mut self) {
+        self.active_game = None;
+        self.set_resume_visible(false);
+        if let Err(e) = ActiveGame::clear(&globalize_path(ACTIVE_GAME_PATH)) {
+            error!("Failed to clear the active game: {}", e);
+        }
+    }
+
+    #[func]
+    fn on_settings_open(&mut self) {
+        if let Some(settings_screen) = self.settings_screen.as_mut() {
+            settings_screen.bind_mut().open();
+        }
+    }
+
+    /// Called through `PREFERENCES_GROUP` after the settings are saved
+    #[func]
+    fn on_preferences_changed(&mut self) {
+        let preferences = preferences();
+        if self.api.host() != preferences.server_url.trim_end_matches('/') {
+            self.api = ApiClient::new(&preferences.server_url);
+        }
+        self.base()
+            .get_node_as::<CanvasLayer>("PlayersListLayer")
+            .get_node_as::<Timer>("SessionsRefreshTimer")
+            .set_wait_time(preferences.poll_interval_secs());
+    }
+
+    /// Offer to go back to the lobby after a crash, the game left by it is not resumed then.
+    /// Closing the dialog starts like any other launch.
+    fn show_crash_recovery(&mut self, report_path: &Path) {
+        let mut dialog = ConfirmationDialog::new_alloc();
+        dialog.set_title("Recovery");
+        dialog.set_text(&format!(
+            "Something went wrong last time.\nThe error report is saved to {}.\nReturn to the lobby?",
+            report_path.display()
+        ));
+        dialog.set_ok_button_text("Return to the lobby");
+        dialog.set_cancel_button_text("Close");
+        dialog.connect("confirmed", &self.base().callable("on_crash_recovery"));
+        dialog.connect("canceled", &self.base().callable("on_crash_dismissed"));
+        self.base_mut().add_child(&dialog);
+        dialog.popup_centered();
+    }
+
+    #[func]
+    fn on_crash_recovery(&mut self) {
+        self.on_discard_resume();
+        self.sign_in_remembered();
+    }
+
+    #[func]
+    fn on_crash_dismissed(&mut self) {
+        self.sign_in_remembered();
+    }
+
+    /// Look for a game of the user left by a crash or restart which the server still considers active.
+    /// A file which can't be read, like an older one keeping the token, is removed.
+    fn find_active_game(&mut self, token: &UserTokenDto) -> Option<ActiveGame> {
+        let active_game = match ActiveGame::load(&globalize_path(ACTIVE_GAME_PATH)) {
+            Some(active_game) => active_game,
+            None => {
+                let _ = ActiveGame::clear(&globalize_path(ACTIVE_GAME_PATH));
+                return None;
+            }
+        };
+        if active_game.user_id != token.user_id {
+            return None;
+        }
+        let body = UserSessionRequestDto {
+            user_id: token.user_id,
+            session_id: Some(active_game.session.session_id.clone()),
+            ..Default::default()
+        };
+        // Only a definitive answer forgets the game, an offline start still offers it
+        let is_active = match self.api.game_state(&body) {
+            Ok(state) => state.is_active,
+            Err(e) if e.status_code() == Some(404) => false,
+            Err(e) => {
+                warn!("The active game can't be checked: {}", e);
+                true
+            }
+        };
+        if !is_active {
+            let _ = ActiveGame::clear(&globalize_path(ACTIVE_GAME_PATH));
+            return None;
+        }
+        Some(active_game)
+    }
+
+    fn set_resume_visible(&mut self, visible: bool) {
+        let mut resume_layer = self.base_mut().get_node_as::<CanvasLayer>("ResumeLayer");
+        if let Some(active_game) = self.active_game.as_ref() {
+            let opponent = active_game
+                .session
+                .user2
+                .as_ref()
+                .map(|u| u.login.clone())
+                .unwrap_or(active_game.session.user1.login.clone());
+            resume_layer
+                .get_node_as::<Label>("ResumeLabel")
+                .set_text(&format!(
+                    "You have an unfinished game ({} vs {}). Rejoin?",
+                    active_game.session.user1.login, opponent
+                ));
+        }
+        resume_layer.set_visible(visible);
+    }
+
+    /// Start an offline game on one screen with the settings of the create game dialog
+    #[func]
+    fn on_hot_seat_start(&mut self) {
+        debug!("Hot seat pressed");
+        let hint_engine = self
+            .read_engine_settings()
+            .and_then(|settings| GtpBridge::spawn(&settings).ok());
+        self.goto_local_game(LocalOpponent::Human(hint_engine));
+    }
+
+    /// Start an offline game against the computer, difficulty from `AiDifficultyOption`
+    #[func]
+    fn on_ai_game_start(&mut self) {
+        debug!("Play with computer pressed");
+        let difficulty = Difficulty::from(
+            self.base_mut()
+                .get_node_as::<CanvasLayer>("AuthLayer")
+                .get_node_as::<OptionButton>("AiDifficultyOption")
+                .get_selected(),
+        );
+        self.goto_local_game(LocalOpponent::Computer(difficulty));
+    }
+
+    /// Start an offline game against the GTP engine from `EnginePathInput`
+    #[func]
+    fn on_engine_game_start(&mut self) {
+        debug!("Play with engine pressed");
+        let mut sign_error_label = self
+            .base_mut()
+            .get_node_as::<CanvasLayer>("AuthLayer")
+            .get_node_as::<Label>("SignError");
+        let settings = match self.read_engine_settings() {
+            Some(settings) => settings,
+            None => {
+                sign_error_label.set_text("Set the engine command line first");
+                return;
+            }
+        };
+        if let Err(e) = settings.save(&globalize_path(ENGINE_SETTINGS_PATH)) {
+            error!("Failed to save the engine settings: {}", e);
+        }
+        match GtpBridge::spawn(&settings) {
+            Ok(engine) => {
+                info!("Engine {} started", engine.name);
+                self.goto_local_game(LocalOpponent::Engine(engine));
+            }
+            Err(e) => sign_error_label.set_text(&format!("Can't start the engine: {}", e)),
+        }
+    }
+
+    /// Engine command line from `EnginePathInput`, `None` when it's empty
+    fn read_engine_settings(&mut self) -> Option<EngineSettings> {
+        let command_line = self
+            .base_mut()
+            .get_node_as::<CanvasLayer>("AuthLayer")
+            .get_node_as::<LineEdit>("EnginePathInput")
+            .get_text()
+            .to_string();
+        EngineSettings::from_command_line(&command_line)
+    }
+
+    /// Open the SGF file chosen in `ReviewFileDialog` in the review mode
+    #[func]
+    fn on_review_file_selected(&mut self, path: GString) {
+        let path = globalize_path(&path.to_string());
+        let tree = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| sgf::parse_tree(&content).map_err(|e| e.to_string()))
+            .and_then(|root| GameTree::from_sgf(&root).map_err(|e| e.to_string()))
+            .and_then(|tree| match tree.size() {
+                // SGF goes up to 52 lines, the board is drawn with GTP coordinates
+                size if size > MAX_GTP_SIZE => Err(format!(
+                    "boards over {}x{} are not supported, this one is {}x{}",
+                    MAX_GTP_SIZE, MAX_GTP_SIZE, size, size
+                )),
+                _ => Ok(tree),
+            });
+        let tree = match tree {
+            Ok(tree) => tree,
+            Err(e) => {
+                error!("Failed to open {}: {}", path.display(), e);
+                self.base_mut()
+                    .get_node_as::<CanvasLayer>("AuthLayer")
+                    .get_node_as::<Label>("SignError")
+                    .set_text(&format!("Can't open the game: {}", e));
+                return;
+            }
+        };
+        let master_scene: Gd<PackedScene> = load("res://content/scenes/Master.tscn");
+        let mut master_scene = master_scene.instantiate_as::<MasterScene>();
+        master_scene.bind_mut().init_review(tree);
+        self.base()
+            .get_tree()
+            .and_then(|t| t.get_root())
+            .unwrap()
+            .add_child(&master_scene);
+        for layer in ["AuthLayer", "PlayersListLayer", "CreateGameLayer"] {
+            self.base_mut()
+                .get_node_as::<CanvasLayer>(layer)
+                .set_visible(false);
+        }
+    }
+
+    fn goto_local_game(&mut self, opponent: LocalOpponent) {
+        let settings = self.get_game_settings();
+        let master_scene: Gd<PackedScene> = load("res://content/scenes/Master.tscn");
+        let mut master_scene = master_scene.instantiate_as::<MasterScene>();
+        match opponent {
+            LocalOpponent::Human(hint_engine) => {
+                let mut scene = master_scene.bind_mut();
+                scene.init_local_game(&settings);
+                if let Some(engine) = hint_engine {
+                    scene.set_hint_engine(engine);
+                }
+            }
+            LocalOpponent::Computer(difficulty) => {
+                master_scene.bind_mut().init_ai_game(&settings, difficulty)
             }
-            Err(e) => godot_error!("{}", e),
+            LocalOpponent::Engine(engine) => {
+                master_scene.bind_mut().init_engine_game(&settings, engine)
+            }
+        }
+        self.base()
+            .get_tree()
+            .and_then(|t| t.get_root())
+            .unwrap()
+            .add_child(&master_scene);
+        for layer in ["AuthLayer", "PlayersListLayer", "CreateGameLayer"] {
+            self.base_mut()
+                .get_node_as::<CanvasLayer>(layer)
+                .set_visible(false);
+        }
+    }
+
+    fn goto_game(&mut self, user_session: Option<UserSessionDto>) {
+        let master_scene: Gd<PackedScene> = load("res://content/scenes/Master.tscn");
+        let mut master_scene = master_scene.instantiate_as::<MasterScene>();
+        master_scene
+            .bind_mut()
+            .init_game_data(user_session, self.user_token.clone());
+        self.base()
+            .get_tree()
+            .and_then(|t| t.get_root())
+            .unwrap()
+            .add_child(&master_scene);
+        let mut player_list_layer = self
+            .base_mut()
+            .get_node_as::<CanvasLayer>("PlayersListLayer");
+        player_list_layer
+            .get_node_as::<Timer>("SessionsRefreshTimer")
+            .stop();
+        player_list_layer.set_visible(false);
+    }
+
+    /// Read the game settings from the create game dialog controls
+    fn get_game_settings(&mut self) -> GameSettingsDto {
+        let layer = self
+            .base_mut()
+            .get_node_as::<CanvasLayer>("CreateGameLayer");
+        let board_size = match layer
+            .get_node_as::<OptionButton>("BoardSizeOption")
+            .get_selected()
+        {
+            0 => 9,
+            1 => 13,
+            _ => 19,
         };
+        let color = match layer
+            .get_node_as::<OptionButton>("ColorOption")
+            .get_selected()
+        {
+            0 => ColorPreference::Black,
+            1 => ColorPreference::White,
+            _ => ColorPreference::Nigiri,
+        };
+        let main_time_secs = layer.get_node_as::<SpinBox>("MainTimeInput").get_value() as u32 * 60;
+        let extra_secs = layer.get_node_as::<SpinBox>("ExtraTimeInput").get_value() as u32;
+        let periods = layer.get_node_as::<SpinBox>("PeriodsInput").get_value() as u32;
+        let time_control = match layer
+            .get_node_as::<OptionButton>("TimeControlOption")
+            .get_selected()
+        {
+            1 => TimeControl::Absolute { main_time_secs },
+            2 => TimeControl::Fischer {
+                main_time_secs,
+                increment_secs: extra_secs,
+            },
+            3 => TimeControl::ByoYomi {
+                main_time_secs,
+                period_secs: extra_secs,
+                periods,
+            },
+            4 => TimeControl::Canadian {
+                main_time_secs,
+                period_secs: extra_secs,
+                stones: periods,
+            },
+            _ => TimeControl::None,
+        };
+        let handicap = layer.get_node_as::<SpinBox>("HandicapInput").get_value() as i32;
+        let komi = layer.get_node_as::<SpinBox>("KomiInput").get_value() as f32;
+        let handicap_placement = match layer
+            .get_node_as::<OptionButton>("HandicapPlacementOption")
+            .get_selected()
+        {
+            1 => HandicapPlacement::Free,
+            _ => HandicapPlacement::Fixed,
+        };
+        GameSettingsDto {
+            board_size,
+            komi: handicap::handicap_komi(komi, handicap.max(0) as usize),
+            handicap,
+            handicap_placement,
+            color,
+            time_control,
+            is_private: layer.get_node_as::<CheckBox>("PrivateCheck").is_pressed(),
+        }
     }
 
     // #[func]
@@ -235,16 +986,91 @@ impl StartGameHud {
     //     self.base().get_node_as::<Self>("/root/Hud").free();
     // }
 
-    fn handle_ok_response(&mut self, response: &mut Response, label: &mut Label) {
-        let mut body: String = String::new();
-        let _ = response.read_to_string(&mut body);
-        let user_token = serde_json::from_str::<UserTokenDto>(body.trim());
-        self.user_token = user_token.ok();
-        godot_print_rich!("Body: {}", body);
-        godot_print_rich!("Sign response: {:?}", &response);
-        godot_print_rich!("Sign status: {}", response.status());
+    fn handle_ok_response(&mut self, user_token: UserTokenDto, label: &mut Label) {
+        debug!("Signed in: {:?}", user_token);
+        let remember = self
+            .base()
+            .get_node_as::<CanvasLayer>("AuthLayer")
+            .get_node_as::<CheckBox>("RememberMe")
+            .is_pressed();
+        if !remember {
+            self.forget_token();
+        } else if let Err(e) = self.token_store.save(&user_token) {
+            error!("Failed to remember the token: {}", e);
+        }
+        self.active_game = self.find_active_game(&user_token);
+        self.user_token = Some(user_token);
         label.set_text("");
         self.goto_players_list();
+        let has_active_game = self.active_game.is_some();
+        self.set_resume_visible(has_active_game);
+    }
+
+    /// Skip the login form with the token saved by "remember me"
+    fn sign_in_remembered(&mut self) {
+        let token = match self.token_store.load() {
+            Ok(Some(token)) => token,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Forgetting the remembered token: {}", e);
+                return self.forget_token();
+            }
+        };
+        let auth_layer = self.base().get_node_as::<CanvasLayer>("AuthLayer");
+        let mut sign_error_label = auth_layer.get_node_as::<Label>("SignError");
+        auth_layer
+            .get_node_as::<LineEdit>("LoginInput")
+            .set_text(&token.login);
+        auth_layer
+            .get_node_as::<CheckBox>("RememberMe")
+            .set_pressed(true);
+        match self.api.signin_with_token(&token) {
+            Ok(token) => self.handle_ok_response(token, &mut sign_error_label),
+            Err(e) if e.is_unauthorized() => {
+                self.forget_token();
+                sign_error_label.set_text("The session has expired. Sign in again.");
+            }
+            Err(e) => {
+                sign_error_label.set_text("The server is unavailable. Sign in later.");
+                error!("Error on the remembered signin: {}", e)
+            }
+        }
+    }
+
+    fn forget_token(&mut self) {
+        if let Err(e) = self.token_store.clear() {
+            error!("Failed to forget the token: {}", e);
+        }
+    }
+
+    /// Back to the login form, the remembered token is cleared
+    #[func]
+    fn on_logout(&mut self) {
+        debug!("Logout pressed");
+        if let Some(matchmaking_screen) = self.matchmaking_screen.as_mut() {
+            matchmaking_screen.bind_mut().leave();
+        }
+        self.forget_token();
+        self.user_token = None;
+        // The saved game stays for the same user's next sign in, only its prompt goes
+        self.active_game = None;
+        self.set_resume_visible(false);
+        let mut player_list_layer = self
+            .base_mut()
+            .get_node_as::<CanvasLayer>("PlayersListLayer");
+        player_list_layer
+            .get_node_as::<Timer>("SessionsRefreshTimer")
+            .stop();
+        player_list_layer.set_visible(false);
+        let mut auth_layer = self.base_mut().get_node_as::<CanvasLayer>("AuthLayer");
+        auth_layer.get_node_as::<LineEdit>("PassInput").clear();
+        auth_layer
+            .get_node_as::<CheckBox>("RememberMe")
+            .set_pressed(false);
+        auth_layer.set_visible(true);
+        auth_layer
+            .get_node_as::<LineEdit>("LoginInput")
+            .grab_focus();
     }
 
     fn goto_players_list(&mut self) {
@@ -258,48 +1084,32 @@ impl StartGameHud {
         player_list_layer
             .get_node_as::<Button>("SessionsRequestButton")
             .grab_focus();
-        godot_print_rich!("Switch layers: OK");
-    }
-
-    fn get_login(&mut self) -> Result<String, Error> {
-        let auth_layer = self.base_mut().get_node_as::<CanvasLayer>("AuthLayer");
-        let mut login_input = auth_layer.get_node_as::<LineEdit>("LoginInput");
-        let login = login_input.get_text().to_string();
-        if login.len() < 3 {
-            login_input.set_placeholder("Login length must be >= 3");
-            return Err(Error::ERR_INVALID_DATA);
-        }
-        Ok(login)
+        player_list_layer
+            .get_node_as::<Timer>("SessionsRefreshTimer")
+            .start();
+        self.on_lobby_refresh();
+        debug!("Switch layers: OK");
     }
 
-    fn get_pass(&mut self) -> Result<String, Error> {
-        let auth_layer = self.base_mut().get_node_as::<CanvasLayer>("AuthLayer");
-        let mut pass_input = auth_layer.get_node_as::<LineEdit>("PassInput");
-        let pass = pass_input.get_text().to_string();
-        if pass.len() < 4 {
-            pass_input.set_placeholder("Password length must be >= 4");
-            return Err(Error::ERR_INVALID_DATA);
-        }
-        Ok(pass)
+    fn auth_input(&self, name: &str) -> String {
+        self.base()
+            .get_node_as::<CanvasLayer>("AuthLayer")
+            .get_node_as::<LineEdit>(name)
+            .get_text()
+            .to_string()
     }
 
-    fn send_auth_request(
-        &mut self,
-        login: String,
-        pass: String,
-        uri: &str,
-    ) -> Result<Response, reqwest::Error> {
-        let mut body = HashMap::new();
-        body.insert("login", login);
-        body.insert("pass", pass);
-        let sign_res = self
-            .client
-            .post(format!("{}/{}", HOST, uri))
-            .json(&body)
-            .send();
-        match sign_res {
-            Ok(response) => Ok(response),
-            Err(e) => Err(e),
+    /// Show the errors under the fields of the login form, the other fields are cleared
+    fn show_auth_errors(&mut self, errors: &[FieldErrorDto]) {
+        let auth_layer = self.base().get_node_as::<CanvasLayer>("AuthLayer");
+        for (field, label) in [
+            (account::FIELD_LOGIN, "LoginError"),
+            (account::FIELD_PASS, "PassError"),
+            (account::FIELD_CONFIRM, "ConfirmError"),
+        ] {
+            auth_layer
+                .get_node_as::<Label>(label)
+                .set_text(account::error_for(errors, field).unwrap_or_default());
         }
     }
 }
@@ -307,14 +1117,28 @@ impl StartGameHud {
 #[godot_api]
 impl IControl for StartGameHud {
     fn init(base: Base<Self::Base>) -> Self {
-        godot_print_rich!("Init Hud: Begin");
-        let client = reqwest::blocking::Client::new();
+        debug!("Init Hud: Begin");
         let hud = StartGameHud {
-            client,
+            api: ApiClient::new(&preferences().server_url),
             user_token: None,
+            sessions: vec![],
+            session_filter: SessionFilter::default(),
+            session_sort: SessionSort::default(),
+            online_players: vec![],
+            active_sessions: vec![],
+            incoming_challenge: None,
+            outgoing_challenge: None,
+            active_game: None,
+            settings_screen: None,
+            account_screen: None,
+            matchmaking_screen: None,
+            token_store: TokenStore::new(
+                &globalize_path(REMEMBERED_TOKEN_PATH),
+                &globalize_path(TOKEN_SECRET_PATH),
+            ),
             base,
         };
-        godot_print_rich!("Init Hud: OK");
+        debug!("Init Hud: OK");
         hud
     }
 
@@ -326,10 +1150,41 @@ impl IControl for StartGameHud {
 
         auth_layer.set_visible(true);
         player_list_layer.set_visible(false);
-        godot_print_rich!("Set layers: OK");
+        self.base_mut()
+            .get_node_as::<CanvasLayer>("CreateGameLayer")
+            .set_visible(false);
+        self.hide_invitation();
+        crash::set_scene("Hud");
+        apply_global(&preferences());
+        self.settings_screen = Some(SettingsScreen::attach(self.base().clone().upcast()));
+        let mut account_screen = AccountScreen::attach(self.base().clone().upcast());
+        account_screen.connect("account_deleted", &self.base().callable("on_logout"));
+        self.account_screen = Some(account_screen);
+        let mut matchmaking_screen = MatchmakingScreen::attach(self.base().clone().upcast());
+        matchmaking_screen.connect("matched", &self.base().callable("on_match_found"));
+        self.matchmaking_screen = Some(matchmaking_screen);
+        self.base_mut().add_to_group(PREFERENCES_GROUP);
+        crash::set_session_id(None);
+        LogConsole::attach(self.base().clone().upcast());
+        self.set_resume_visible(false);
+        debug!("Set layers: OK");
+
+        let mut refresh_timer = player_list_layer.get_node_as::<Timer>("SessionsRefreshTimer");
+        refresh_timer.set_wait_time(preferences().poll_interval_secs());
+        refresh_timer.connect("timeout", &self.base().callable("on_lobby_refresh"));
 
+        if let Some(settings) = EngineSettings::load(&globalize_path(ENGINE_SETTINGS_PATH)) {
+            auth_layer
+                .get_node_as::<LineEdit>("EnginePathInput")
+                .set_text(&settings.command_line());
+        }
         // let auth_layer = self.base_mut().get_node_as::<CanvasLayer>("AuthLayer");
         let mut login_input = auth_layer.get_node_as::<LineEdit>("LoginInput");
         login_input.grab_focus();
+        // The recovery dialog decides what happens to the game left by the crash
+        match crash::take_report() {
+            Some(report_path) => self.show_crash_recovery(&report_path),
+            None => self.sign_in_remembered(),
+        }
     }
 }
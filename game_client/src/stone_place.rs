@@ -1,5 +1,6 @@
 use godot::classes::{IMarker2D, InputEvent, Marker2D};
 use godot::prelude::*;
+use log::debug;
 
 #[derive(GodotClass)]
 #[class(base=Marker2D)]
@@ -18,7 +19,7 @@ impl StonePlace {
 #[godot_api]
 impl IMarker2D for StonePlace {
     fn init(base: Base<Self::Base>) -> Self {
-        godot_print!("Make a StonePlace container...");
+        debug!("Make a StonePlace container...");
         Self {
             input: Input::singleton(),
             base,
@@ -35,14 +36,14 @@ impl IMarker2D for StonePlace {
         {
             let row = self.base().get_meta("Row");
             let col = self.base().get_meta("Col");
-            godot_print!(
+            debug!(
                 "Stone is putted to {}:{}. Position: {}",
                 row,
                 col,
                 self.base().get_global_position()
             );
             // let mut m_scn: Gd<PackedScene> = load("res://content/scenes/Master.tscn");
-            godot_print!("Emit 'user_step' signal");
+            debug!("Emit 'user_step' signal");
             self.base_mut().emit_signal("user_step", &[col, row]);
         }
     }
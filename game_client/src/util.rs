@@ -1,9 +1,27 @@
-use std::time::SystemTime;
+use std::{path::PathBuf, time::SystemTime};
 
 use chrono::{DateTime, Utc};
+use godot::classes::ProjectSettings;
 
 pub fn get_format_time(format: Option<&str>) -> String {
     let now = SystemTime::now();
     let datetime: DateTime<Utc> = now.into();
     format!("{}", datetime.format(format.unwrap_or("%d/%m/%Y'T'%T")))
 }
+
+pub fn get_unix_time() -> i64 {
+    Utc::now().timestamp()
+}
+
+pub fn get_unix_time_ms() -> i64 {
+    Utc::now().timestamp_millis()
+}
+
+/// Convert a Godot `user://` or `res://` path to a filesystem path
+pub fn globalize_path(path: &str) -> PathBuf {
+    PathBuf::from(
+        ProjectSettings::singleton()
+            .globalize_path(path)
+            .to_string(),
+    )
+}
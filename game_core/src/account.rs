@@ -0,0 +1,143 @@
+use crate::dto::FieldErrorDto;
+
+pub const FIELD_LOGIN: &str = "login";
+pub const FIELD_PASS: &str = "pass";
+pub const FIELD_CONFIRM: &str = "confirm";
+pub const FIELD_OLD_PASS: &str = "old_pass";
+pub const FIELD_NEW_PASS: &str = "new_pass";
+pub const FIELD_DISPLAY_NAME: &str = "display_name";
+pub const FIELD_AVATAR: &str = "avatar";
+
+pub const MIN_LOGIN_LEN: usize = 3;
+pub const MAX_LOGIN_LEN: usize = 24;
+pub const MIN_PASS_LEN: usize = 6;
+pub const MAX_PASS_LEN: usize = 64;
+pub const MAX_DISPLAY_NAME_LEN: usize = 32;
+
+/// Avatars the user can choose from, the textures are named after them
+pub const AVATARS: [&str; 6] = [
+    "black_stone",
+    "white_stone",
+    "tiger",
+    "crane",
+    "dragon",
+    "turtle",
+];
+
+/// Error of one form field, shown next to it
+pub fn field_error(field: &str, message: impl Into<String>) -> FieldErrorDto {
+    FieldErrorDto {
+        field: field.to_string(),
+        message: message.into(),
+    }
+}
+
+/// Letters, digits, `_` and `-`, the length is counted in characters
+///
+/// # Examples
+///
+/// ```
+/// use game_core::account::validate_login;
+///
+/// assert_eq!(None, validate_login("go_player-1"));
+/// assert!(validate_login("ab").is_some());
+/// assert!(validate_login("white space").is_some());
+/// ```
+pub fn validate_login(login: &str) -> Option<String> {
+    let len = login.chars().count();
+    if !(MIN_LOGIN_LEN..=MAX_LOGIN_LEN).contains(&len) {
+        return Some(format!(
+            "Must be {} to {} characters",
+            MIN_LOGIN_LEN, MAX_LOGIN_LEN
+        ));
+    }
+    if !login
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    {
+        return Some("Only letters, digits, _ and - are allowed".to_string());
+    }
+    None
+}
+
+/// At least one letter and one digit
+pub fn validate_pass(pass: &str) -> Option<String> {
+    let len = pass.chars().count();
+    if !(MIN_PASS_LEN..=MAX_PASS_LEN).contains(&len) {
+        return Some(format!(
+            "Must be {} to {} characters",
+            MIN_PASS_LEN, MAX_PASS_LEN
+        ));
+    }
+    if !pass.chars().any(char::is_alphabetic) || !pass.chars().any(|c| c.is_ascii_digit()) {
+        return Some("Must contain a letter and a digit".to_string());
+    }
+    None
+}
+
+pub fn validate_display_name(display_name: &str) -> Option<String> {
+    if display_name.chars().count() > MAX_DISPLAY_NAME_LEN {
+        return Some(format!("At most {} characters", MAX_DISPLAY_NAME_LEN));
+    }
+    if display_name.chars().any(char::is_control) {
+        return Some("Control characters are not allowed".to_string());
+    }
+    None
+}
+
+pub fn validate_avatar(avatar: &str) -> Option<String> {
+    (!AVATARS.contains(&avatar)).then(|| "Unknown avatar".to_string())
+}
+
+/// Sign in only needs both fields, the rules may have changed since the signup
+pub fn validate_signin(login: &str, pass: &str) -> Vec<FieldErrorDto> {
+    let mut errors = vec![];
+    if login.is_empty() {
+        errors.push(field_error(FIELD_LOGIN, "Enter the login"));
+    }
+    if pass.is_empty() {
+        errors.push(field_error(FIELD_PASS, "Enter the password"));
+    }
+    errors
+}
+
+pub fn validate_signup(login: &str, pass: &str, confirm: &str) -> Vec<FieldErrorDto> {
+    let mut errors = vec![];
+    if let Some(message) = validate_login(login) {
+        errors.push(field_error(FIELD_LOGIN, message));
+    }
+    if let Some(message) = validate_pass(pass) {
+        errors.push(field_error(FIELD_PASS, message));
+    }
+    if pass != confirm {
+        errors.push(field_error(FIELD_CONFIRM, "Passwords don't match"));
+    }
+    errors
+}
+
+pub fn validate_pass_change(old_pass: &str, new_pass: &str, confirm: &str) -> Vec<FieldErrorDto> {
+    let mut errors = vec![];
+    if old_pass.is_empty() {
+        errors.push(field_error(FIELD_OLD_PASS, "Enter the current password"));
+    }
+    if let Some(message) = validate_pass(new_pass) {
+        errors.push(field_error(FIELD_NEW_PASS, message));
+    } else if new_pass == old_pass {
+        errors.push(field_error(
+            FIELD_NEW_PASS,
+            "Must differ from the current one",
+        ));
+    }
+    if new_pass != confirm {
+        errors.push(field_error(FIELD_CONFIRM, "Passwords don't match"));
+    }
+    errors
+}
+
+/// The message for the field, the first one when there are several
+pub fn error_for<'a>(errors: &'a [FieldErrorDto], field: &str) -> Option<&'a str> {
+    errors
+        .iter()
+        .find(|e| e.field == field)
+        .map(|e| e.message.as_str())
+}
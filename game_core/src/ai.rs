@@ -0,0 +1,299 @@
+use std::{
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    rules::{GoGame, Move},
+    scoring::{self, BoardGrid, Point},
+};
+
+/// Exploration constant of the UCT formula
+const EXPLORATION: f64 = 1.4;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Difficulty {
+    #[default]
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl From<i32> for Difficulty {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::Medium,
+            2 => Self::Hard,
+            _ => Self::Easy,
+        }
+    }
+}
+
+/// The search stops at whichever limit is reached first
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchBudget {
+    pub playouts: u32,
+    pub time: Duration,
+}
+
+impl From<Difficulty> for SearchBudget {
+    fn from(value: Difficulty) -> Self {
+        match value {
+            Difficulty::Easy => Self {
+                playouts: 200,
+                time: Duration::from_millis(500),
+            },
+            Difficulty::Medium => Self {
+                playouts: 2_000,
+                time: Duration::from_secs(2),
+            },
+            Difficulty::Hard => Self {
+                playouts: 20_000,
+                time: Duration::from_secs(8),
+            },
+        }
+    }
+}
+
+/// Xorshift generator, playouts only need cheap and reproducible randomness
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+struct Node {
+    /// The move leading to this node, `None` is a pass
+    point: Option<Point>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<Option<Point>>,
+    visits: u32,
+    /// Wins of the player who made the move leading to this node
+    wins: f64,
+}
+
+impl Node {
+    fn new(point: Option<Point>, parent: Option<usize>, game: &GoGame) -> Self {
+        let mut untried: Vec<Option<Point>> = candidate_moves(game).into_iter().map(Some).collect();
+        untried.push(None);
+        Self {
+            point,
+            parent,
+            children: vec![],
+            untried,
+            visits: 0,
+            wins: 0.0,
+        }
+    }
+
+    fn uct(&self, parent_visits: u32) -> f64 {
+        self.wins / self.visits as f64
+            + EXPLORATION * ((parent_visits as f64).ln() / self.visits as f64).sqrt()
+    }
+}
+
+/// A point surrounded by own stones only, filling it would be self destructive
+fn is_own_eye(board: &BoardGrid, point: Point, color: bool) -> bool {
+    scoring::get(board, point).is_none()
+        && scoring::neighbors(board, point).all(|n| scoring::get(board, n) == Some(color))
+}
+
+/// Legal moves without filling own eyes
+fn candidate_moves(game: &GoGame) -> Vec<Point> {
+    game.legal_moves()
+        .into_iter()
+        .filter(|point| !is_own_eye(game.board(), *point, game.to_move()))
+        .collect()
+}
+
+fn apply(game: &mut GoGame, point: Option<Point>) {
+    let _ = match point {
+        Some(point) => game.play(point).map(|_| ()),
+        None => game.pass(),
+    };
+}
+
+/// Area score difference (black - white - komi) of a finished playout
+fn playout_result(game: &GoGame) -> f32 {
+    let board = game.board();
+    let owners = scoring::territory(board, &Default::default());
+    let mut black = 0;
+    let mut white = 0;
+    for (row_num, row) in board.iter().enumerate() {
+        for (col_num, stone) in row.iter().enumerate() {
+            match stone.or(owners[row_num][col_num]) {
+                Some(true) => black += 1,
+                Some(false) => white += 1,
+                None => {}
+            }
+        }
+    }
+    black as f32 - white as f32 - game.komi()
+}
+
+/// Play random non eye filling moves until both players pass
+fn playout(game: &mut GoGame, rng: &mut Rng) {
+    let max_moves = game.size() * game.size() * 2;
+    for _ in 0..max_moves {
+        if game.is_over() {
+            break;
+        }
+        let color = game.to_move();
+        let mut empty: Vec<Point> = (0..game.size())
+            .flat_map(|row| (0..game.size()).map(move |col| (row, col)))
+            .filter(|point| !is_own_eye(game.board(), *point, color))
+            .filter(|point| scoring::get(game.board(), *point).is_none())
+            .collect();
+        let mut played = false;
+        while !empty.is_empty() {
+            let point = empty.swap_remove(rng.below(empty.len()));
+            if game.play(point).is_ok() {
+                played = true;
+                break;
+            }
+        }
+        if !played {
+            let _ = game.pass();
+        }
+    }
+}
+
+/// Choose a move for the side to move with Monte Carlo tree search
+pub fn best_move(game: &GoGame, budget: SearchBudget, seed: u64) -> Move {
+    let color = game.to_move();
+    let mut rng = Rng::new(seed);
+    let mut nodes = vec![Node::new(None, None, game)];
+    let started = Instant::now();
+    let mut playouts = 0;
+    while playouts < budget.playouts && started.elapsed() < budget.time {
+        playouts += 1;
+        let mut current = 0;
+        let mut state = game.clone();
+        // Selection
+        while nodes[current].untried.is_empty() && !nodes[current].children.is_empty() {
+            let parent_visits = nodes[current].visits;
+            current = *nodes[current]
+                .children
+                .iter()
+                .max_by(|a, b| {
+                    nodes[**a]
+                        .uct(parent_visits)
+                        .total_cmp(&nodes[**b].uct(parent_visits))
+                })
+                .unwrap();
+            apply(&mut state, nodes[current].point);
+        }
+        // Expansion
+        if !nodes[current].untried.is_empty() && !state.is_over() {
+            let index = rng.below(nodes[current].untried.len());
+            let point = nodes[current].untried.swap_remove(index);
+            apply(&mut state, point);
+            nodes.push(Node::new(point, Some(current), &state));
+            let child = nodes.len() - 1;
+            nodes[current].children.push(child);
+            current = child;
+        }
+        // Simulation
+        playout(&mut state, &mut rng);
+        let black_won = playout_result(&state) > 0.0;
+        // Backpropagation: the player who moved into a node is the opposite of the
+        // side to move at the node, the root is reached through the AI's own moves
+        let mut node = Some(current);
+        let mut mover = if depth(&nodes, current) % 2 == 1 {
+            color
+        } else {
+            !color
+        };
+        while let Some(index) = node {
+            nodes[index].visits += 1;
+            if mover == black_won {
+                nodes[index].wins += 1.0;
+            }
+            mover = !mover;
+            node = nodes[index].parent;
+        }
+    }
+    let best = nodes[0]
+        .children
+        .iter()
+        .max_by_key(|child| nodes[**child].visits)
+        .and_then(|child| nodes[*child].point);
+    match best {
+        Some(point) => Move::Play { point, color },
+        None => Move::Pass { color },
+    }
+}
+
+fn depth(nodes: &[Node], mut index: usize) -> usize {
+    let mut depth = 0;
+    while let Some(parent) = nodes[index].parent {
+        depth += 1;
+        index = parent;
+    }
+    depth
+}
+
+/// A computer opponent searching on a background thread so the scene stays responsive
+pub struct AiPlayer {
+    pub color: bool,
+    pub budget: SearchBudget,
+    receiver: Option<Receiver<Move>>,
+}
+
+impl AiPlayer {
+    pub fn new(color: bool, difficulty: Difficulty) -> Self {
+        Self {
+            color,
+            budget: SearchBudget::from(difficulty),
+            receiver: None,
+        }
+    }
+
+    pub fn is_thinking(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    pub fn start_thinking(&mut self, game: &GoGame) {
+        if self.is_thinking() {
+            return;
+        }
+        let (sender, receiver) = mpsc::channel();
+        let game = game.clone();
+        let budget = self.budget;
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(1);
+        thread::spawn(move || {
+            let _ = sender.send(best_move(&game, budget, seed));
+        });
+        self.receiver = Some(receiver);
+    }
+
+    /// The chosen move once the search is finished
+    pub fn poll(&mut self) -> Option<Move> {
+        let result = match self.receiver.as_ref()?.try_recv() {
+            Ok(m) => Some(m),
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => None,
+        };
+        self.receiver = None;
+        result
+    }
+}
@@ -0,0 +1,314 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+    sync::Mutex,
+};
+
+use reqwest::{
+    blocking::{Client, RequestBuilder, Response},
+    Method, StatusCode,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::dto::{
+    AccountDeleteDto, ChallengeAnswerDto, ChallengeDto, ChallengeRequestDto, ChatMessageDto,
+    ChatRequestDto, FieldErrorDto, GameRecordDto, GameStateDto, MatchmakingRequestDto,
+    MatchmakingTicketDto, PasswordChangeDto, ProfileDto, ProfileUpdateDto, UserScoringRequestDto,
+    UserSessionDto, UserSessionRequestDto, UserSessionStepDto, UserStatusDto, UserStepRequestDto,
+    UserTokenDto, UserUndoAnswerDto, UserUndoRequestDto, ValidationErrorDto,
+};
+
+pub const DEFAULT_HOST: &str = "http://localhost:8080";
+
+const RECENT_RESPONSES: usize = 32;
+
+/// The last requests of all clients with their status, kept for crash reports
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+fn record_response(line: String) {
+    let mut recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+    recent.push_back(line);
+    while recent.len() > RECENT_RESPONSES {
+        recent.pop_front();
+    }
+}
+
+/// The last requests as "METHOD /path -> status", oldest first
+pub fn recent_responses() -> Vec<String> {
+    let recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+    recent.iter().cloned().collect()
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    /// The server is unreachable or the body is not the expected JSON
+    Http(reqwest::Error),
+    Status(StatusCode),
+    /// Fields of the request refused by the server
+    Validation(Vec<FieldErrorDto>),
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::Http(e) => write!(f, "{}", e),
+            ApiError::Status(status) => write!(f, "Status: {}", status),
+            ApiError::Validation(errors) => {
+                let errors: Vec<String> = errors.iter().map(FieldErrorDto::to_string).collect();
+                write!(f, "{}", errors.join(", "))
+            }
+        }
+    }
+}
+
+impl ApiError {
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            ApiError::Http(e) => e.status().map(|s| s.as_u16()),
+            ApiError::Status(status) => Some(status.as_u16()),
+            ApiError::Validation(_) => Some(StatusCode::BAD_REQUEST.as_u16()),
+        }
+    }
+
+    /// The token was refused, the user has to sign in again
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(
+            self,
+            ApiError::Status(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+        )
+    }
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(e: reqwest::Error) -> Self {
+        ApiError::Http(e)
+    }
+}
+
+/// Field errors of a 400 or 422 response, only the status when the body has none
+fn status_error(response: Response) -> ApiError {
+    let status = response.status();
+    if status != StatusCode::BAD_REQUEST && status != StatusCode::UNPROCESSABLE_ENTITY {
+        return ApiError::Status(status);
+    }
+    match response.json::<ValidationErrorDto>() {
+        Ok(body) if !body.errors.is_empty() => ApiError::Validation(body.errors),
+        _ => ApiError::Status(status),
+    }
+}
+
+/// Blocking client of the backend REST API, every body is JSON
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    client: Client,
+    host: String,
+}
+
+impl Default for ApiClient {
+    fn default() -> Self {
+        Self::new(DEFAULT_HOST)
+    }
+}
+
+impl ApiClient {
+    pub fn new(host: &str) -> Self {
+        Self {
+            client: Client::new(),
+            host: host.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    fn request(&self, method: Method, uri: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}/{}", self.host, uri))
+            .header("Content-Type", "application/json")
+    }
+
+    fn json_request<B: Serialize>(&self, method: Method, uri: &str, body: &B) -> RequestBuilder {
+        self.request(method, uri)
+            .body(serde_json::to_string(body).unwrap_or("{}".to_string()))
+    }
+
+    fn execute(&self, request: RequestBuilder) -> Result<Response, ApiError> {
+        let request = request.build()?;
+        let line = format!("{} {}", request.method(), request.url().path());
+        match self.client.execute(request) {
+            Ok(response) => {
+                record_response(format!("{} -> {}", line, response.status()));
+                Ok(response)
+            }
+            Err(e) => {
+                record_response(format!("{} -> {}", line, e));
+                Err(e.into())
+            }
+        }
+    }
+
+    fn send(&self, request: RequestBuilder) -> Result<(), ApiError> {
+        let response = self.execute(request)?;
+        match response.status() {
+            StatusCode::OK => Ok(()),
+            _ => Err(status_error(response)),
+        }
+    }
+
+    fn fetch<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, ApiError> {
+        let response = self.execute(request)?;
+        match response.status() {
+            StatusCode::OK => Ok(response.json()?),
+            _ => Err(status_error(response)),
+        }
+    }
+
+    fn auth(&self, uri: &str, login: &str, pass: &str) -> Result<UserTokenDto, ApiError> {
+        let body = HashMap::from([("login", login), ("pass", pass)]);
+        self.fetch(self.json_request(Method::POST, uri, &body))
+    }
+
+    pub fn signup(&self, login: &str, pass: &str) -> Result<UserTokenDto, ApiError> {
+        self.auth("signup", login, pass)
+    }
+
+    pub fn signin(&self, login: &str, pass: &str) -> Result<UserTokenDto, ApiError> {
+        self.auth("signin", login, pass)
+    }
+
+    /// Sign in again with a remembered token, `Unauthorized` once it has expired
+    pub fn signin_with_token(&self, token: &UserTokenDto) -> Result<UserTokenDto, ApiError> {
+        self.fetch(self.json_request(Method::POST, "signin/token", token))
+    }
+
+    pub fn profile(&self, user_id: i64) -> Result<ProfileDto, ApiError> {
+        self.fetch(self.request(Method::GET, &format!("user/{}/profile", user_id)))
+    }
+
+    pub fn update_profile(&self, request: &ProfileUpdateDto) -> Result<ProfileDto, ApiError> {
+        self.fetch(self.json_request(Method::PATCH, "user/profile", request))
+    }
+
+    /// The token stays valid after the change
+    pub fn change_password(&self, request: &PasswordChangeDto) -> Result<(), ApiError> {
+        self.send(self.json_request(Method::PATCH, "user/password", request))
+    }
+
+    pub fn delete_account(&self, request: &AccountDeleteDto) -> Result<(), ApiError> {
+        self.send(self.json_request(Method::DELETE, "user", request))
+    }
+
+    /// Public sessions waiting for an opponent
+    pub fn vacant_sessions(&self, token: &UserTokenDto) -> Result<Vec<UserSessionDto>, ApiError> {
+        self.fetch(self.json_request(Method::GET, "session", token))
+    }
+
+    /// Sessions being played, open to spectators
+    pub fn active_sessions(&self, token: &UserTokenDto) -> Result<Vec<UserSessionDto>, ApiError> {
+        self.fetch(self.json_request(Method::GET, "session/active", token))
+    }
+
+    pub fn online_users(&self, token: &UserTokenDto) -> Result<Vec<UserStatusDto>, ApiError> {
+        self.fetch(self.json_request(Method::GET, "user/online", token))
+    }
+
+    pub fn create_session(
+        &self,
+        request: &UserSessionRequestDto,
+    ) -> Result<UserSessionDto, ApiError> {
+        self.fetch(self.json_request(Method::POST, "session", request))
+    }
+
+    /// Join by the session id or by the join code of a private session
+    pub fn join_session(
+        &self,
+        request: &UserSessionRequestDto,
+    ) -> Result<UserSessionDto, ApiError> {
+        self.fetch(self.json_request(Method::PATCH, "session", request))
+    }
+
+    pub fn session(&self, session_id: &str) -> Result<UserSessionDto, ApiError> {
+        self.fetch(self.request(Method::GET, &format!("session/{}", session_id)))
+    }
+
+    pub fn game_state(&self, request: &UserSessionRequestDto) -> Result<GameStateDto, ApiError> {
+        self.fetch(self.json_request(Method::POST, "game/state", request))
+    }
+
+    pub fn play(&self, request: &UserStepRequestDto) -> Result<(), ApiError> {
+        self.send(self.json_request(Method::PATCH, "game/state", request))
+    }
+
+    pub fn pass(&self, session: &UserSessionStepDto) -> Result<(), ApiError> {
+        self.send(self.json_request(Method::PATCH, "game/pass", session))
+    }
+
+    /// Ask the server to check the flag, it decides about the time loss
+    pub fn claim_timeout(&self, session: &UserSessionStepDto) -> Result<(), ApiError> {
+        self.send(self.json_request(Method::PATCH, "game/timeout", session))
+    }
+
+    pub fn mark_dead_stones(&self, request: &UserScoringRequestDto) -> Result<(), ApiError> {
+        self.send(self.json_request(Method::PATCH, "game/scoring", request))
+    }
+
+    pub fn accept_score(&self, session: &UserSessionStepDto) -> Result<(), ApiError> {
+        self.send(self.json_request(Method::POST, "game/scoring/accept", session))
+    }
+
+    pub fn request_undo(&self, request: &UserUndoRequestDto) -> Result<(), ApiError> {
+        self.send(self.json_request(Method::POST, "game/undo", request))
+    }
+
+    pub fn answer_undo(&self, answer: &UserUndoAnswerDto) -> Result<(), ApiError> {
+        self.send(self.json_request(Method::POST, "game/undo/answer", answer))
+    }
+
+    pub fn game_record(&self, session_id: &str) -> Result<GameRecordDto, ApiError> {
+        self.fetch(self.request(Method::GET, &format!("game/record/{}", session_id)))
+    }
+
+    pub fn send_chat(&self, request: &ChatRequestDto) -> Result<(), ApiError> {
+        self.send(self.json_request(Method::POST, "chat", request))
+    }
+
+    /// Messages of the session with ids greater than `after`
+    pub fn chat(&self, session_id: &str, after: i64) -> Result<Vec<ChatMessageDto>, ApiError> {
+        let uri = format!("chat/{}?after={}", session_id, after);
+        self.fetch(self.request(Method::GET, &uri))
+    }
+
+    pub fn challenges(&self, token: &UserTokenDto) -> Result<Vec<ChallengeDto>, ApiError> {
+        self.fetch(self.json_request(Method::GET, "challenge", token))
+    }
+
+    pub fn challenge(&self, request: &ChallengeRequestDto) -> Result<ChallengeDto, ApiError> {
+        self.fetch(self.json_request(Method::POST, "challenge", request))
+    }
+
+    pub fn answer_challenge(&self, answer: &ChallengeAnswerDto) -> Result<(), ApiError> {
+        self.send(self.json_request(Method::PATCH, "challenge", answer))
+    }
+
+    /// Join the matchmaking queue, a queued user only gets the preferences replaced
+    pub fn find_game(
+        &self,
+        request: &MatchmakingRequestDto,
+    ) -> Result<MatchmakingTicketDto, ApiError> {
+        self.fetch(self.json_request(Method::POST, "matchmaking", request))
+    }
+
+    /// Polled while searching, the ticket carries the session once matched
+    pub fn matchmaking_ticket(
+        &self,
+        token: &UserTokenDto,
+    ) -> Result<MatchmakingTicketDto, ApiError> {
+        self.fetch(self.json_request(Method::GET, "matchmaking", token))
+    }
+
+    /// Leave the queue, fails with 409 when the match was already made
+    pub fn cancel_matchmaking(&self, token: &UserTokenDto) -> Result<(), ApiError> {
+        self.send(self.json_request(Method::DELETE, "matchmaking", token))
+    }
+}
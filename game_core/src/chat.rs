@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+
+use go_udp_request::{
+    chat::chat_message::{self, ChatMessage},
+    request::chat_request::ChatRequest,
+};
+
+use crate::dto::{ChatChannel, ChatMessageDto, ChatRequestDto};
+
+/// Messages of the game chat and the authors muted by the user
+#[derive(Debug, Clone, Default)]
+pub struct ChatHistory {
+    messages: Vec<ChatMessageDto>,
+    muted: HashSet<String>,
+}
+
+impl ChatHistory {
+    /// Add messages fetched from the server, already known ids are skipped
+    pub fn merge(&mut self, messages: Vec<ChatMessageDto>) -> bool {
+        let known: HashSet<i64> = self.messages.iter().map(|m| m.message_id).collect();
+        let len = self.messages.len();
+        self.messages.extend(
+            messages
+                .into_iter()
+                .filter(|m| !known.contains(&m.message_id)),
+        );
+        self.messages.sort_by_key(|m| m.message_id);
+        self.messages.len() != len
+    }
+
+    pub fn last_message_id(&self) -> Option<i64> {
+        self.messages.last().map(|m| m.message_id)
+    }
+
+    /// Mute or unmute the author, returns true when the author is muted now
+    pub fn toggle_mute(&mut self, login: &str) -> bool {
+        if self.muted.remove(login) {
+            return false;
+        }
+        self.muted.insert(login.to_string());
+        true
+    }
+
+    pub fn is_muted(&self, login: &str) -> bool {
+        self.muted.contains(login)
+    }
+
+    /// Messages shown to the viewer: players don't see the spectators channel
+    /// during the game, muted authors are hidden
+    pub fn visible(&self, spectator: bool) -> Vec<&ChatMessageDto> {
+        self.messages
+            .iter()
+            .filter(|m| spectator || m.channel == ChatChannel::Players)
+            .filter(|m| !self.is_muted(&m.login))
+            .collect()
+    }
+}
+
+/// Chat line like "[12:30:05] alice: hi", spectators are marked
+pub fn format_message(message: &ChatMessageDto) -> String {
+    let mark = match message.channel {
+        ChatChannel::Players => "",
+        ChatChannel::Spectators => " (spectator)",
+    };
+    format!(
+        "[{}] {}{}: {}",
+        message.sent_at, message.login, mark, message.text
+    )
+}
+
+/// The same message for the UDP transport
+pub fn to_udp_request(login: &str, request: &ChatRequestDto) -> ChatRequest {
+    let channel = match request.channel {
+        ChatChannel::Players => chat_message::ChatChannel::Players,
+        ChatChannel::Spectators => chat_message::ChatChannel::Spectators,
+    };
+    ChatRequest::builder()
+        .message(ChatMessage {
+            author: login.into(),
+            text: request.text.as_str().into(),
+            channel,
+        })
+        .session(request.session.session_id.as_str().into())
+        .build()
+}
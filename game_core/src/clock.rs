@@ -0,0 +1,226 @@
+use crate::dto::{ClockDto, PlayerClockDto, TimeControl};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PlayerClock {
+    pub main_ms: i64,
+    /// Time left in the current overtime period
+    pub period_ms: i64,
+    /// Byo-yomi periods left
+    pub periods: u32,
+    /// Stones left to play in the current Canadian overtime period
+    pub stones: u32,
+    pub flagged: bool,
+}
+
+impl PlayerClock {
+    pub fn new(control: &TimeControl) -> Self {
+        match *control {
+            TimeControl::None => Self::default(),
+            TimeControl::Absolute { main_time_secs }
+            | TimeControl::Fischer { main_time_secs, .. } => Self {
+                main_ms: main_time_secs as i64 * 1000,
+                ..Default::default()
+            },
+            TimeControl::ByoYomi {
+                main_time_secs,
+                period_secs,
+                periods,
+            } => Self {
+                main_ms: main_time_secs as i64 * 1000,
+                period_ms: period_secs as i64 * 1000,
+                periods,
+                ..Default::default()
+            },
+            TimeControl::Canadian {
+                main_time_secs,
+                period_secs,
+                stones,
+            } => Self {
+                main_ms: main_time_secs as i64 * 1000,
+                period_ms: period_secs as i64 * 1000,
+                stones,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn in_overtime(&self) -> bool {
+        self.main_ms <= 0
+    }
+
+    /// Spend `ms` of thinking time
+    pub fn elapse(&mut self, control: &TimeControl, ms: i64) {
+        if self.flagged || ms <= 0 {
+            return;
+        }
+        if let TimeControl::None = control {
+            return;
+        }
+        self.main_ms -= ms;
+        if self.main_ms > 0 {
+            return;
+        }
+        let overflow = -self.main_ms;
+        self.main_ms = 0;
+        match *control {
+            TimeControl::ByoYomi { period_secs, .. } => {
+                self.period_ms -= overflow;
+                while self.period_ms <= 0 && self.periods > 1 {
+                    self.periods -= 1;
+                    self.period_ms += period_secs as i64 * 1000;
+                }
+                if self.period_ms <= 0 {
+                    self.period_ms = 0;
+                    self.periods = 0;
+                    self.flagged = true;
+                }
+            }
+            TimeControl::Canadian { .. } => {
+                self.period_ms -= overflow;
+                if self.period_ms <= 0 {
+                    self.period_ms = 0;
+                    self.flagged = true;
+                }
+            }
+            _ => self.flagged = true,
+        }
+    }
+
+    /// Apply the increment or overtime reset after the player moved
+    pub fn on_move(&mut self, control: &TimeControl) {
+        if self.flagged {
+            return;
+        }
+        match *control {
+            TimeControl::Fischer { increment_secs, .. } => {
+                self.main_ms += increment_secs as i64 * 1000;
+            }
+            TimeControl::ByoYomi { period_secs, .. } if self.in_overtime() => {
+                self.period_ms = period_secs as i64 * 1000;
+            }
+            TimeControl::Canadian {
+                period_secs,
+                stones,
+                ..
+            } if self.in_overtime() => {
+                self.stones = self.stones.saturating_sub(1);
+                if self.stones == 0 {
+                    self.stones = stones;
+                    self.period_ms = period_secs as i64 * 1000;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Clock text like "04:59", "00:21 (3x)" in byo-yomi or "03:10 /12" in Canadian overtime
+    pub fn format(&self, control: &TimeControl) -> String {
+        let format_ms = |ms: i64| {
+            let secs = (ms.max(0) + 999) / 1000;
+            format!("{:02}:{:02}", secs / 60, secs % 60)
+        };
+        match control {
+            TimeControl::None => "--:--".to_string(),
+            TimeControl::ByoYomi { .. } if self.in_overtime() => {
+                format!("{} ({}x)", format_ms(self.period_ms), self.periods)
+            }
+            TimeControl::Canadian { .. } if self.in_overtime() => {
+                format!("{} /{}", format_ms(self.period_ms), self.stones)
+            }
+            _ => format_ms(self.main_ms),
+        }
+    }
+}
+
+impl From<&PlayerClockDto> for PlayerClock {
+    fn from(value: &PlayerClockDto) -> Self {
+        Self {
+            main_ms: value.main_time_ms,
+            period_ms: value.period_time_ms,
+            periods: value.periods,
+            stones: value.stones,
+            flagged: false,
+        }
+    }
+}
+
+/// Both players' clocks counting down locally between server updates
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameClock {
+    pub control: TimeControl,
+    pub black: PlayerClock,
+    pub white: PlayerClock,
+    /// Color of the running clock, `None` while the clock is paused
+    pub running: Option<bool>,
+    /// Estimated `server time - local time` in milliseconds
+    offset_ms: Option<i64>,
+}
+
+impl GameClock {
+    pub fn new(control: TimeControl) -> Self {
+        Self {
+            black: PlayerClock::new(&control),
+            white: PlayerClock::new(&control),
+            control,
+            running: None,
+            offset_ms: None,
+        }
+    }
+
+    pub fn player(&self, color: bool) -> &PlayerClock {
+        if color {
+            &self.black
+        } else {
+            &self.white
+        }
+    }
+
+    fn player_mut(&mut self, color: bool) -> &mut PlayerClock {
+        if color {
+            &mut self.black
+        } else {
+            &mut self.white
+        }
+    }
+
+    pub fn tick(&mut self, elapsed_ms: i64) {
+        if let Some(color) = self.running {
+            let control = self.control;
+            self.player_mut(color).elapse(&control, elapsed_ms);
+        }
+    }
+
+    /// Stop the mover's clock and start the opponent's one
+    pub fn switch(&mut self, moved: bool) {
+        let control = self.control;
+        self.player_mut(moved).on_move(&control);
+        self.running = Some(!moved);
+    }
+
+    /// The color which ran out of time
+    pub fn flagged(&self) -> Option<bool> {
+        match (self.black.flagged, self.white.flagged) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Replace the local state by the server snapshot.
+    /// The snapshot was taken at `server_time_ms`, so the running clock is charged
+    /// with the time elapsed since then, estimated from a smoothed clock offset
+    /// where every sample assumes the response took half of the round trip.
+    pub fn sync(&mut self, clock: &ClockDto, local_now_ms: i64, rtt_ms: i64) {
+        let sample = clock.server_time_ms + rtt_ms / 2 - local_now_ms;
+        let offset = match self.offset_ms {
+            Some(offset) => (offset * 3 + sample) / 4,
+            None => sample,
+        };
+        self.offset_ms = Some(offset);
+        self.black = PlayerClock::from(&clock.black);
+        self.white = PlayerClock::from(&clock.white);
+        self.running = clock.running;
+        let elapsed = (local_now_ms + offset - clock.server_time_ms).max(0);
+        self.tick(elapsed);
+    }
+}
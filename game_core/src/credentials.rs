@@ -0,0 +1,137 @@
+use std::{
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Nonce,
+};
+use sha2::{Digest, Sha256};
+
+use crate::dto::UserTokenDto;
+
+/// Godot path of the encrypted token kept by "remember me"
+pub const REMEMBERED_TOKEN_PATH: &str = "user://remembered_token.bin";
+/// Godot path of the random secret the token key is derived from
+pub const TOKEN_SECRET_PATH: &str = "user://token_secret.bin";
+
+const SECRET_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const KEY_CONTEXT: &[u8] = b"go-client remembered token v1";
+
+#[derive(Debug)]
+pub enum TokenStoreError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// The file was changed or the secret is not the one it was saved with
+    Corrupted,
+}
+
+impl Display for TokenStoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenStoreError::Io(e) => write!(f, "{}", e),
+            TokenStoreError::Json(e) => write!(f, "{}", e),
+            TokenStoreError::Corrupted => write!(f, "the remembered token can't be decrypted"),
+        }
+    }
+}
+
+impl From<io::Error> for TokenStoreError {
+    fn from(e: io::Error) -> Self {
+        TokenStoreError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for TokenStoreError {
+    fn from(e: serde_json::Error) -> Self {
+        TokenStoreError::Json(e)
+    }
+}
+
+/// The sign in token of "remember me", encrypted with ChaCha20-Poly1305.
+/// The key is derived from a random secret created next to the token on the first save.
+#[derive(Debug, Clone)]
+pub struct TokenStore {
+    token_path: PathBuf,
+    secret_path: PathBuf,
+}
+
+impl TokenStore {
+    pub fn new(token_path: &Path, secret_path: &Path) -> Self {
+        Self {
+            token_path: token_path.to_path_buf(),
+            secret_path: secret_path.to_path_buf(),
+        }
+    }
+
+    pub fn save(&self, token: &UserTokenDto) -> Result<(), TokenStoreError> {
+        let cipher = self.cipher(true)?;
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let sealed = cipher
+            .encrypt(&nonce, serde_json::to_vec(token)?.as_slice())
+            .map_err(|_| TokenStoreError::Corrupted)?;
+        write_private(&self.token_path, &[nonce.as_slice(), &sealed].concat())?;
+        Ok(())
+    }
+
+    /// `None` when nothing is remembered
+    pub fn load(&self) -> Result<Option<UserTokenDto>, TokenStoreError> {
+        let data = match fs::read(&self.token_path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            data => data?,
+        };
+        if data.len() < NONCE_LEN {
+            return Err(TokenStoreError::Corrupted);
+        }
+        let (nonce, sealed) = data.split_at(NONCE_LEN);
+        let plain = self
+            .cipher(false)?
+            .decrypt(Nonce::from_slice(nonce), sealed)
+            .map_err(|_| TokenStoreError::Corrupted)?;
+        Ok(Some(serde_json::from_slice(&plain)?))
+    }
+
+    /// Forget the token, the secret is kept for the next one
+    pub fn clear(&self) -> io::Result<()> {
+        match fs::remove_file(&self.token_path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    fn cipher(&self, create_secret: bool) -> Result<ChaCha20Poly1305, TokenStoreError> {
+        let secret = match fs::read(&self.secret_path) {
+            Ok(secret) if secret.len() == SECRET_LEN => secret,
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            // Missing or broken, a token saved with the old secret can't be read anyway
+            _ if create_secret => {
+                let secret = ChaCha20Poly1305::generate_key(&mut OsRng).to_vec();
+                write_private(&self.secret_path, &secret)?;
+                secret
+            }
+            _ => return Err(TokenStoreError::Corrupted),
+        };
+        let key = Sha256::new()
+            .chain_update(KEY_CONTEXT)
+            .chain_update(&secret)
+            .finalize();
+        Ok(ChaCha20Poly1305::new(&key))
+    }
+}
+
+/// Write a file only the user can read
+fn write_private(path: &Path, data: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, data)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
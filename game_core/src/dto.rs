@@ -0,0 +1,500 @@
+use std::{collections::HashMap, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserStatusDto {
+    pub login: String,
+    pub is_online: bool,
+    #[serde(default)]
+    pub rank: Option<String>,
+    /// Sent for the players of a session, spectators match it with the game colors
+    #[serde(default)]
+    pub user_id: Option<i64>,
+    /// Glicko-2 rating, the rank is derived from it when the server sends none
+    #[serde(default)]
+    pub rating: Option<RatingDto>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RatingDto {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserTokenDto {
+    pub user_id: i64,
+    pub login: String,
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserSessionDto {
+    pub user1: UserStatusDto,
+    pub user2: Option<UserStatusDto>,
+    pub session_id: String,
+    #[serde(default)]
+    pub settings: Option<GameSettingsDto>,
+    /// Short code to join a private session, sent to the host only
+    #[serde(default)]
+    pub join_code: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UserSessionRequestDto {
+    pub user_id: i64,
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<GameSettingsDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub join_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameSettingsDto {
+    pub board_size: i32,
+    pub komi: f32,
+    pub handicap: i32,
+    #[serde(default)]
+    pub handicap_placement: HandicapPlacement,
+    pub color: ColorPreference,
+    pub time_control: TimeControl,
+    pub is_private: bool,
+}
+
+impl Default for GameSettingsDto {
+    fn default() -> Self {
+        Self {
+            board_size: 19,
+            komi: 6.5,
+            handicap: 0,
+            handicap_placement: HandicapPlacement::Fixed,
+            color: ColorPreference::Nigiri,
+            time_control: TimeControl::None,
+            is_private: false,
+        }
+    }
+}
+
+/// Fixed handicap stones go to the star points, free ones are clicked by black
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HandicapPlacement {
+    #[default]
+    Fixed,
+    Free,
+}
+
+/// The host's color choice. Nigiri lets the server pick randomly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorPreference {
+    Black,
+    White,
+    #[default]
+    Nigiri,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimeControl {
+    #[default]
+    None,
+    Absolute {
+        main_time_secs: u32,
+    },
+    Fischer {
+        main_time_secs: u32,
+        increment_secs: u32,
+    },
+    ByoYomi {
+        main_time_secs: u32,
+        period_secs: u32,
+        periods: u32,
+    },
+    Canadian {
+        main_time_secs: u32,
+        period_secs: u32,
+        stones: u32,
+    },
+}
+
+fn format_minutes(secs: u32) -> String {
+    if secs.is_multiple_of(60) {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+impl Display for TimeControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeControl::None => write!(f, "-"),
+            TimeControl::Absolute { main_time_secs } => {
+                write!(f, "{}", format_minutes(*main_time_secs))
+            }
+            TimeControl::Fischer {
+                main_time_secs,
+                increment_secs,
+            } => write!(f, "{}+{}s", format_minutes(*main_time_secs), increment_secs),
+            TimeControl::ByoYomi {
+                main_time_secs,
+                period_secs,
+                periods,
+            } => write!(
+                f,
+                "{} {}x{}s",
+                format_minutes(*main_time_secs),
+                periods,
+                period_secs
+            ),
+            TimeControl::Canadian {
+                main_time_secs,
+                period_secs,
+                stones,
+            } => write!(
+                f,
+                "{} {}/{}",
+                format_minutes(*main_time_secs),
+                stones,
+                format_minutes(*period_secs)
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameStateDto {
+    pub game_state_id: i64,
+    pub user_session_id: String,
+    pub active_user_id: i64,
+    pub game_state: GameState,
+    pub is_active: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameState {
+    pub score: GameScore,
+    pub board: Vec<Vec<Option<bool>>>,
+    pub colors: HashMap<i64, bool>,
+    #[serde(default)]
+    pub phase: GamePhase,
+    #[serde(default)]
+    pub scoring: Option<GameScoring>,
+    #[serde(default)]
+    pub clock: Option<ClockDto>,
+    #[serde(default)]
+    pub result: Option<GameResultDto>,
+    /// Number of moves played after the handicap stones, including passes
+    #[serde(default)]
+    pub move_number: usize,
+    /// Handicap stones black still has to place, white moves first afterwards
+    #[serde(default)]
+    pub handicap_left: u32,
+    /// The last takeback request of the game
+    #[serde(default)]
+    pub undo: Option<UndoRequestDto>,
+}
+
+/// Remaining time of both players at `server_time_ms`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockDto {
+    pub black: PlayerClockDto,
+    pub white: PlayerClockDto,
+    /// Color of the running clock
+    pub running: Option<bool>,
+    /// Unix timestamp of the snapshot in milliseconds
+    pub server_time_ms: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlayerClockDto {
+    pub main_time_ms: i64,
+    pub period_time_ms: i64,
+    pub periods: u32,
+    pub stones: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameResultReason {
+    Score,
+    Time,
+    Resign,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameResultDto {
+    /// `None` is a draw
+    pub winner: Option<bool>,
+    pub reason: GameResultReason,
+}
+
+impl Display for GameResultDto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let winner = match self.winner {
+            Some(true) => "Black wins",
+            Some(false) => "White wins",
+            None => return write!(f, "Draw"),
+        };
+        match self.reason {
+            GameResultReason::Score => write!(f, "{} by score", winner),
+            GameResultReason::Time => write!(f, "{} on time", winner),
+            GameResultReason::Resign => write!(f, "{} by resignation", winner),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GamePhase {
+    #[default]
+    Play,
+    /// Both players passed and are agreeing on dead stones
+    Scoring,
+    Finished,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GameScoring {
+    pub dead_stones: Vec<StepDto>,
+    /// Ids of the users who accepted the current dead stones selection
+    pub accepted: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameScore {
+    pub black: i32,
+    pub white: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserStepRequestDto {
+    pub session: UserSessionStepDto,
+    pub step: StepDto,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserSessionStepDto {
+    pub session_id: String,
+    pub user_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepDto {
+    pub row: i32,
+    pub col: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserScoringRequestDto {
+    pub session: UserSessionStepDto,
+    pub dead_stones: Vec<StepDto>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChallengeRequestDto {
+    pub user_id: i64,
+    pub opponent_login: String,
+    pub settings: GameSettingsDto,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChallengeStatus {
+    Pending,
+    Accepted,
+    Declined,
+    Expired,
+}
+
+/// An invitation to play sent directly to an online player.
+/// `settings` are given from the challenger's point of view.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChallengeDto {
+    pub challenge_id: String,
+    pub challenger: UserStatusDto,
+    pub opponent_login: String,
+    pub settings: GameSettingsDto,
+    /// Unix timestamp in seconds
+    pub created_at: i64,
+    pub status: ChallengeStatus,
+    /// The session created by the opponent on accept
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChallengeAnswerDto {
+    pub user_id: i64,
+    pub challenge_id: String,
+    pub status: ChallengeStatus,
+    pub session_id: Option<String>,
+}
+
+/// What a player in the matchmaking queue accepts from the opponent
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MatchPreferencesDto {
+    pub board_size: i32,
+    pub time_control: TimeControl,
+    /// Rank values like `lobby::rank_value`, open ended when `None`
+    #[serde(default)]
+    pub min_rank: Option<i32>,
+    #[serde(default)]
+    pub max_rank: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchmakingRequestDto {
+    pub token: UserTokenDto,
+    pub preferences: MatchPreferencesDto,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchmakingStatus {
+    Searching,
+    Matched,
+}
+
+/// The place of the user in the matchmaking queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchmakingTicketDto {
+    pub status: MatchmakingStatus,
+    pub preferences: MatchPreferencesDto,
+    /// Unix timestamp in seconds
+    pub queued_at: i64,
+    /// The session both players were put into, set when matched
+    #[serde(default)]
+    pub session: Option<UserSessionDto>,
+}
+
+/// A move of the game record, `step` is `None` for a pass
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordMoveDto {
+    pub color: bool,
+    pub step: Option<StepDto>,
+}
+
+/// The move history of a session for the review mode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameRecordDto {
+    pub settings: GameSettingsDto,
+    /// Black stones placed before the first move
+    #[serde(default)]
+    pub handicap_stones: Vec<StepDto>,
+    pub moves: Vec<RecordMoveDto>,
+    #[serde(default)]
+    pub result: Option<GameResultDto>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatChannel {
+    #[default]
+    Players,
+    Spectators,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessageDto {
+    pub message_id: i64,
+    pub login: String,
+    pub text: String,
+    pub channel: ChatChannel,
+    /// Sender's local time formatted with `util::get_format_time`
+    pub sent_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatRequestDto {
+    pub session: UserSessionStepDto,
+    pub text: String,
+    pub channel: ChatChannel,
+    pub sent_at: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UndoStatus {
+    Pending,
+    Accepted,
+    Declined,
+}
+
+/// A takeback asked by `requested_by`, the board returns to `move_number` moves on accept
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoRequestDto {
+    pub requested_by: i64,
+    pub move_number: usize,
+    pub status: UndoStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserUndoRequestDto {
+    pub session: UserSessionStepDto,
+    pub move_number: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserUndoAnswerDto {
+    pub session: UserSessionStepDto,
+    pub accept: bool,
+}
+
+/// A field of the request refused by the server, `field` is the name of the JSON field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldErrorDto {
+    pub field: String,
+    pub message: String,
+}
+
+impl Display for FieldErrorDto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Body of a 400 or 422 response to an account request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ValidationErrorDto {
+    pub errors: Vec<FieldErrorDto>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordChangeDto {
+    pub token: UserTokenDto,
+    pub old_pass: String,
+    pub new_pass: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileDto {
+    pub user_id: i64,
+    pub login: String,
+    /// Shown instead of the login when it's set
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// One of `account::AVATARS`
+    #[serde(default)]
+    pub avatar: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileUpdateDto {
+    pub token: UserTokenDto,
+    pub display_name: Option<String>,
+    pub avatar: Option<String>,
+}
+
+/// The password is asked again before the account is deleted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountDeleteDto {
+    pub token: UserTokenDto,
+    pub pass: String,
+}
@@ -0,0 +1,255 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+    thread,
+    time::Duration,
+};
+
+use go_gtp::{
+    engine::{GtpEngine, GtpError, GtpMove},
+    vertex::Vertex,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::rules::{GoGame, Move};
+
+/// Godot path of the file keeping the external engine configuration
+pub const ENGINE_SETTINGS_PATH: &str = "user://engine.json";
+
+/// How long `GtpBridge::spawn` waits for the engine to answer `name`
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Command line of a local GTP engine, e.g. `gnugo --mode gtp`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EngineSettings {
+    pub path: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl EngineSettings {
+    /// Split a command line typed by the user into the executable and its arguments.
+    /// Double quotes keep the spaces of a word, like `"C:\Program Files\gnugo.exe" --mode gtp`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use game_core::gtp_bridge::EngineSettings;
+    ///
+    /// let settings = EngineSettings::from_command_line(r#""/opt/go engines/gnugo" --mode gtp"#).unwrap();
+    /// assert_eq!("/opt/go engines/gnugo", settings.path);
+    /// assert_eq!(vec!["--mode", "gtp"], settings.args);
+    /// assert_eq!(r#""/opt/go engines/gnugo" --mode gtp"#, settings.command_line());
+    /// ```
+    pub fn from_command_line(command_line: &str) -> Option<EngineSettings> {
+        let mut words = vec![];
+        let mut word: Option<String> = None;
+        let mut quoted = false;
+        for c in command_line.chars() {
+            match c {
+                '"' => {
+                    quoted = !quoted;
+                    word.get_or_insert_with(String::new);
+                }
+                c if c.is_whitespace() && !quoted => words.extend(word.take()),
+                c => word.get_or_insert_with(String::new).push(c),
+            }
+        }
+        words.extend(word);
+        let mut words = words.into_iter();
+        Some(EngineSettings {
+            path: words.next()?,
+            args: words.collect(),
+        })
+    }
+
+    /// The command line `from_command_line` reads back, words with spaces are quoted
+    pub fn command_line(&self) -> String {
+        std::iter::once(&self.path)
+            .chain(self.args.iter())
+            .map(|word| {
+                if word.is_empty() || word.contains(char::is_whitespace) {
+                    format!("\"{}\"", word)
+                } else {
+                    word.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn load(path: &Path) -> Option<EngineSettings> {
+        let content = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)
+    }
+}
+
+/// What the engine is asked to generate a move for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EngineJob {
+    /// The engine plays this move as the opponent
+    Play,
+    /// A suggestion for the user, the position is not changed
+    Hint,
+}
+
+/// The answer of the engine for the side to move
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EngineMove {
+    Play(Move),
+    Resign { color: bool },
+}
+
+pub struct EngineReply {
+    pub job: EngineJob,
+    /// Number of moves of the position the request was made for
+    pub move_number: usize,
+    pub result: Result<EngineMove, GtpError>,
+}
+
+struct EngineRequest {
+    job: EngineJob,
+    /// The side to move
+    color: bool,
+    size: usize,
+    komi: f32,
+    handicap: Vec<Vertex>,
+    moves: Vec<(bool, Vertex)>,
+}
+
+/// Moves of the game in GTP terms
+pub fn to_gtp_moves(game: &GoGame) -> Vec<(bool, Vertex)> {
+    game.moves()
+        .iter()
+        .map(|m| match m {
+            Move::Play { point, color } => (*color, Vertex::Point(point.0, point.1)),
+            Move::Pass { color } => (*color, Vertex::Pass),
+        })
+        .collect()
+}
+
+pub fn from_gtp_move(color: bool, gtp_move: GtpMove) -> EngineMove {
+    match gtp_move {
+        GtpMove::Play(Vertex::Point(row, col)) => EngineMove::Play(Move::Play {
+            point: (row, col),
+            color,
+        }),
+        GtpMove::Play(Vertex::Pass) => EngineMove::Play(Move::Pass { color }),
+        GtpMove::Resign => EngineMove::Resign { color },
+    }
+}
+
+/// An external GTP engine process driven from a worker thread.
+/// The whole position is sent before every `genmove`, so local undo needs no engine state.
+/// The process quits when the bridge is dropped.
+pub struct GtpBridge {
+    pub name: String,
+    requests: Sender<EngineRequest>,
+    replies: Receiver<EngineReply>,
+    pending: Option<(EngineJob, usize)>,
+}
+
+impl GtpBridge {
+    pub fn spawn(settings: &EngineSettings) -> Result<GtpBridge, GtpError> {
+        Self::spawn_with_timeout(settings, HANDSHAKE_TIMEOUT)
+    }
+
+    /// The `name` handshake runs on the worker thread, the caller waits at most `timeout`.
+    /// An engine which never answers is left to the worker and killed once it does.
+    pub fn spawn_with_timeout(
+        settings: &EngineSettings,
+        timeout: Duration,
+    ) -> Result<GtpBridge, GtpError> {
+        let mut engine = GtpEngine::spawn(&PathBuf::from(&settings.path), &settings.args)?;
+        let (name_sender, name_receiver) = mpsc::channel();
+        let (requests, request_receiver) = mpsc::channel::<EngineRequest>();
+        let (reply_sender, replies) = mpsc::channel();
+        thread::spawn(move || {
+            let name = engine.name();
+            let failed = name.is_err();
+            if name_sender.send(name).is_err() || failed {
+                return;
+            }
+            for request in request_receiver {
+                let color = request.color;
+                let result = engine
+                    .setup(
+                        request.size,
+                        request.komi,
+                        &request.handicap,
+                        &request.moves,
+                    )
+                    .and_then(|_| engine.genmove(color))
+                    .map(|gtp_move| from_gtp_move(color, gtp_move));
+                let reply = EngineReply {
+                    job: request.job,
+                    move_number: request.moves.len(),
+                    result,
+                };
+                if reply_sender.send(reply).is_err() {
+                    break;
+                }
+            }
+        });
+        let name = name_receiver
+            .recv_timeout(timeout)
+            .map_err(|_| GtpError::Protocol(format!("no answer to name in {:?}", timeout)))??;
+        Ok(GtpBridge {
+            name,
+            requests,
+            replies,
+            pending: None,
+        })
+    }
+
+    pub fn pending_job(&self) -> Option<EngineJob> {
+        self.pending.map(|(job, _)| job)
+    }
+
+    /// Ask for a move in the current position, ignored while another request is pending
+    pub fn request(&mut self, job: EngineJob, game: &GoGame) {
+        if self.pending.is_some() {
+            return;
+        }
+        let request = EngineRequest {
+            job,
+            color: game.to_move(),
+            size: game.size(),
+            komi: game.komi(),
+            handicap: game
+                .handicap()
+                .iter()
+                .map(|(row, col)| Vertex::Point(*row, *col))
+                .collect(),
+            moves: to_gtp_moves(game),
+        };
+        let move_number = request.moves.len();
+        if self.requests.send(request).is_ok() {
+            self.pending = Some((job, move_number));
+        }
+    }
+
+    /// The engine answer once it is ready, a stopped worker is reported as an error
+    pub fn poll(&mut self) -> Option<EngineReply> {
+        let (job, move_number) = self.pending?;
+        let reply = match self.replies.try_recv() {
+            Ok(reply) => reply,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => EngineReply {
+                job,
+                move_number,
+                result: Err(GtpError::Protocol("engine stopped".to_string())),
+            },
+        };
+        self.pending = None;
+        Some(reply)
+    }
+}
@@ -0,0 +1,56 @@
+use crate::scoring::Point;
+
+pub const MIN_HANDICAP: usize = 2;
+pub const MAX_HANDICAP: usize = 9;
+
+/// Komi of handicap games, white only gets half a point to avoid draws
+pub const HANDICAP_KOMI: f32 = 0.5;
+
+/// Star points of the fixed placement in the GTP order:
+/// opposite corners, the other corners, the center for odd counts, then the sides.
+/// `None` for unsupported board sizes or stone counts.
+///
+/// # Examples
+///
+/// ```
+/// use game_core::handicap::fixed_handicap_points;
+///
+/// assert_eq!(Some(vec![(15, 3), (3, 15)]), fixed_handicap_points(19, 2));
+/// assert_eq!(Some(9), fixed_handicap_points(9, 9).map(|p| p.len()));
+/// assert_eq!(None, fixed_handicap_points(19, 1));
+/// ```
+pub fn fixed_handicap_points(size: usize, stones: usize) -> Option<Vec<Point>> {
+    if !(MIN_HANDICAP..=MAX_HANDICAP).contains(&stones) {
+        return None;
+    }
+    let low = match size {
+        9 => 2,
+        13 | 19 => 3,
+        _ => return None,
+    };
+    let high = size - 1 - low;
+    let middle = size / 2;
+    let corners = [(high, low), (low, high), (low, low), (high, high)];
+    let sides = [(middle, low), (middle, high), (high, middle), (low, middle)];
+    let center = (middle, middle);
+    let mut points: Vec<Point> = corners.into_iter().take(stones.min(4)).collect();
+    let side_count = match stones {
+        6 | 7 => 2,
+        8 | 9 => 4,
+        _ => 0,
+    };
+    points.extend(sides.into_iter().take(side_count));
+    if stones >= 5 && stones % 2 == 1 {
+        points.push(center);
+    }
+    Some(points)
+}
+
+/// Komi of the game with the given number of handicap stones
+pub fn handicap_komi(komi: f32, stones: usize) -> f32 {
+    if stones >= MIN_HANDICAP {
+        HANDICAP_KOMI
+    } else {
+        komi
+    }
+}
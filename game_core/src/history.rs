@@ -0,0 +1,26 @@
+use crate::{dto::GameScore, scoring::BoardGrid};
+
+/// Positions of an online game by move number, seen while polling the game state.
+/// Lets the client show the position a takeback returns to before the server confirms it.
+#[derive(Debug, Clone, Default)]
+pub struct PositionHistory {
+    positions: Vec<Option<(BoardGrid, GameScore)>>,
+}
+
+impl PositionHistory {
+    /// Keep the position, the later ones are dropped as they were taken back
+    pub fn record(&mut self, move_number: usize, board: &BoardGrid, score: &GameScore) {
+        self.positions.resize(move_number + 1, None);
+        self.positions[move_number] = Some((board.clone(), score.clone()));
+    }
+
+    pub fn get(&self, move_number: usize) -> Option<&(BoardGrid, GameScore)> {
+        self.positions.get(move_number).and_then(|p| p.as_ref())
+    }
+}
+
+/// Move number a takeback of the user's last move returns to:
+/// one move back right after the user moved, two once the opponent answered
+pub fn undo_target(move_number: usize, user_to_move: bool) -> Option<usize> {
+    move_number.checked_sub(if user_to_move { 2 } else { 1 })
+}
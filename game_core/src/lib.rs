@@ -0,0 +1,23 @@
+//! Go rules, game records, the backend DTOs and API client of the game client.
+//! Nothing here depends on Godot, the GDExtension in `game_client` binds it to the scenes.
+
+pub mod account;
+pub mod ai;
+pub mod api;
+pub mod chat;
+pub mod clock;
+pub mod credentials;
+pub mod dto;
+pub mod gtp_bridge;
+pub mod handicap;
+pub mod history;
+pub mod lobby;
+pub mod matchmaking;
+pub mod preferences;
+pub mod rating;
+pub mod resume;
+pub mod review;
+pub mod rules;
+pub mod scoring;
+pub mod session;
+pub mod sgf;
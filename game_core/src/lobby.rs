@@ -0,0 +1,353 @@
+use std::collections::HashSet;
+
+use crate::{
+    dto::{
+        ChallengeDto, ChallengeStatus, ColorPreference, GameSettingsDto, UserSessionDto,
+        UserStatusDto,
+    },
+    rating::Rating,
+};
+
+/// Columns shown per session row in the lobby `ItemList`
+pub const LOBBY_COLUMNS: [&str; 8] = [
+    "Host", "Status", "Rank", "Board", "Komi", "Handicap", "Time", "Players",
+];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionFilter {
+    pub online_only: bool,
+    pub board_size: Option<i32>,
+    pub min_rank: Option<i32>,
+    pub max_rank: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SessionSort {
+    #[default]
+    Host,
+    Online,
+    Rank,
+}
+
+impl From<i32> for SessionSort {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::Online,
+            2 => Self::Rank,
+            _ => Self::Host,
+        }
+    }
+}
+
+/// Convert a kyu/dan rank like "5k" or "2d" to a comparable value:
+/// kyu ranks are negative (1k = -1), dan ranks start from zero (1d = 0)
+pub fn rank_value(rank: &str) -> Option<i32> {
+    let rank = rank.trim().to_lowercase();
+    let parse = |number: &str| number.parse::<i32>().ok().filter(|n| *n > 0);
+    if let Some(number) = rank.strip_suffix('k') {
+        parse(number).map(|n| -n)
+    } else if let Some(number) = rank.strip_suffix('d') {
+        parse(number).map(|n| n - 1)
+    } else {
+        None
+    }
+}
+
+/// The rank sent by the server, otherwise the one of the rating
+fn user_rank(user: &UserStatusDto) -> Option<i32> {
+    user.rank
+        .as_deref()
+        .and_then(rank_value)
+        .or_else(|| user.rating.map(|r| Rating::from(r).rank_value()))
+}
+
+/// Rank and rating shown next to the player like "5k 1523±80", "-" when unranked
+///
+/// # Examples
+///
+/// ```
+/// use game_core::{dto::{RatingDto, UserStatusDto}, lobby::rank_label};
+///
+/// let mut user = UserStatusDto {
+///     login: "alice".to_string(),
+///     is_online: true,
+///     rank: None,
+///     user_id: None,
+///     rating: None,
+/// };
+/// assert_eq!("-", rank_label(&user));
+/// user.rating = Some(RatingDto { rating: 1523.4, deviation: 80.2, volatility: 0.06 });
+/// assert_eq!("5k 1523±80", rank_label(&user));
+/// user.rank = Some("3d".to_string());
+/// assert_eq!("3d 1523±80", rank_label(&user));
+/// ```
+pub fn rank_label(user: &UserStatusDto) -> String {
+    let rank = user
+        .rank
+        .clone()
+        .or_else(|| user.rating.map(|r| Rating::from(r).rank()));
+    match (rank, user.rating) {
+        (Some(rank), Some(rating)) => {
+            format!("{} {:.0}±{:.0}", rank, rating.rating, rating.deviation)
+        }
+        (Some(rank), None) => rank,
+        (None, _) => "-".to_string(),
+    }
+}
+
+/// Scheme of the invite links shared for private sessions
+pub const INVITE_LINK_PREFIX: &str = "gogame://join/";
+
+const JOIN_CODE_LEN: usize = 6;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JoinInvite {
+    pub code: String,
+    pub password: Option<String>,
+}
+
+/// Percent-encode a query value, only the unreserved characters of RFC 3986 stay as they are
+fn encode_query_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// `None` for a broken escape or a value which isn't UTF-8
+fn decode_query_value(value: &str) -> Option<String> {
+    let mut bytes = vec![];
+    let mut rest = value.as_bytes();
+    while let Some((b, tail)) = rest.split_first() {
+        match b {
+            b'%' => {
+                let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+                rest = &tail[2..];
+            }
+            b'+' => {
+                bytes.push(b' ');
+                rest = tail;
+            }
+            b => {
+                bytes.push(*b);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Build an invite link like `gogame://join/K7QX2M`, the password is percent-encoded
+///
+/// # Examples
+///
+/// ```
+/// use game_core::lobby::invite_link;
+///
+/// assert_eq!("gogame://join/K7QX2M", invite_link("K7QX2M", None));
+/// assert_eq!(
+///     "gogame://join/K7QX2M?password=a%26b%20c",
+///     invite_link("K7QX2M", Some("a&b c"))
+/// );
+/// ```
+pub fn invite_link(code: &str, password: Option<&str>) -> String {
+    match password {
+        Some(password) => format!(
+            "{}{}?password={}",
+            INVITE_LINK_PREFIX,
+            code,
+            encode_query_value(password)
+        ),
+        None => format!("{}{}", INVITE_LINK_PREFIX, code),
+    }
+}
+
+/// Parse either a bare join code or an invite link.
+/// Codes are case insensitive, spaces and dashes are ignored.
+pub fn parse_invite(input: &str) -> Option<JoinInvite> {
+    let input = input.trim();
+    let input = input.strip_prefix(INVITE_LINK_PREFIX).unwrap_or(input);
+    let (code, password) = match input.split_once('?') {
+        Some((code, query)) => {
+            let password = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("password="))
+                .map(decode_query_value);
+            match password {
+                Some(None) => return None,
+                password => (code, password.flatten()),
+            }
+        }
+        None => (input, None),
+    };
+    let code: String = code
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    if code.len() != JOIN_CODE_LEN || !code.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some(JoinInvite {
+        code,
+        password: password.filter(|p| !p.is_empty()),
+    })
+}
+
+/// Drop repeated sessions keeping the latest entry for every session id
+pub fn dedupe_sessions(sessions: Vec<UserSessionDto>) -> Vec<UserSessionDto> {
+    let mut seen = HashSet::new();
+    let mut result: Vec<UserSessionDto> = sessions
+        .into_iter()
+        .rev()
+        .filter(|s| seen.insert(s.session_id.clone()))
+        .collect();
+    result.reverse();
+    result
+}
+
+impl SessionFilter {
+    pub fn matches(&self, session: &UserSessionDto) -> bool {
+        if session.settings.as_ref().is_some_and(|s| s.is_private) {
+            return false;
+        }
+        if self.online_only && !session.user1.is_online {
+            return false;
+        }
+        if self.board_size.is_some()
+            && self.board_size != session.settings.as_ref().map(|s| s.board_size)
+        {
+            return false;
+        }
+        if self.min_rank.is_none() && self.max_rank.is_none() {
+            return true;
+        }
+        match user_rank(&session.user1) {
+            Some(rank) => {
+                self.min_rank.map(|min| rank >= min).unwrap_or(true)
+                    && self.max_rank.map(|max| rank <= max).unwrap_or(true)
+            }
+            None => false,
+        }
+    }
+}
+
+/// Filter and sort sessions for display
+pub fn visible_sessions(
+    sessions: &[UserSessionDto],
+    filter: &SessionFilter,
+    sort: SessionSort,
+) -> Vec<UserSessionDto> {
+    let mut result: Vec<UserSessionDto> = sessions
+        .iter()
+        .filter(|s| filter.matches(s))
+        .cloned()
+        .collect();
+    match sort {
+        SessionSort::Host => result.sort_by_key(|s| s.user1.login.to_lowercase()),
+        SessionSort::Online => result.sort_by_key(|s| !s.user1.is_online),
+        SessionSort::Rank => result.sort_by_key(|s| std::cmp::Reverse(user_rank(&s.user1))),
+    }
+    result
+}
+
+/// Cell texts of a session row in the order of `LOBBY_COLUMNS`
+pub fn session_columns(session: &UserSessionDto) -> Vec<String> {
+    let settings = session.settings.as_ref();
+    vec![
+        session.user1.login.clone(),
+        if session.user1.is_online {
+            "online"
+        } else {
+            "offline"
+        }
+        .to_string(),
+        rank_label(&session.user1),
+        settings
+            .map(|s| format!("{}x{}", s.board_size, s.board_size))
+            .unwrap_or("-".to_string()),
+        settings
+            .map(|s| s.komi.to_string())
+            .unwrap_or("-".to_string()),
+        settings
+            .map(|s| s.handicap.to_string())
+            .unwrap_or("-".to_string()),
+        settings
+            .map(|s| s.time_control.to_string())
+            .unwrap_or("-".to_string()),
+        format!("{}/2", if session.user2.is_some() { 2 } else { 1 }),
+    ]
+}
+
+/// Games in progress that can be watched: both players joined, private games are hidden
+pub fn watchable_sessions(sessions: &[UserSessionDto], login: &str) -> Vec<UserSessionDto> {
+    sessions
+        .iter()
+        .filter(|s| s.user2.is_some())
+        .filter(|s| {
+            !s.settings
+                .as_ref()
+                .is_some_and(|settings| settings.is_private)
+        })
+        .filter(|s| s.user1.login != login && s.user2.as_ref().is_some_and(|u| u.login != login))
+        .cloned()
+        .collect()
+}
+
+/// Row text of an active game like "alice (3d) vs bob (-) 19x19"
+pub fn active_game_title(session: &UserSessionDto) -> String {
+    let player = |user: &UserStatusDto| format!("{} ({})", user.login, rank_label(user));
+    let board = session
+        .settings
+        .as_ref()
+        .map(|s| format!(" {}x{}", s.board_size, s.board_size))
+        .unwrap_or_default();
+    format!(
+        "{} vs {}{}",
+        player(&session.user1),
+        session.user2.as_ref().map(player).unwrap_or_default(),
+        board
+    )
+}
+
+/// Pending challenges older than this are treated as expired
+pub const CHALLENGE_TTL_SECS: i64 = 120;
+
+pub fn is_challenge_expired(challenge: &ChallengeDto, now: i64) -> bool {
+    challenge.status == ChallengeStatus::Expired
+        || (challenge.status == ChallengeStatus::Pending
+            && now - challenge.created_at > CHALLENGE_TTL_SECS)
+}
+
+/// The oldest pending, not expired challenge sent to `login`
+pub fn incoming_challenge(
+    challenges: &[ChallengeDto],
+    login: &str,
+    now: i64,
+) -> Option<ChallengeDto> {
+    challenges
+        .iter()
+        .filter(|c| c.opponent_login == login && c.status == ChallengeStatus::Pending)
+        .filter(|c| !is_challenge_expired(c, now))
+        .min_by_key(|c| c.created_at)
+        .cloned()
+}
+
+/// Settings of the session the challenged player creates on accept:
+/// the color preference is mirrored as the opponent becomes the host
+pub fn accepted_challenge_settings(challenge: &ChallengeDto) -> GameSettingsDto {
+    let mut settings = challenge.settings.clone();
+    settings.color = match settings.color {
+        ColorPreference::Black => ColorPreference::White,
+        ColorPreference::White => ColorPreference::Black,
+        ColorPreference::Nigiri => ColorPreference::Nigiri,
+    };
+    settings.is_private = true;
+    settings
+}
@@ -0,0 +1,138 @@
+use crate::{
+    dto::{ColorPreference, GameSettingsDto, MatchPreferencesDto, TimeControl},
+    rating::{self, Rating},
+};
+
+/// Board sizes offered by "find game"
+pub const MATCH_BOARD_SIZES: [i32; 3] = [9, 13, 19];
+
+/// Time controls offered by "find game", players are only paired on the same one
+pub const MATCH_TIME_CONTROLS: [TimeControl; 4] = [
+    TimeControl::None,
+    TimeControl::Absolute {
+        main_time_secs: 600,
+    },
+    TimeControl::Fischer {
+        main_time_secs: 300,
+        increment_secs: 10,
+    },
+    TimeControl::ByoYomi {
+        main_time_secs: 600,
+        period_secs: 30,
+        periods: 3,
+    },
+];
+
+/// A player waiting in the queue
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueueEntry {
+    pub user_id: i64,
+    pub rating: Rating,
+    pub preferences: MatchPreferencesDto,
+    /// Unix timestamp in seconds
+    pub queued_at: i64,
+}
+
+impl QueueEntry {
+    fn accepts(&self, rank: i32) -> bool {
+        self.preferences.min_rank.is_none_or(|min| rank >= min)
+            && self.preferences.max_rank.is_none_or(|max| rank <= max)
+    }
+
+    /// Same board and clock, each rank within the range of the other player
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use game_core::{dto::MatchPreferencesDto, matchmaking::QueueEntry, rating::Rating};
+    ///
+    /// let entry = |user_id, rating, min_rank| QueueEntry {
+    ///     user_id,
+    ///     rating: Rating { rating, ..Default::default() },
+    ///     preferences: MatchPreferencesDto {
+    ///         board_size: 19,
+    ///         min_rank,
+    ///         ..Default::default()
+    ///     },
+    ///     queued_at: 0,
+    /// };
+    /// // 5k and 10k, the 5k player wants 7k or stronger
+    /// assert!(entry(1, 1500.0, None).is_match(&entry(2, 1000.0, None)));
+    /// assert!(!entry(1, 1500.0, Some(-7)).is_match(&entry(2, 1000.0, None)));
+    /// ```
+    pub fn is_match(&self, other: &QueueEntry) -> bool {
+        self.user_id != other.user_id
+            && self.preferences.board_size == other.preferences.board_size
+            && self.preferences.time_control == other.preferences.time_control
+            && self.accepts(other.rating.rank_value())
+            && other.accepts(self.rating.rank_value())
+    }
+}
+
+/// Players waiting for an opponent, the one waiting longest is paired first
+#[derive(Debug, Clone, Default)]
+pub struct MatchQueue {
+    entries: Vec<QueueEntry>,
+}
+
+impl MatchQueue {
+    /// Add the player, a queued player only gets the preferences replaced
+    pub fn enqueue(&mut self, entry: QueueEntry) {
+        match self.entries.iter_mut().find(|e| e.user_id == entry.user_id) {
+            Some(queued) => queued.preferences = entry.preferences,
+            None => self.entries.push(entry),
+        }
+    }
+
+    /// `false` when the player was not waiting
+    pub fn cancel(&mut self, user_id: i64) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|e| e.user_id != user_id);
+        self.entries.len() != len
+    }
+
+    pub fn get(&self, user_id: i64) -> Option<&QueueEntry> {
+        self.entries.iter().find(|e| e.user_id == user_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Take the first pair that suits each other, the earlier player goes first
+    pub fn pop_match(&mut self) -> Option<(QueueEntry, QueueEntry)> {
+        let (first, second) = self.entries.iter().enumerate().find_map(|(i, entry)| {
+            self.entries[i + 1..]
+                .iter()
+                .position(|other| entry.is_match(other))
+                .map(|j| (i, i + 1 + j))
+        })?;
+        let second = self.entries.remove(second);
+        let first = self.entries.remove(first);
+        Some((first, second))
+    }
+}
+
+/// Settings of the session hosted by `host`: the weaker player takes black
+/// with the suggested handicap, players of one rank play nigiri.
+pub fn match_settings(host: &QueueEntry, guest: &QueueEntry) -> GameSettingsDto {
+    let defaults = GameSettingsDto::default();
+    let (black, white, color) = match host.rating.rank_value().cmp(&guest.rating.rank_value()) {
+        std::cmp::Ordering::Less => (&host.rating, &guest.rating, ColorPreference::Black),
+        std::cmp::Ordering::Greater => (&guest.rating, &host.rating, ColorPreference::White),
+        std::cmp::Ordering::Equal => (&host.rating, &guest.rating, ColorPreference::Nigiri),
+    };
+    let suggestion = rating::suggest_handicap(black, white, defaults.komi);
+    GameSettingsDto {
+        board_size: host.preferences.board_size,
+        komi: suggestion.komi,
+        handicap: suggestion.stones as i32,
+        color,
+        time_control: host.preferences.time_control,
+        ..defaults
+    }
+}
@@ -0,0 +1,149 @@
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::DEFAULT_HOST;
+
+/// Godot path of the file keeping the user preferences
+pub const PREFERENCES_PATH: &str = "user://preferences.json";
+
+pub const MIN_POLL_INTERVAL_MS: u64 = 250;
+pub const MAX_POLL_INTERVAL_MS: u64 = 10_000;
+
+/// Locales with a translation, the first one is the default
+pub const LANGUAGES: [&str; 2] = ["en", "ru"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoardTheme {
+    #[default]
+    Wood,
+    Light,
+    Dark,
+}
+
+impl BoardTheme {
+    pub const ALL: [BoardTheme; 3] = [BoardTheme::Wood, BoardTheme::Light, BoardTheme::Dark];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BoardTheme::Wood => "Wood",
+            BoardTheme::Light => "Light",
+            BoardTheme::Dark => "Dark",
+        }
+    }
+
+    /// Tint of the board texture as RGB
+    pub fn tint(&self) -> (f32, f32, f32) {
+        match self {
+            BoardTheme::Wood => (1.0, 1.0, 1.0),
+            BoardTheme::Light => (1.0, 0.95, 0.85),
+            BoardTheme::Dark => (0.55, 0.5, 0.45),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StoneSet {
+    #[default]
+    Classic,
+    Flat,
+}
+
+impl StoneSet {
+    pub const ALL: [StoneSet; 2] = [StoneSet::Classic, StoneSet::Flat];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StoneSet::Classic => "Classic",
+            StoneSet::Flat => "Flat",
+        }
+    }
+
+    /// Godot path of the stone texture
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use game_core::preferences::StoneSet;
+    ///
+    /// assert_eq!("res://content/materials/black_stone.svg", StoneSet::Classic.texture_path(true));
+    /// assert_eq!("res://content/materials/flat/white_stone.svg", StoneSet::Flat.texture_path(false));
+    /// ```
+    pub fn texture_path(&self, color: bool) -> String {
+        let dir = match self {
+            StoneSet::Classic => "",
+            StoneSet::Flat => "flat/",
+        };
+        let name = if color { "black" } else { "white" };
+        format!("res://content/materials/{}{}_stone.svg", dir, name)
+    }
+}
+
+/// Client settings stored between launches, missing fields take the defaults
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    pub server_url: String,
+    pub board_theme: BoardTheme,
+    pub stone_set: StoneSet,
+    pub show_coordinates: bool,
+    /// The first click shows the stone, the second one plays it
+    pub confirm_moves: bool,
+    pub sound: bool,
+    pub language: String,
+    /// How often the game state and the lobby are requested
+    pub poll_interval_ms: u64,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            server_url: DEFAULT_HOST.to_string(),
+            board_theme: BoardTheme::default(),
+            stone_set: StoneSet::default(),
+            show_coordinates: true,
+            confirm_moves: false,
+            sound: true,
+            language: LANGUAGES[0].to_string(),
+            poll_interval_ms: 1000,
+        }
+    }
+}
+
+impl Preferences {
+    /// The saved preferences, the defaults when the file is missing or broken
+    pub fn load(path: &Path) -> Preferences {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Preferences>(&content).ok())
+            .map(Preferences::normalized)
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Clamp the values edited by hand to the supported ones
+    pub fn normalized(mut self) -> Preferences {
+        self.poll_interval_ms = self
+            .poll_interval_ms
+            .clamp(MIN_POLL_INTERVAL_MS, MAX_POLL_INTERVAL_MS);
+        if self.server_url.trim().is_empty() {
+            self.server_url = DEFAULT_HOST.to_string();
+        }
+        if !LANGUAGES.contains(&self.language.as_str()) {
+            self.language = LANGUAGES[0].to_string();
+        }
+        self
+    }
+
+    pub fn poll_interval_secs(&self) -> f64 {
+        self.poll_interval_ms as f64 / 1000.0
+    }
+}
@@ -0,0 +1,250 @@
+use std::{collections::HashMap, f64::consts::PI};
+
+use crate::{
+    dto::RatingDto,
+    handicap::{self, HANDICAP_KOMI, MAX_HANDICAP},
+};
+
+pub const DEFAULT_RATING: f64 = 1500.0;
+pub const DEFAULT_DEVIATION: f64 = 350.0;
+pub const DEFAULT_VOLATILITY: f64 = 0.06;
+/// Constrains the volatility change, Glickman suggests 0.3 to 1.2
+pub const TAU: f64 = 0.5;
+
+/// Rating of the 1 dan rank, every 100 points is one rank
+pub const FIRST_DAN_RATING: f64 = 2000.0;
+pub const RANK_POINTS: f64 = 100.0;
+pub const MAX_KYU: i32 = 30;
+pub const MAX_DAN: i32 = 9;
+
+/// Conversion between the Glicko and the Glicko-2 scales
+const SCALE: f64 = 173.7178;
+const CONVERGENCE: f64 = 0.000001;
+
+/// Glicko-2 rating of a player
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rating {
+    pub rating: f64,
+    /// Rating deviation, the uncertainty of the rating
+    pub deviation: f64,
+    /// How erratic the results of the player are
+    pub volatility: f64,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self {
+            rating: DEFAULT_RATING,
+            deviation: DEFAULT_DEVIATION,
+            volatility: DEFAULT_VOLATILITY,
+        }
+    }
+}
+
+impl From<RatingDto> for Rating {
+    fn from(dto: RatingDto) -> Self {
+        Self {
+            rating: dto.rating,
+            deviation: dto.deviation,
+            volatility: dto.volatility,
+        }
+    }
+}
+
+impl From<Rating> for RatingDto {
+    fn from(rating: Rating) -> Self {
+        Self {
+            rating: rating.rating,
+            deviation: rating.deviation,
+            volatility: rating.volatility,
+        }
+    }
+}
+
+/// A game of the rating period seen by one player
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GameOutcome {
+    /// Rating of the opponent at the start of the period
+    pub opponent: Rating,
+    /// 1 for a win, 0.5 for a draw, 0 for a loss
+    pub score: f64,
+}
+
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt()
+}
+
+fn expected(mu: f64, mu_j: f64, phi_j: f64) -> f64 {
+    1.0 / (1.0 + (-g(phi_j) * (mu - mu_j)).exp())
+}
+
+impl Rating {
+    /// The rating after the games of one period, only the deviation grows without games
+    pub fn update(&self, games: &[GameOutcome]) -> Rating {
+        let mu = (self.rating - DEFAULT_RATING) / SCALE;
+        let phi = self.deviation / SCALE;
+        if games.is_empty() {
+            let phi = (phi * phi + self.volatility * self.volatility).sqrt();
+            return Rating {
+                deviation: (phi * SCALE).min(DEFAULT_DEVIATION),
+                ..*self
+            };
+        }
+        let mut v_inverse = 0.0;
+        let mut improvement = 0.0;
+        for game in games {
+            let mu_j = (game.opponent.rating - DEFAULT_RATING) / SCALE;
+            let phi_j = game.opponent.deviation / SCALE;
+            let e = expected(mu, mu_j, phi_j);
+            v_inverse += g(phi_j).powi(2) * e * (1.0 - e);
+            improvement += g(phi_j) * (game.score - e);
+        }
+        let v = 1.0 / v_inverse;
+        let delta = v * improvement;
+        let volatility = self.new_volatility(phi, v, delta);
+        let phi_star = (phi * phi + volatility * volatility).sqrt();
+        let new_phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / v).sqrt();
+        let new_mu = mu + new_phi * new_phi * improvement;
+        Rating {
+            rating: new_mu * SCALE + DEFAULT_RATING,
+            deviation: (new_phi * SCALE).min(DEFAULT_DEVIATION),
+            volatility,
+        }
+    }
+
+    /// Step 5 of the Glicko-2 paper, the Illinois algorithm
+    fn new_volatility(&self, phi: f64, v: f64, delta: f64) -> f64 {
+        let a = (self.volatility * self.volatility).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            let d = phi * phi + v + ex;
+            ex * (delta * delta - phi * phi - v - ex) / (2.0 * d * d) - (x - a) / (TAU * TAU)
+        };
+        let mut big_a = a;
+        let mut big_b = if delta * delta > phi * phi + v {
+            (delta * delta - phi * phi - v).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * TAU) < 0.0 {
+                k += 1.0;
+            }
+            a - k * TAU
+        };
+        let mut f_a = f(big_a);
+        let mut f_b = f(big_b);
+        while (big_b - big_a).abs() > CONVERGENCE {
+            let big_c = big_a + (big_a - big_b) * f_a / (f_b - f_a);
+            let f_c = f(big_c);
+            if f_c * f_b <= 0.0 {
+                big_a = big_b;
+                f_a = f_b;
+            } else {
+                f_a /= 2.0;
+            }
+            big_b = big_c;
+            f_b = f_c;
+        }
+        (big_a / 2.0).exp()
+    }
+
+    /// Comparable rank value like `lobby::rank_value`: 1k = -1, 1d = 0
+    pub fn rank_value(&self) -> i32 {
+        let ranks = ((self.rating - FIRST_DAN_RATING) / RANK_POINTS).floor() as i32;
+        ranks.clamp(-MAX_KYU, MAX_DAN - 1)
+    }
+
+    /// Kyu/dan rank like "5k" or "2d"
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use game_core::rating::Rating;
+    ///
+    /// let rank = |rating| Rating { rating, ..Default::default() }.rank();
+    /// assert_eq!("5k", rank(1500.0));
+    /// assert_eq!("1k", rank(1999.0));
+    /// assert_eq!("1d", rank(2000.0));
+    /// assert_eq!("30k", rank(-2000.0));
+    /// ```
+    pub fn rank(&self) -> String {
+        rank_name(self.rank_value())
+    }
+}
+
+/// Name of a rank value, the inverse of `lobby::rank_value`
+pub fn rank_name(rank_value: i32) -> String {
+    if rank_value < 0 {
+        format!("{}k", -rank_value)
+    } else {
+        format!("{}d", rank_value + 1)
+    }
+}
+
+/// Stones and komi of a game, black takes the stones
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HandicapSuggestion {
+    pub stones: usize,
+    pub komi: f32,
+}
+
+/// Handicap for the weaker player taking black: one stone per rank of difference,
+/// a single rank only removes the komi. An even game when black is not weaker.
+pub fn suggest_handicap(black: &Rating, white: &Rating, komi: f32) -> HandicapSuggestion {
+    let difference = (white.rank_value() - black.rank_value()).max(0) as usize;
+    match difference {
+        0 => HandicapSuggestion { stones: 0, komi },
+        1 => HandicapSuggestion {
+            stones: 0,
+            komi: HANDICAP_KOMI,
+        },
+        _ => {
+            let stones = difference.min(MAX_HANDICAP);
+            HandicapSuggestion {
+                stones,
+                komi: handicap::handicap_komi(komi, stones),
+            }
+        }
+    }
+}
+
+/// Games collected over a rating period, the ratings are updated together when it closes
+#[derive(Debug, Clone, Default)]
+pub struct RatingPeriod {
+    games: Vec<(i64, i64, f64)>,
+}
+
+impl RatingPeriod {
+    /// `score` is the result of the first player
+    pub fn record(&mut self, player: i64, opponent: i64, score: f64) {
+        self.games.push((player, opponent, score));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.games.is_empty()
+    }
+
+    /// Update every rated player with the ratings from the start of the period.
+    /// New players start from the default rating.
+    pub fn close(self, ratings: &mut HashMap<i64, Rating>) {
+        for (player, opponent, _) in self.games.iter() {
+            ratings.entry(*player).or_default();
+            ratings.entry(*opponent).or_default();
+        }
+        let start = ratings.clone();
+        let mut outcomes: HashMap<i64, Vec<GameOutcome>> = HashMap::new();
+        for (player, opponent, score) in self.games {
+            outcomes.entry(player).or_default().push(GameOutcome {
+                opponent: start[&opponent],
+                score,
+            });
+            outcomes.entry(opponent).or_default().push(GameOutcome {
+                opponent: start[&player],
+                score: 1.0 - score,
+            });
+        }
+        for (player, rating) in ratings.iter_mut() {
+            let games = outcomes.remove(player).unwrap_or_default();
+            *rating = start[player].update(&games);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::dto::UserSessionDto;
+
+/// Godot path of the file keeping the game to rejoin after a restart
+pub const ACTIVE_GAME_PATH: &str = "user://active_game.json";
+
+const MAX_RECONNECT_DELAY_SECS: f64 = 30.0;
+
+/// The token is not kept here, the game is offered again once the same user signs in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveGame {
+    pub session: UserSessionDto,
+    pub user_id: i64,
+}
+
+impl ActiveGame {
+    pub fn load(path: &Path) -> Option<ActiveGame> {
+        let content = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    pub fn clear(path: &Path) -> io::Result<()> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Exponential backoff for reconnect attempts: 1s, 2s, 4s ... up to 30s
+pub fn reconnect_delay_secs(attempt: u32) -> f64 {
+    2f64.powi(attempt.min(5) as i32)
+        .min(MAX_RECONNECT_DELAY_SECS)
+}
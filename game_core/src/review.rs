@@ -0,0 +1,329 @@
+use crate::{
+    dto::GameRecordDto,
+    rules::{GoGame, Move, MoveError},
+    scoring::Point,
+    sgf::{self, SgfError, SgfNode},
+};
+
+/// A position of the game tree, the root has no move
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReviewNode {
+    pub mv: Option<Move>,
+    pub comment: String,
+    parent: Option<usize>,
+    /// The first child continues the main line, the others are variations
+    children: Vec<usize>,
+}
+
+/// The game record being reviewed: all variations and the line shown on the board.
+/// Moving back keeps the line, so moving forward again returns to the same branch.
+#[derive(Debug, Clone)]
+pub struct GameTree {
+    size: usize,
+    komi: f32,
+    handicap: Vec<Point>,
+    /// `PB` and `PW` of the record, empty when unknown
+    players: [String; 2],
+    nodes: Vec<ReviewNode>,
+    /// Node indices from the root to the end of the shown line
+    line: Vec<usize>,
+    /// Index in `line` of the shown position, also the move number
+    position: usize,
+}
+
+impl GameTree {
+    pub fn new(size: usize, komi: f32, handicap: &[Point]) -> Self {
+        Self {
+            size,
+            komi,
+            handicap: handicap.to_vec(),
+            players: Default::default(),
+            nodes: vec![ReviewNode::default()],
+            line: vec![0],
+            position: 0,
+        }
+    }
+
+    /// The main line of a finished game, moves are expected to be legal
+    pub fn from_moves(size: usize, komi: f32, handicap: &[Point], moves: &[Move]) -> Self {
+        let mut tree = Self::new(size, komi, handicap);
+        let mut parent = 0;
+        for m in moves {
+            parent = tree.add_child(parent, *m);
+        }
+        tree.extend_line();
+        tree
+    }
+
+    /// The move history sent by the server
+    pub fn from_record(record: &GameRecordDto) -> Self {
+        let moves: Vec<Move> = record
+            .moves
+            .iter()
+            .map(|m| match m.step.as_ref() {
+                Some(step) => Move::Play {
+                    point: (step.row as usize, step.col as usize),
+                    color: m.color,
+                },
+                None => Move::Pass { color: m.color },
+            })
+            .collect();
+        let handicap: Vec<Point> = record
+            .handicap_stones
+            .iter()
+            .map(|step| (step.row as usize, step.col as usize))
+            .collect();
+        Self::from_moves(
+            record.settings.board_size as usize,
+            record.settings.komi,
+            &handicap,
+            &moves,
+        )
+    }
+
+    /// Build the tree from a parsed SGF, checking every move of every variation
+    pub fn from_sgf(root: &SgfNode) -> Result<Self, SgfError> {
+        let record = sgf::record_from_tree(root)?;
+        let mut tree = Self::new(record.size, record.komi, &record.handicap);
+        for (index, ident) in ["PB", "PW"].iter().enumerate() {
+            tree.players[index] = root.property(ident).unwrap_or_default().trim().to_string();
+        }
+        let game = tree.game();
+        match node_move(root, record.size)? {
+            // The root holds the first move, keep an empty position before it
+            Some(_) => tree.add_sgf_node(0, root, &game)?,
+            None => {
+                tree.nodes[0].comment = root.property("C").unwrap_or_default().to_string();
+                for child in root.children.iter() {
+                    tree.add_sgf_node(0, child, &game)?;
+                }
+            }
+        }
+        tree.extend_line();
+        Ok(tree)
+    }
+
+    fn add_sgf_node(
+        &mut self,
+        parent: usize,
+        node: &SgfNode,
+        game: &GoGame,
+    ) -> Result<(), SgfError> {
+        let mut game = game.clone();
+        let mut parent = parent;
+        let mut node = node;
+        // Follow single child sequences in a loop, recurse on variations only
+        loop {
+            if let Some(m) = node_move(node, game.size())? {
+                game.play_move(m)
+                    .map_err(|e| SgfError::IllegalMove(game.moves().len() + 1, e))?;
+                parent = self.add_child(parent, m);
+                self.nodes[parent].comment = node.property("C").unwrap_or_default().to_string();
+            }
+            match node.children.as_slice() {
+                [] => return Ok(()),
+                [child] => node = child,
+                children => {
+                    for child in children {
+                        self.add_sgf_node(parent, child, &game)?;
+                    }
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn add_child(&mut self, parent: usize, m: Move) -> usize {
+        self.nodes.push(ReviewNode {
+            mv: Some(m),
+            comment: String::new(),
+            parent: Some(parent),
+            children: vec![],
+        });
+        let child = self.nodes.len() - 1;
+        self.nodes[parent].children.push(child);
+        child
+    }
+
+    /// Continue the shown line from its last node along the first children
+    fn extend_line(&mut self) {
+        let mut last = *self.line.last().unwrap();
+        while let Some(child) = self.nodes[last].children.first() {
+            last = *child;
+            self.line.push(last);
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn komi(&self) -> f32 {
+        self.komi
+    }
+
+    /// Name of the player from the record, `None` when the record has none
+    pub fn player_name(&self, color: bool) -> Option<&str> {
+        let name = &self.players[if color { 0 } else { 1 }];
+        (!name.is_empty()).then_some(name.as_str())
+    }
+
+    pub fn current(&self) -> &ReviewNode {
+        &self.nodes[self.line[self.position]]
+    }
+
+    pub fn move_number(&self) -> usize {
+        self.position
+    }
+
+    /// Number of moves in the shown line
+    pub fn line_length(&self) -> usize {
+        self.line.len() - 1
+    }
+
+    /// Replay the shown line up to the current position
+    pub fn game(&self) -> GoGame {
+        let mut game = GoGame::with_handicap(self.size, self.komi, &self.handicap);
+        for index in &self.line[1..=self.position] {
+            if let Some(m) = self.nodes[*index].mv {
+                // Every move was checked when the node was added
+                let _ = game.play_move(m);
+            }
+        }
+        game
+    }
+
+    pub fn forward(&mut self) -> bool {
+        self.jump(self.position + 1)
+    }
+
+    pub fn back(&mut self) -> bool {
+        self.position > 0 && self.jump(self.position - 1)
+    }
+
+    pub fn to_start(&mut self) {
+        self.position = 0;
+    }
+
+    pub fn to_end(&mut self) {
+        self.position = self.line_length();
+    }
+
+    /// Go to the move of the shown line, returns false when it's out of the line
+    pub fn jump(&mut self, move_number: usize) -> bool {
+        if move_number > self.line_length() {
+            return false;
+        }
+        self.position = move_number;
+        true
+    }
+
+    /// Play a move at the current position, an existing variation with the same move is reused
+    pub fn play(&mut self, m: Move) -> Result<(), MoveError> {
+        let current = self.line[self.position];
+        let existing = self.nodes[current]
+            .children
+            .iter()
+            .copied()
+            .find(|child| self.nodes[*child].mv == Some(m));
+        let child = match existing {
+            Some(child) => child,
+            None => {
+                self.game().play_move(m)?;
+                self.add_child(current, m)
+            }
+        };
+        if self.line.get(self.position + 1) != Some(&child) {
+            self.line.truncate(self.position + 1);
+            self.line.push(child);
+            self.extend_line();
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    /// `(index, count)` of the current move among the alternatives to it
+    pub fn variation(&self) -> (usize, usize) {
+        let current = self.line[self.position];
+        match self.nodes[current].parent {
+            Some(parent) => {
+                let siblings = &self.nodes[parent].children;
+                let index = siblings.iter().position(|s| *s == current).unwrap_or(0);
+                (index, siblings.len())
+            }
+            None => (0, 1),
+        }
+    }
+
+    /// Switch the current move to the next alternative, wrapping around
+    pub fn next_variation(&mut self) -> bool {
+        let (index, count) = self.variation();
+        if count < 2 {
+            return false;
+        }
+        let parent = self.line[self.position - 1];
+        let sibling = self.nodes[parent].children[(index + 1) % count];
+        self.line.truncate(self.position);
+        self.line.push(sibling);
+        self.extend_line();
+        true
+    }
+
+    pub fn set_comment(&mut self, comment: &str) {
+        let current = self.line[self.position];
+        self.nodes[current].comment = comment.to_string();
+    }
+
+    /// Serialize the whole tree with variations and comments
+    pub fn to_sgf(&self) -> String {
+        let mut sgf = format!(
+            "(;{}",
+            sgf::root_properties(self.size, self.komi, &self.handicap)
+        );
+        for (ident, name) in ["PB", "PW"].iter().zip(self.players.iter()) {
+            if !name.is_empty() {
+                sgf.push_str(&format!("{}[{}]", ident, sgf::escape_value(name)));
+            }
+        }
+        self.write_sgf_node(0, &mut sgf);
+        sgf.push(')');
+        sgf
+    }
+
+    fn write_sgf_node(&self, index: usize, sgf: &mut String) {
+        let mut index = index;
+        loop {
+            let node = &self.nodes[index];
+            if let Some(m) = node.mv {
+                sgf.push_str(&sgf::move_to_sgf(&m));
+            }
+            if !node.comment.is_empty() {
+                sgf.push_str(&format!("C[{}]", sgf::escape_value(&node.comment)));
+            }
+            match node.children.as_slice() {
+                [] => return,
+                [child] => index = *child,
+                children => {
+                    for child in children {
+                        sgf.push('(');
+                        self.write_sgf_node(*child, sgf);
+                        sgf.push(')');
+                    }
+                    return;
+                }
+            }
+        }
+    }
+}
+
+fn node_move(node: &SgfNode, size: usize) -> Result<Option<Move>, SgfError> {
+    for (ident, color) in [("B", true), ("W", false)] {
+        if let Some(value) = node.property(ident) {
+            return Ok(Some(match sgf::point_from_sgf(value.trim(), size)? {
+                Some(point) => Move::Play { point, color },
+                None => Move::Pass { color },
+            }));
+        }
+    }
+    Ok(None)
+}
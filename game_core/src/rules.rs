@@ -0,0 +1,288 @@
+use std::{collections::HashSet, fmt::Display};
+
+use crate::{
+    dto::GameScore,
+    scoring::{self, BoardGrid, Point},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Move {
+    Play { point: Point, color: bool },
+    Pass { color: bool },
+}
+
+impl Move {
+    pub fn color(&self) -> bool {
+        match self {
+            Move::Play { color, .. } | Move::Pass { color } => *color,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoveError {
+    OutOfBoard,
+    Occupied,
+    Suicide,
+    Ko,
+    GameOver,
+    HandicapAfterFirstMove,
+    /// The move is recorded for the color which is not to move
+    WrongColor,
+}
+
+impl Display for MoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            MoveError::OutOfBoard => "the point is out of the board",
+            MoveError::Occupied => "the point is occupied",
+            MoveError::Suicide => "suicide is not allowed",
+            MoveError::Ko => "the move retakes a ko",
+            MoveError::GameOver => "the game is over",
+            MoveError::HandicapAfterFirstMove => "handicap stones go before the first move",
+            MoveError::WrongColor => "it's the other color's turn",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Position {
+    board: BoardGrid,
+    to_move: bool,
+    captures: GameScore,
+    passes: u32,
+}
+
+/// An in-process Go game: board, turn, captures and move history.
+/// Black moves first unless handicap stones were placed, then white starts.
+/// The game is over after two consecutive passes.
+#[derive(Debug, Clone)]
+pub struct GoGame {
+    size: usize,
+    komi: f32,
+    position: Position,
+    history: Vec<Position>,
+    moves: Vec<Move>,
+    handicap: Vec<Point>,
+}
+
+/// Count the distinct empty points adjacent to the stones
+pub fn liberties_of(board: &BoardGrid, group: &[Point]) -> usize {
+    group
+        .iter()
+        .flat_map(|point| scoring::neighbors(board, *point))
+        .filter(|point| scoring::get(board, *point).is_none())
+        .collect::<HashSet<Point>>()
+        .len()
+}
+
+impl GoGame {
+    pub fn new(size: usize, komi: f32) -> Self {
+        Self {
+            size,
+            komi,
+            position: Position {
+                board: vec![vec![None; size]; size],
+                to_move: true,
+                captures: GameScore { black: 0, white: 0 },
+                passes: 0,
+            },
+            history: vec![],
+            moves: vec![],
+            handicap: vec![],
+        }
+    }
+
+    /// A game with black handicap stones already placed, white to move
+    pub fn with_handicap(size: usize, komi: f32, stones: &[Point]) -> Self {
+        let mut game = Self::new(size, komi);
+        for point in stones {
+            let _ = game.place_handicap(*point);
+        }
+        game.start_after_handicap();
+        game
+    }
+
+    /// Put a black handicap stone before the first move, the turn doesn't change
+    pub fn place_handicap(&mut self, point: Point) -> Result<(), MoveError> {
+        let (row, col) = point;
+        if row >= self.size || col >= self.size {
+            return Err(MoveError::OutOfBoard);
+        }
+        if !self.moves.is_empty() {
+            return Err(MoveError::HandicapAfterFirstMove);
+        }
+        if self.position.board[row][col].is_some() {
+            return Err(MoveError::Occupied);
+        }
+        self.position.board[row][col] = Some(true);
+        self.handicap.push(point);
+        Ok(())
+    }
+
+    /// Give the first move to white once handicap stones are on the board
+    pub fn start_after_handicap(&mut self) {
+        if !self.handicap.is_empty() && self.moves.is_empty() {
+            self.position.to_move = false;
+        }
+    }
+
+    pub fn handicap(&self) -> &[Point] {
+        &self.handicap
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn komi(&self) -> f32 {
+        self.komi
+    }
+
+    pub fn board(&self) -> &BoardGrid {
+        &self.position.board
+    }
+
+    /// `true` when black is to move
+    pub fn to_move(&self) -> bool {
+        self.position.to_move
+    }
+
+    pub fn captures(&self) -> &GameScore {
+        &self.position.captures
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.position.passes >= 2
+    }
+
+    /// Liberties of the group at `point`, zero for an empty point
+    pub fn liberties(&self, point: Point) -> usize {
+        let group = scoring::group_at(&self.position.board, point);
+        if group.is_empty() {
+            return 0;
+        }
+        liberties_of(&self.position.board, &group)
+    }
+
+    /// Board and captured stones after playing `point`, without applying the move
+    fn try_play(&self, point: Point) -> Result<(BoardGrid, Vec<Point>), MoveError> {
+        if self.is_over() {
+            return Err(MoveError::GameOver);
+        }
+        let (row, col) = point;
+        if row >= self.size || col >= self.size {
+            return Err(MoveError::OutOfBoard);
+        }
+        if self.position.board[row][col].is_some() {
+            return Err(MoveError::Occupied);
+        }
+        let color = self.position.to_move;
+        let mut board = self.position.board.clone();
+        board[row][col] = Some(color);
+        let mut captured = vec![];
+        for next in scoring::neighbors(&board, point) {
+            if scoring::get(&board, next) != Some(!color) || captured.contains(&next) {
+                continue;
+            }
+            let group = scoring::group_at(&board, next);
+            if liberties_of(&board, &group) == 0 {
+                captured.extend(group);
+            }
+        }
+        captured.iter().for_each(|(r, c)| board[*r][*c] = None);
+        if captured.is_empty() && self.liberties_on(&board, point) == 0 {
+            return Err(MoveError::Suicide);
+        }
+        if self
+            .history
+            .last()
+            .is_some_and(|previous| previous.board == board)
+        {
+            return Err(MoveError::Ko);
+        }
+        Ok((board, captured))
+    }
+
+    fn liberties_on(&self, board: &BoardGrid, point: Point) -> usize {
+        liberties_of(board, &scoring::group_at(board, point))
+    }
+
+    pub fn is_legal(&self, point: Point) -> Result<(), MoveError> {
+        self.try_play(point).map(|_| ())
+    }
+
+    /// Play a stone of the color to move. Returns the captured stones.
+    pub fn play(&mut self, point: Point) -> Result<Vec<Point>, MoveError> {
+        let (board, captured) = self.try_play(point)?;
+        let color = self.position.to_move;
+        let mut position = Position {
+            board,
+            to_move: !color,
+            captures: self.position.captures.clone(),
+            passes: 0,
+        };
+        match color {
+            true => position.captures.black += captured.len() as i32,
+            false => position.captures.white += captured.len() as i32,
+        }
+        self.history
+            .push(std::mem::replace(&mut self.position, position));
+        self.moves.push(Move::Play { point, color });
+        Ok(captured)
+    }
+
+    pub fn pass(&mut self) -> Result<(), MoveError> {
+        if self.is_over() {
+            return Err(MoveError::GameOver);
+        }
+        let color = self.position.to_move;
+        let mut position = self.position.clone();
+        position.to_move = !color;
+        position.passes += 1;
+        self.history
+            .push(std::mem::replace(&mut self.position, position));
+        self.moves.push(Move::Pass { color });
+        Ok(())
+    }
+
+    /// Play a recorded move, its color has to be the color to move
+    pub fn play_move(&mut self, m: Move) -> Result<(), MoveError> {
+        if m.color() != self.to_move() {
+            return Err(MoveError::WrongColor);
+        }
+        match m {
+            Move::Play { point, .. } => self.play(point).map(|_| ()),
+            Move::Pass { .. } => self.pass(),
+        }
+    }
+
+    /// Take back the last move
+    pub fn undo(&mut self) -> Option<Move> {
+        let previous = self.history.pop()?;
+        self.position = previous;
+        self.moves.pop()
+    }
+
+    pub fn legal_moves(&self) -> Vec<Point> {
+        (0..self.size)
+            .flat_map(|row| (0..self.size).map(move |col| (row, col)))
+            .filter(|point| self.is_legal(*point).is_ok())
+            .collect()
+    }
+
+    /// Territory score with the given dead stones, komi goes to white
+    pub fn score(&self, dead: &HashSet<Point>) -> (f32, f32) {
+        let estimate = scoring::estimate(&self.position.board, dead);
+        let captures = &self.position.captures;
+        (
+            estimate.black(captures.black) as f32,
+            estimate.white(captures.white) as f32 + self.komi,
+        )
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::dto::StepDto;
+
+/// A board point as `(row, col)`
+pub type Point = (usize, usize);
+
+/// The board representation shared with the server: `Some(true)` is black,
+/// `Some(false)` is white and `None` is an empty point
+pub type BoardGrid = Vec<Vec<Option<bool>>>;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScoreEstimate {
+    pub black_territory: i32,
+    pub white_territory: i32,
+    /// White stones marked as dead, counted as black prisoners
+    pub black_prisoners: i32,
+    /// Black stones marked as dead, counted as white prisoners
+    pub white_prisoners: i32,
+}
+
+impl ScoreEstimate {
+    pub fn black(&self, captures: i32) -> i32 {
+        captures + self.black_territory + self.black_prisoners
+    }
+
+    pub fn white(&self, captures: i32) -> i32 {
+        captures + self.white_territory + self.white_prisoners
+    }
+}
+
+pub fn neighbors(board: &BoardGrid, (row, col): Point) -> impl Iterator<Item = Point> {
+    [
+        (row > 0).then(|| (row - 1, col)),
+        (col > 0).then(|| (row, col - 1)),
+        (row + 1 < board.len()).then_some((row + 1, col)),
+        (col + 1 < board[row].len()).then_some((row, col + 1)),
+    ]
+    .into_iter()
+    .flatten()
+}
+
+pub fn get(board: &BoardGrid, (row, col): Point) -> Option<bool> {
+    board.get(row).and_then(|r| r.get(col)).copied().flatten()
+}
+
+/// Collect the chain of same-colored stones connected to `point`.
+/// Returns an empty vec when the point is empty or off the board.
+pub fn group_at(board: &BoardGrid, point: Point) -> Vec<Point> {
+    let color = match get(board, point) {
+        Some(color) => color,
+        None => return vec![],
+    };
+    let mut visited = HashSet::from([point]);
+    let mut queue = VecDeque::from([point]);
+    let mut group = vec![];
+    while let Some(current) = queue.pop_front() {
+        group.push(current);
+        for next in neighbors(board, current) {
+            if get(board, next) == Some(color) && visited.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    group
+}
+
+/// Toggle the dead flag of the whole group at `point`
+pub fn toggle_dead_group(board: &BoardGrid, dead: &mut HashSet<Point>, point: Point) {
+    let group = group_at(board, point);
+    if group.is_empty() {
+        return;
+    }
+    if dead.contains(&point) {
+        group.iter().for_each(|p| {
+            dead.remove(p);
+        });
+    } else {
+        dead.extend(group);
+    }
+}
+
+/// Resolve the owner of every empty (or dead) point.
+/// A region belongs to a color when only that color's living stones border it.
+pub fn territory(board: &BoardGrid, dead: &HashSet<Point>) -> BoardGrid {
+    let living = |point: Point| {
+        if dead.contains(&point) {
+            None
+        } else {
+            get(board, point)
+        }
+    };
+    let mut owners: BoardGrid = board.iter().map(|row| vec![None; row.len()]).collect();
+    let mut visited: HashSet<Point> = HashSet::new();
+    for (row_num, row) in board.iter().enumerate() {
+        for col_num in 0..row.len() {
+            let start = (row_num, col_num);
+            if living(start).is_some() || visited.contains(&start) {
+                continue;
+            }
+            let mut region = vec![];
+            let mut borders = HashSet::new();
+            let mut queue = VecDeque::from([start]);
+            visited.insert(start);
+            while let Some(current) = queue.pop_front() {
+                region.push(current);
+                for next in neighbors(board, current) {
+                    match living(next) {
+                        Some(color) => {
+                            borders.insert(color);
+                        }
+                        None => {
+                            if visited.insert(next) {
+                                queue.push_back(next);
+                            }
+                        }
+                    }
+                }
+            }
+            if borders.len() == 1 {
+                let owner = borders.into_iter().next();
+                region.iter().for_each(|(r, c)| owners[*r][*c] = owner);
+            }
+        }
+    }
+    owners
+}
+
+/// Count territory and prisoners for the given board and dead stones
+pub fn estimate(board: &BoardGrid, dead: &HashSet<Point>) -> ScoreEstimate {
+    let owners = territory(board, dead);
+    let mut score = ScoreEstimate::default();
+    for (row_num, row) in owners.iter().enumerate() {
+        for (col_num, owner) in row.iter().enumerate() {
+            match owner {
+                Some(true) => score.black_territory += 1,
+                Some(false) => score.white_territory += 1,
+                None => {}
+            }
+            if dead.contains(&(row_num, col_num)) {
+                match get(board, (row_num, col_num)) {
+                    Some(true) => score.white_prisoners += 1,
+                    Some(false) => score.black_prisoners += 1,
+                    None => {}
+                }
+            }
+        }
+    }
+    score
+}
+
+pub fn dead_from_steps(steps: &[StepDto]) -> HashSet<Point> {
+    steps
+        .iter()
+        .filter(|s| s.row >= 0 && s.col >= 0)
+        .map(|s| (s.row as usize, s.col as usize))
+        .collect()
+}
+
+pub fn dead_to_steps(dead: &HashSet<Point>) -> Vec<StepDto> {
+    let mut steps: Vec<StepDto> = dead
+        .iter()
+        .map(|(row, col)| StepDto {
+            row: *row as i32,
+            col: *col as i32,
+        })
+        .collect();
+    steps.sort_by_key(|s| (s.row, s.col));
+    steps
+}
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use crate::dto::{UserSessionDto, UserSessionRequestDto, UserSessionStepDto, UserTokenDto};
+
+/// Shown in place of a player who hasn't joined yet
+pub const WAITING_PLAYER: &str = "Wait...";
+
+pub fn color_name(color: bool) -> &'static str {
+    if color {
+        "Black"
+    } else {
+        "White"
+    }
+}
+
+pub fn score_label_text(player: &str, score: i32) -> String {
+    format!("{}: {}", player, score)
+}
+
+/// The online game as seen by the signed in user or by a spectator
+#[derive(Debug, Clone, Default)]
+pub struct GameSession {
+    pub session: Option<UserSessionDto>,
+    pub token: Option<UserTokenDto>,
+    /// Color of the user, known after the first game state
+    pub user_color: Option<bool>,
+    /// Watching a game of other players, the board and the actions are read-only
+    pub spectator: bool,
+    /// Logins of the players by color, known to spectators only
+    pub player_logins: HashMap<bool, String>,
+}
+
+impl GameSession {
+    pub fn new(session: Option<UserSessionDto>, token: Option<UserTokenDto>) -> Self {
+        Self {
+            session,
+            token,
+            ..Default::default()
+        }
+    }
+
+    /// `-1` before sign in
+    pub fn user_id(&self) -> i64 {
+        self.token.as_ref().map(|t| t.user_id).unwrap_or(-1)
+    }
+
+    pub fn session_id(&self) -> Option<String> {
+        self.session.as_ref().map(|s| s.session_id.clone())
+    }
+
+    pub fn session_step(&self) -> UserSessionStepDto {
+        UserSessionStepDto {
+            session_id: self.session_id().unwrap_or_default(),
+            user_id: self.user_id(),
+        }
+    }
+
+    pub fn session_request(&self) -> UserSessionRequestDto {
+        UserSessionRequestDto {
+            user_id: self.user_id(),
+            session_id: self.session_id(),
+            ..Default::default()
+        }
+    }
+
+    pub fn nick(&self) -> String {
+        self.token
+            .as_ref()
+            .map(|t| t.login.clone())
+            .unwrap_or_default()
+    }
+
+    /// The other player of the session, [`WAITING_PLAYER`] until someone joins
+    pub fn opponent_nick(&self) -> String {
+        let session = match self.session.as_ref() {
+            Some(session) => session,
+            None => return WAITING_PLAYER.to_string(),
+        };
+        if session.user1.login != self.nick() {
+            return session.user1.login.clone();
+        }
+        session
+            .user2
+            .as_ref()
+            .map(|u| u.login.clone())
+            .unwrap_or(WAITING_PLAYER.to_string())
+    }
+
+    pub fn player_login(&self, user_id: i64) -> Option<String> {
+        let session = self.session.as_ref()?;
+        std::iter::once(&session.user1)
+            .chain(session.user2.as_ref())
+            .find(|user| user.user_id == Some(user_id))
+            .map(|user| user.login.clone())
+    }
+
+    /// Take the colors of the game state: the user's own color or, for spectators,
+    /// the logins of both players
+    pub fn set_colors(&mut self, colors: &HashMap<i64, bool>) {
+        if self.spectator {
+            self.player_logins = colors
+                .iter()
+                .filter_map(|(user_id, color)| Some((*color, self.player_login(*user_id)?)))
+                .collect();
+            return;
+        }
+        self.user_color = colors.get(&self.user_id()).copied();
+    }
+
+    /// Who plays the color: the login when it's known, the color name to spectators
+    pub fn player_name(&self, color: bool) -> String {
+        if self.spectator {
+            return self
+                .player_logins
+                .get(&color)
+                .cloned()
+                .unwrap_or(color_name(color).to_string());
+        }
+        match self.user_color {
+            Some(user_color) if user_color == color => self.nick(),
+            Some(_) => self.opponent_nick(),
+            None => WAITING_PLAYER.to_string(),
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use std::fmt::Display;
+
+use crate::{
+    rules::{GoGame, Move, MoveError},
+    scoring::Point,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SgfError {
+    Syntax(String),
+    BadCoordinate(String),
+    IllegalMove(usize, MoveError),
+}
+
+impl Display for SgfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SgfError::Syntax(message) => write!(f, "SGF syntax error: {}", message),
+            SgfError::BadCoordinate(value) => write!(f, "SGF bad coordinate: {}", value),
+            SgfError::IllegalMove(number, e) => write!(f, "SGF move {} is illegal: {}", number, e),
+        }
+    }
+}
+
+/// The biggest board SGF coordinates can address
+pub const MAX_SGF_SIZE: usize = 52;
+
+/// A parsed SGF game record: root properties and the main line of moves
+#[derive(Debug, Clone, PartialEq)]
+pub struct SgfRecord {
+    pub size: usize,
+    pub komi: f32,
+    /// Black stones added before the first move (`AB`)
+    pub handicap: Vec<Point>,
+    pub moves: Vec<Move>,
+}
+
+/// Boards up to this size may write a pass as "tt" (FF[3])
+const TT_PASS_MAX_SIZE: usize = 19;
+
+fn coordinate_to_sgf(index: usize) -> char {
+    match index {
+        0..=25 => (b'a' + index as u8) as char,
+        _ => (b'A' + (index - 26) as u8) as char,
+    }
+}
+
+fn coordinate_from_sgf(letter: u8) -> Option<usize> {
+    match letter {
+        b'a'..=b'z' => Some((letter - b'a') as usize),
+        b'A'..=b'Z' => Some((letter - b'A') as usize + 26),
+        _ => None,
+    }
+}
+
+/// SGF coordinates are "column row" letters, 'a'-'z' then 'A'-'Z' on boards over 26 lines
+///
+/// # Examples
+///
+/// ```
+/// use game_core::sgf::point_to_sgf;
+///
+/// assert_eq!("dc", point_to_sgf((2, 3)));
+/// assert_eq!("Az", point_to_sgf((25, 26)));
+/// ```
+pub fn point_to_sgf((row, col): Point) -> String {
+    format!("{}{}", coordinate_to_sgf(col), coordinate_to_sgf(row))
+}
+
+/// An empty value is a pass, so is "tt" on boards up to 19x19
+pub fn point_from_sgf(value: &str, size: usize) -> Result<Option<Point>, SgfError> {
+    if value.is_empty() || (value == "tt" && size <= TT_PASS_MAX_SIZE) {
+        return Ok(None);
+    }
+    match value.as_bytes() {
+        [col, row] => match (coordinate_from_sgf(*col), coordinate_from_sgf(*row)) {
+            (Some(col), Some(row)) if row < size && col < size => Ok(Some((row, col))),
+            _ => Err(SgfError::BadCoordinate(value.to_string())),
+        },
+        _ => Err(SgfError::BadCoordinate(value.to_string())),
+    }
+}
+
+pub fn move_to_sgf(m: &Move) -> String {
+    match m {
+        Move::Play { point, color } => {
+            format!(";{}[{}]", color_to_sgf(*color), point_to_sgf(*point))
+        }
+        Move::Pass { color } => format!(";{}[]", color_to_sgf(*color)),
+    }
+}
+
+/// Escape the characters closing an SGF value
+pub fn escape_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(']', "\\]")
+}
+
+fn color_to_sgf(color: bool) -> &'static str {
+    if color {
+        "B"
+    } else {
+        "W"
+    }
+}
+
+/// Properties of the root node, handicap stones included
+pub fn root_properties(size: usize, komi: f32, handicap: &[Point]) -> String {
+    let mut properties = format!("GM[1]FF[4]CA[UTF-8]SZ[{}]KM[{}]", size, komi);
+    if !handicap.is_empty() {
+        properties.push_str(&format!("HA[{}]AB", handicap.len()));
+        for point in handicap {
+            properties.push_str(&format!("[{}]", point_to_sgf(*point)));
+        }
+    }
+    properties
+}
+
+/// Serialize the game main line
+pub fn to_sgf(game: &GoGame) -> String {
+    let mut sgf = format!(
+        "(;{}",
+        root_properties(game.size(), game.komi(), game.handicap())
+    );
+    game.moves()
+        .iter()
+        .for_each(|m| sgf.push_str(&move_to_sgf(m)));
+    sgf.push(')');
+    sgf
+}
+
+/// A node of the SGF game tree, the first child continues the main line
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SgfNode {
+    pub properties: Vec<(String, Vec<String>)>,
+    pub children: Vec<SgfNode>,
+}
+
+impl SgfNode {
+    pub fn property(&self, ident: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|(id, _)| id == ident)
+            .and_then(|(_, values)| values.first())
+            .map(|v| v.as_str())
+    }
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), SgfError> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(SgfError::Syntax(format!(
+                "expected '{}', got '{}'",
+                expected, c
+            ))),
+            None => Err(SgfError::Syntax(format!(
+                "expected '{}', got the end",
+                expected
+            ))),
+        }
+    }
+
+    /// GameTree = "(" Node+ GameTree* ")"
+    fn game_tree(&mut self) -> Result<SgfNode, SgfError> {
+        self.expect('(')?;
+        let mut sequence = vec![];
+        loop {
+            self.skip_whitespace();
+            if self.chars.next_if_eq(&';').is_none() {
+                break;
+            }
+            sequence.push(self.node()?);
+        }
+        let mut variations = vec![];
+        loop {
+            self.skip_whitespace();
+            match self.chars.peek() {
+                Some('(') => variations.push(self.game_tree()?),
+                Some(')') => {
+                    self.chars.next();
+                    break;
+                }
+                _ => return Err(SgfError::Syntax("unterminated game tree".to_string())),
+            }
+        }
+        let mut last = sequence
+            .pop()
+            .ok_or(SgfError::Syntax("empty sequence".to_string()))?;
+        last.children = variations;
+        while let Some(mut node) = sequence.pop() {
+            node.children = vec![last];
+            last = node;
+        }
+        Ok(last)
+    }
+
+    /// Node = ";" (Ident "[" Value "]"+)*
+    fn node(&mut self) -> Result<SgfNode, SgfError> {
+        let mut node = SgfNode::default();
+        loop {
+            self.skip_whitespace();
+            let mut ident = String::new();
+            while let Some(c) = self.chars.next_if(|c| c.is_ascii_alphabetic()) {
+                // FF[3] allowed lowercase letters in identifiers, they are ignored
+                if c.is_ascii_uppercase() {
+                    ident.push(c);
+                }
+            }
+            if ident.is_empty() {
+                return Ok(node);
+            }
+            let mut values = vec![];
+            loop {
+                self.skip_whitespace();
+                if self.chars.next_if_eq(&'[').is_none() {
+                    break;
+                }
+                values.push(self.value()?);
+            }
+            if values.is_empty() {
+                return Err(SgfError::Syntax(format!("property {} has no value", ident)));
+            }
+            node.properties.push((ident, values));
+        }
+    }
+
+    fn value(&mut self) -> Result<String, SgfError> {
+        let mut value = String::new();
+        loop {
+            match self.chars.next() {
+                Some('\\') => {
+                    if let Some(escaped) = self.chars.next() {
+                        value.push(escaped);
+                    }
+                }
+                Some(']') => return Ok(value),
+                Some(c) => value.push(c),
+                None => return Err(SgfError::Syntax("unterminated value".to_string())),
+            }
+        }
+    }
+}
+
+/// Parse the whole game tree, returns the root node
+pub fn parse_tree(content: &str) -> Result<SgfNode, SgfError> {
+    Parser {
+        chars: content.chars().peekable(),
+    }
+    .game_tree()
+}
+
+/// Parse a game record following the main line of the tree
+pub fn parse_sgf(content: &str) -> Result<SgfRecord, SgfError> {
+    record_from_tree(&parse_tree(content)?)
+}
+
+/// Root properties and the main line of a parsed tree
+pub fn record_from_tree(root: &SgfNode) -> Result<SgfRecord, SgfError> {
+    let mut record = SgfRecord {
+        size: 19,
+        komi: 0.0,
+        handicap: vec![],
+        moves: vec![],
+    };
+    let mut node = Some(root);
+    while let Some(current) = node {
+        // The points depend on the size, whatever the order of the properties
+        if let Some(size) = current.property("SZ") {
+            apply_property(&mut record, "SZ", size)?;
+        }
+        for (ident, values) in current.properties.iter() {
+            if ident == "AB" {
+                for value in values {
+                    record
+                        .handicap
+                        .extend(point_from_sgf(value.trim(), record.size)?);
+                }
+                continue;
+            }
+            apply_property(&mut record, ident, &values[0])?;
+        }
+        node = current.children.first();
+    }
+    Ok(record)
+}
+
+fn apply_property(record: &mut SgfRecord, ident: &str, value: &str) -> Result<(), SgfError> {
+    match ident {
+        "SZ" => {
+            record.size = value
+                .trim()
+                .parse()
+                .ok()
+                .filter(|size| (1..=MAX_SGF_SIZE).contains(size))
+                .ok_or(SgfError::Syntax(format!("bad size {}", value)))?
+        }
+        "KM" => record.komi = value.trim().parse().unwrap_or(0.0),
+        "B" | "W" => {
+            let color = ident == "B";
+            let m = match point_from_sgf(value.trim(), record.size)? {
+                Some(point) => Move::Play { point, color },
+                None => Move::Pass { color },
+            };
+            record.moves.push(m);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Replay a record into a game, checking every move
+pub fn load_game(record: &SgfRecord) -> Result<GoGame, SgfError> {
+    let mut game = GoGame::with_handicap(record.size, record.komi, &record.handicap);
+    for (number, m) in record.moves.iter().enumerate() {
+        game.play_move(*m)
+            .map_err(|e| SgfError::IllegalMove(number + 1, e))?;
+    }
+    Ok(game)
+}
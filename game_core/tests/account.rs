@@ -0,0 +1,48 @@
+use game_core::account::{
+    error_for, validate_avatar, validate_display_name, validate_pass, validate_pass_change,
+    validate_signin, validate_signup, FIELD_CONFIRM, FIELD_LOGIN, FIELD_NEW_PASS, FIELD_OLD_PASS,
+    FIELD_PASS,
+};
+
+#[test]
+fn signup_reports_every_bad_field() {
+    assert!(validate_signup("alice", "secret1", "secret1").is_empty());
+    let errors = validate_signup("al", "short", "other");
+    assert!(error_for(&errors, FIELD_LOGIN).is_some());
+    assert!(error_for(&errors, FIELD_PASS).is_some());
+    assert_eq!(
+        Some("Passwords don't match"),
+        error_for(&errors, FIELD_CONFIRM)
+    );
+    // The length is counted in characters, not bytes
+    assert!(validate_signup("ёжик", "пароль1", "пароль1").is_empty());
+    assert!(validate_pass("onlyletters").is_some());
+    assert!(validate_pass("12345678").is_some());
+}
+
+#[test]
+fn signin_only_needs_both_fields() {
+    assert!(validate_signin("al", "1234").is_empty());
+    let errors = validate_signin("", "");
+    assert!(error_for(&errors, FIELD_LOGIN).is_some());
+    assert!(error_for(&errors, FIELD_PASS).is_some());
+}
+
+#[test]
+fn password_change_and_profile_rules() {
+    assert!(validate_pass_change("secret1", "better2", "better2").is_empty());
+    let errors = validate_pass_change("", "secret1", "secret2");
+    assert!(error_for(&errors, FIELD_OLD_PASS).is_some());
+    assert!(error_for(&errors, FIELD_CONFIRM).is_some());
+    let errors = validate_pass_change("secret1", "secret1", "secret1");
+    assert_eq!(
+        Some("Must differ from the current one"),
+        error_for(&errors, FIELD_NEW_PASS)
+    );
+
+    assert_eq!(None, validate_display_name("Honinbo Shusaku"));
+    assert!(validate_display_name(&"x".repeat(33)).is_some());
+    assert!(validate_display_name("tab\there").is_some());
+    assert_eq!(None, validate_avatar("crane"));
+    assert!(validate_avatar("unicorn").is_some());
+}
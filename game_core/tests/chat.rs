@@ -0,0 +1,39 @@
+use game_core::{
+    chat::to_udp_request,
+    dto::{ChatChannel, ChatRequestDto, UserSessionStepDto},
+};
+use go_udp_request::{chat::chat_message, request::game_request::SessionId};
+
+fn chat_request(text: &str, channel: ChatChannel) -> ChatRequestDto {
+    ChatRequestDto {
+        session: UserSessionStepDto {
+            session_id: "8e2db1b1".to_string(),
+            user_id: 7,
+        },
+        text: text.to_string(),
+        channel,
+        sent_at: "12:30:05".to_string(),
+    }
+}
+
+#[test]
+fn udp_request_keeps_the_message() {
+    let request = to_udp_request("alice", &chat_request(" hi ", ChatChannel::Spectators));
+    assert_eq!(SessionId::from("8e2db1b1"), request.session);
+    assert_eq!("alice", String::from(request.message.author));
+    assert_eq!("hi", String::from(request.message.text));
+    assert_eq!(
+        chat_message::ChatChannel::Spectators,
+        request.message.channel
+    );
+}
+
+#[test]
+fn udp_request_cuts_long_texts() {
+    let text = "x".repeat(chat_message::MAX_TEXT_LEN + 10);
+    let request = to_udp_request("bob", &chat_request(&text, ChatChannel::Players));
+    assert_eq!(
+        chat_message::MAX_TEXT_LEN,
+        String::from(request.message.text).len()
+    );
+}
@@ -0,0 +1,134 @@
+use game_core::{
+    clock::{GameClock, PlayerClock},
+    dto::{ClockDto, PlayerClockDto, TimeControl},
+};
+
+#[test]
+fn absolute_time_flags_at_zero() {
+    let control = TimeControl::Absolute { main_time_secs: 10 };
+    let mut clock = PlayerClock::new(&control);
+    clock.elapse(&control, 9_999);
+    assert!(!clock.flagged);
+    assert_eq!("00:01", clock.format(&control));
+    clock.on_move(&control);
+    assert_eq!(1, clock.main_ms);
+    clock.elapse(&control, 1);
+    assert!(clock.flagged);
+    // A flagged clock stays as it is
+    clock.elapse(&control, 1_000);
+    assert_eq!(0, clock.main_ms);
+}
+
+#[test]
+fn fischer_adds_the_increment_after_each_move() {
+    let control = TimeControl::Fischer {
+        main_time_secs: 60,
+        increment_secs: 5,
+    };
+    let mut clock = PlayerClock::new(&control);
+    clock.elapse(&control, 2_000);
+    clock.on_move(&control);
+    assert_eq!(63_000, clock.main_ms);
+    clock.elapse(&control, 63_000);
+    assert!(clock.flagged);
+    clock.on_move(&control);
+    assert_eq!(0, clock.main_ms);
+}
+
+#[test]
+fn byo_yomi_consumes_periods_and_resets_on_move() {
+    let control = TimeControl::ByoYomi {
+        main_time_secs: 10,
+        period_secs: 30,
+        periods: 3,
+    };
+    let mut clock = PlayerClock::new(&control);
+    // Main time and one full period are used up at once
+    clock.elapse(&control, 10_000 + 30_000 + 5_000);
+    assert!(clock.in_overtime());
+    assert_eq!((2, 25_000), (clock.periods, clock.period_ms));
+    assert_eq!("00:25 (2x)", clock.format(&control));
+
+    // A move in time restores the period but not the lost ones
+    clock.on_move(&control);
+    assert_eq!((2, 30_000), (clock.periods, clock.period_ms));
+
+    clock.elapse(&control, 59_999);
+    assert!(!clock.flagged);
+    assert_eq!((1, 1), (clock.periods, clock.period_ms));
+    clock.elapse(&control, 1);
+    assert!(clock.flagged);
+    assert_eq!((0, 0), (clock.periods, clock.period_ms));
+}
+
+#[test]
+fn canadian_counts_stones_of_the_period() {
+    let control = TimeControl::Canadian {
+        main_time_secs: 0,
+        period_secs: 60,
+        stones: 3,
+    };
+    let mut clock = PlayerClock::new(&control);
+    assert!(clock.in_overtime());
+    clock.elapse(&control, 20_000);
+    clock.on_move(&control);
+    clock.elapse(&control, 20_000);
+    clock.on_move(&control);
+    assert_eq!((1, 20_000), (clock.stones, clock.period_ms));
+    assert_eq!("00:20 /1", clock.format(&control));
+
+    // The last stone of the period starts a new one
+    clock.elapse(&control, 19_000);
+    clock.on_move(&control);
+    assert_eq!((3, 60_000), (clock.stones, clock.period_ms));
+
+    clock.elapse(&control, 60_000);
+    assert!(clock.flagged);
+}
+
+#[test]
+fn game_clock_runs_for_the_player_to_move() {
+    let control = TimeControl::Absolute { main_time_secs: 5 };
+    let mut clock = GameClock::new(control);
+    clock.tick(1_000);
+    assert_eq!(5_000, clock.black.main_ms);
+
+    clock.switch(false);
+    clock.tick(1_000);
+    assert_eq!((4_000, 5_000), (clock.black.main_ms, clock.white.main_ms));
+    clock.switch(true);
+    clock.tick(5_000);
+    assert_eq!(Some(false), clock.flagged());
+}
+
+fn snapshot(main_time_ms: i64, server_time_ms: i64) -> ClockDto {
+    ClockDto {
+        black: PlayerClockDto {
+            main_time_ms,
+            ..Default::default()
+        },
+        white: PlayerClockDto {
+            main_time_ms,
+            ..Default::default()
+        },
+        running: Some(true),
+        server_time_ms,
+    }
+}
+
+#[test]
+fn sync_charges_the_time_since_the_snapshot() {
+    let mut clock = GameClock::new(TimeControl::Absolute { main_time_secs: 60 });
+    // The server clock is 5 s ahead, the response took half of the 200 ms round trip
+    clock.sync(&snapshot(30_000, 10_000), 5_000, 200);
+    assert_eq!(29_900, clock.black.main_ms);
+    assert_eq!(30_000, clock.white.main_ms);
+
+    // A slow response only moves the offset by a quarter of the difference
+    clock.sync(&snapshot(20_000, 20_000), 15_000, 1_000);
+    assert_eq!(19_800, clock.black.main_ms);
+
+    // The snapshot can't be in the future
+    clock.sync(&snapshot(10_000, 40_000), 30_000, 0);
+    assert_eq!(10_000, clock.black.main_ms);
+}
@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+
+use game_core::{
+    credentials::{TokenStore, TokenStoreError},
+    dto::UserTokenDto,
+};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn token() -> UserTokenDto {
+    UserTokenDto {
+        user_id: 7,
+        login: "alice".to_string(),
+        token: "secret-token".to_string(),
+    }
+}
+
+#[test]
+fn remembered_token_is_encrypted_and_cleared() {
+    let dir = temp_dir("token_store_roundtrip");
+    let store = TokenStore::new(&dir.join("token.bin"), &dir.join("secret.bin"));
+    assert!(store.load().unwrap().is_none());
+
+    store.save(&token()).unwrap();
+    let saved = std::fs::read(dir.join("token.bin")).unwrap();
+    assert!(!String::from_utf8_lossy(&saved).contains("secret-token"));
+    let loaded = store.load().unwrap().unwrap();
+    assert_eq!(
+        (7, "alice", "secret-token"),
+        (loaded.user_id, loaded.login.as_str(), loaded.token.as_str())
+    );
+
+    store.clear().unwrap();
+    assert!(store.load().unwrap().is_none());
+    store.clear().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn tampered_token_or_other_secret_is_rejected() {
+    let dir = temp_dir("token_store_tampered");
+    let store = TokenStore::new(&dir.join("token.bin"), &dir.join("secret.bin"));
+    store.save(&token()).unwrap();
+
+    let mut saved = std::fs::read(dir.join("token.bin")).unwrap();
+    let last = saved.len() - 1;
+    saved[last] ^= 1;
+    std::fs::write(dir.join("token.bin"), &saved).unwrap();
+    assert!(matches!(store.load(), Err(TokenStoreError::Corrupted)));
+
+    store.save(&token()).unwrap();
+    let other = TokenStore::new(&dir.join("token.bin"), &dir.join("other_secret.bin"));
+    assert!(matches!(other.load(), Err(TokenStoreError::Corrupted)));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
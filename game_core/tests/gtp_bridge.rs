@@ -0,0 +1,39 @@
+use std::time::{Duration, Instant};
+
+use game_core::gtp_bridge::{EngineSettings, GtpBridge};
+
+#[cfg(unix)]
+#[test]
+fn silent_engine_times_out_the_handshake() {
+    let settings = EngineSettings {
+        path: "sleep".to_string(),
+        args: vec!["3".to_string()],
+    };
+    let started = Instant::now();
+    let result = GtpBridge::spawn_with_timeout(&settings, Duration::from_millis(200));
+    assert!(result.is_err());
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+fn missing_engine_fails_to_spawn() {
+    let settings = EngineSettings {
+        path: "no-such-gtp-engine".to_string(),
+        args: vec![],
+    };
+    assert!(GtpBridge::spawn(&settings).is_err());
+}
+
+#[test]
+fn command_line_keeps_quoted_spaces() {
+    let settings =
+        EngineSettings::from_command_line(r#"  "C:\Go Engines\gnugo.exe"  --mode gtp "" "#)
+            .unwrap();
+    assert_eq!(r"C:\Go Engines\gnugo.exe", settings.path);
+    assert_eq!(vec!["--mode", "gtp", ""], settings.args);
+    assert_eq!(
+        Some(settings.clone()),
+        EngineSettings::from_command_line(&settings.command_line())
+    );
+    assert_eq!(None, EngineSettings::from_command_line("   "));
+}
@@ -0,0 +1,39 @@
+use game_core::lobby::{invite_link, parse_invite, rank_value};
+
+#[test]
+fn kyu_and_dan_ranks_are_ordered() {
+    assert_eq!(Some(-30), rank_value("30k"));
+    assert_eq!(Some(-1), rank_value("1k"));
+    assert_eq!(Some(0), rank_value("1d"));
+    assert_eq!(Some(8), rank_value(" 9D "));
+}
+
+#[test]
+fn unknown_ranks_are_rejected() {
+    for rank in ["", "k", "d", "0k", "-3k", "5p", "5", "abc"] {
+        assert_eq!(None, rank_value(rank), "{:?}", rank);
+    }
+}
+
+#[test]
+fn non_ascii_input_does_not_panic() {
+    // Cyrillic "к" typed with the Russian layout
+    assert_eq!(None, rank_value("5к"));
+    assert_eq!(None, rank_value("к"));
+    assert_eq!(None, rank_value("５k"));
+}
+
+#[test]
+fn invite_password_survives_the_link() {
+    for password in ["a&b", "what?#", "two words", "100%", "пароль", "x=y+z"] {
+        let invite = parse_invite(&invite_link("K7QX2M", Some(password))).unwrap();
+        assert_eq!("K7QX2M", invite.code);
+        assert_eq!(
+            Some(password.to_string()),
+            invite.password,
+            "{:?}",
+            password
+        );
+    }
+    assert_eq!(None, parse_invite("gogame://join/K7QX2M?password=%zz"));
+}
@@ -0,0 +1,96 @@
+use game_core::{
+    dto::{ColorPreference, MatchPreferencesDto, TimeControl},
+    handicap::HANDICAP_KOMI,
+    matchmaking::{self, MatchQueue, QueueEntry, MATCH_TIME_CONTROLS},
+    rating::Rating,
+};
+
+fn entry(user_id: i64, rating: f64, board_size: i32) -> QueueEntry {
+    QueueEntry {
+        user_id,
+        rating: Rating {
+            rating,
+            ..Default::default()
+        },
+        preferences: MatchPreferencesDto {
+            board_size,
+            ..Default::default()
+        },
+        queued_at: user_id,
+    }
+}
+
+#[test]
+fn players_are_paired_on_the_same_board_and_clock() {
+    let mut queue = MatchQueue::default();
+    queue.enqueue(entry(1, 1500.0, 19));
+    queue.enqueue(entry(2, 1500.0, 9));
+    assert_eq!(None, queue.pop_match());
+
+    let mut timed = entry(3, 1500.0, 9);
+    timed.preferences.time_control = MATCH_TIME_CONTROLS[1];
+    queue.enqueue(timed);
+    assert_eq!(None, queue.pop_match());
+
+    queue.enqueue(entry(4, 1500.0, 9));
+    let (first, second) = queue.pop_match().unwrap();
+    assert_eq!((2, 4), (first.user_id, second.user_id));
+    assert_eq!(2, queue.len());
+}
+
+#[test]
+fn rank_range_is_checked_both_ways() {
+    let mut queue = MatchQueue::default();
+    // 1d wants dan players only, 10k accepts anyone
+    let mut strong = entry(1, 2050.0, 19);
+    strong.preferences.min_rank = Some(0);
+    queue.enqueue(strong);
+    queue.enqueue(entry(2, 1050.0, 19));
+    assert_eq!(None, queue.pop_match());
+
+    queue.enqueue(entry(3, 2150.0, 19));
+    let (first, second) = queue.pop_match().unwrap();
+    assert_eq!((1, 3), (first.user_id, second.user_id));
+    assert_eq!(
+        vec![2],
+        (0..4)
+            .filter(|id| queue.get(*id).is_some())
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn queue_again_replaces_preferences_and_cancel_leaves() {
+    let mut queue = MatchQueue::default();
+    queue.enqueue(entry(1, 1500.0, 19));
+    queue.enqueue(entry(1, 1500.0, 9));
+    assert_eq!(1, queue.len());
+    assert_eq!(9, queue.get(1).unwrap().preferences.board_size);
+    // The place in the queue is kept
+    assert_eq!(1, queue.get(1).unwrap().queued_at);
+
+    assert!(queue.cancel(1));
+    assert!(!queue.cancel(1));
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn weaker_player_takes_black_with_handicap() {
+    let even = matchmaking::match_settings(&entry(1, 1510.0, 19), &entry(2, 1590.0, 19));
+    assert_eq!(ColorPreference::Nigiri, even.color);
+    assert_eq!((0, 6.5), (even.handicap, even.komi));
+
+    let mut host = entry(1, 1850.0, 13);
+    host.preferences.time_control = TimeControl::Absolute {
+        main_time_secs: 600,
+    };
+    let settings = matchmaking::match_settings(&host, &entry(2, 1550.0, 13));
+    assert_eq!(ColorPreference::White, settings.color);
+    assert_eq!((3, HANDICAP_KOMI), (settings.handicap, settings.komi));
+    assert_eq!(13, settings.board_size);
+    assert_eq!(host.preferences.time_control, settings.time_control);
+
+    let settings = matchmaking::match_settings(&entry(1, 1450.0, 19), &entry(2, 1550.0, 19));
+    assert_eq!(ColorPreference::Black, settings.color);
+    assert_eq!((0, HANDICAP_KOMI), (settings.handicap, settings.komi));
+}
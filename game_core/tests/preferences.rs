@@ -0,0 +1,53 @@
+use game_core::{
+    api::DEFAULT_HOST,
+    preferences::{BoardTheme, Preferences, StoneSet, MAX_POLL_INTERVAL_MS, MIN_POLL_INTERVAL_MS},
+};
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("{}_{}.json", name, std::process::id()))
+}
+
+#[test]
+fn preferences_survive_a_save_and_load() {
+    let path = temp_path("preferences_roundtrip");
+    let preferences = Preferences {
+        server_url: "http://go.example:9000".to_string(),
+        board_theme: BoardTheme::Dark,
+        stone_set: StoneSet::Flat,
+        show_coordinates: false,
+        confirm_moves: true,
+        sound: false,
+        language: "ru".to_string(),
+        poll_interval_ms: 2000,
+    };
+    preferences.save(&path).unwrap();
+    assert_eq!(preferences, Preferences::load(&path));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn missing_or_broken_values_take_defaults() {
+    let path = temp_path("preferences_partial");
+    std::fs::write(
+        &path,
+        r#"{"board_theme": "light", "poll_interval_ms": 1, "language": "xx", "server_url": " "}"#,
+    )
+    .unwrap();
+    let preferences = Preferences::load(&path);
+    assert_eq!(BoardTheme::Light, preferences.board_theme);
+    assert_eq!(MIN_POLL_INTERVAL_MS, preferences.poll_interval_ms);
+    assert_eq!("en", preferences.language);
+    assert_eq!(DEFAULT_HOST, preferences.server_url);
+    assert!(preferences.show_coordinates);
+
+    std::fs::write(&path, "not json").unwrap();
+    assert_eq!(Preferences::default(), Preferences::load(&path));
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(Preferences::default(), Preferences::load(&path));
+
+    let slow = Preferences {
+        poll_interval_ms: u64::MAX,
+        ..Default::default()
+    };
+    assert_eq!(MAX_POLL_INTERVAL_MS, slow.normalized().poll_interval_ms);
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use game_core::{
+    handicap::{HANDICAP_KOMI, MAX_HANDICAP},
+    lobby,
+    rating::{self, GameOutcome, Rating, RatingPeriod},
+};
+
+fn rating(rating: f64, deviation: f64) -> Rating {
+    Rating {
+        rating,
+        deviation,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn glickman_example_is_reproduced() {
+    let player = rating(1500.0, 200.0);
+    let games = [
+        GameOutcome {
+            opponent: rating(1400.0, 30.0),
+            score: 1.0,
+        },
+        GameOutcome {
+            opponent: rating(1550.0, 100.0),
+            score: 0.0,
+        },
+        GameOutcome {
+            opponent: rating(1700.0, 300.0),
+            score: 0.0,
+        },
+    ];
+    let updated = player.update(&games);
+    assert!((updated.rating - 1464.06).abs() < 0.01, "{:?}", updated);
+    assert!((updated.deviation - 151.52).abs() < 0.01, "{:?}", updated);
+    assert!(
+        (updated.volatility - 0.05999).abs() < 0.00001,
+        "{:?}",
+        updated
+    );
+}
+
+#[test]
+fn deviation_grows_without_games_up_to_the_default() {
+    let player = rating(1700.0, 50.0);
+    let idle = player.update(&[]);
+    assert_eq!(1700.0, idle.rating);
+    assert!(idle.deviation > 50.0);
+    assert_eq!(
+        rating::DEFAULT_DEVIATION,
+        Rating::default().update(&[]).deviation
+    );
+}
+
+#[test]
+fn rating_period_updates_both_players_from_the_start_ratings() {
+    let mut ratings = HashMap::from([(1, Rating::default()), (3, rating(1800.0, 60.0))]);
+    let mut period = RatingPeriod::default();
+    assert!(period.is_empty());
+    period.record(1, 2, 1.0);
+    period.record(2, 1, 0.5);
+    period.close(&mut ratings);
+
+    assert!(ratings[&1].rating > rating::DEFAULT_RATING);
+    assert!(ratings[&2].rating < rating::DEFAULT_RATING);
+    assert!(ratings[&1].deviation < rating::DEFAULT_DEVIATION);
+    // Idle players only become less certain
+    assert_eq!(1800.0, ratings[&3].rating);
+    assert!(ratings[&3].deviation > 60.0);
+}
+
+#[test]
+fn ranks_match_the_lobby_rank_values() {
+    for value in [-30, -5, -1, 0, 3, 8] {
+        let rating = rating(
+            rating::FIRST_DAN_RATING + value as f64 * rating::RANK_POINTS + 50.0,
+            100.0,
+        );
+        assert_eq!(value, rating.rank_value());
+        assert_eq!(Some(value), lobby::rank_value(&rating.rank()));
+    }
+    assert_eq!("9d", rating(5000.0, 100.0).rank());
+}
+
+#[test]
+fn handicap_follows_the_rank_difference() {
+    let komi = 6.5;
+    let even = rating::suggest_handicap(&rating(1500.0, 50.0), &rating(1550.0, 50.0), komi);
+    assert_eq!((0, komi), (even.stones, even.komi));
+
+    let one_rank = rating::suggest_handicap(&rating(1450.0, 50.0), &rating(1550.0, 50.0), komi);
+    assert_eq!((0, HANDICAP_KOMI), (one_rank.stones, one_rank.komi));
+
+    let four_ranks = rating::suggest_handicap(&rating(1150.0, 50.0), &rating(1550.0, 50.0), komi);
+    assert_eq!((4, HANDICAP_KOMI), (four_ranks.stones, four_ranks.komi));
+
+    let capped = rating::suggest_handicap(&rating(100.0, 50.0), &rating(2500.0, 50.0), komi);
+    assert_eq!(MAX_HANDICAP, capped.stones);
+
+    // The stronger player taking black gets no handicap
+    let reversed = rating::suggest_handicap(&rating(1900.0, 50.0), &rating(1500.0, 50.0), komi);
+    assert_eq!((0, komi), (reversed.stones, reversed.komi));
+}
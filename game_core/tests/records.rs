@@ -0,0 +1,116 @@
+use game_core::{
+    review::GameTree,
+    rules::{GoGame, Move, MoveError},
+    sgf,
+};
+
+#[test]
+fn sgf_round_trip_keeps_moves_and_handicap() {
+    let mut game = GoGame::with_handicap(9, 0.5, &[(2, 6), (6, 2)]);
+    game.play((4, 4)).unwrap();
+    game.pass().unwrap();
+    let record = sgf::parse_sgf(&sgf::to_sgf(&game)).unwrap();
+    let loaded = sgf::load_game(&record).unwrap();
+    assert_eq!(game.moves(), loaded.moves());
+    assert_eq!(game.handicap(), loaded.handicap());
+    assert_eq!(game.board(), loaded.board());
+}
+
+#[test]
+fn review_tree_keeps_variations_and_comments() {
+    let moves = [
+        Move::Play {
+            point: (2, 2),
+            color: true,
+        },
+        Move::Play {
+            point: (6, 6),
+            color: false,
+        },
+    ];
+    let mut tree = GameTree::from_moves(9, 6.5, &[], &moves);
+    assert_eq!(2, tree.line_length());
+    tree.jump(1);
+    tree.play(Move::Play {
+        point: (2, 6),
+        color: false,
+    })
+    .unwrap();
+    tree.set_comment("better");
+    assert_eq!((1, 2), tree.variation());
+    assert_eq!(2, tree.line_length());
+
+    let mut loaded = GameTree::from_sgf(&sgf::parse_tree(&tree.to_sgf()).unwrap()).unwrap();
+    loaded.jump(2);
+    assert_eq!((0, 2), loaded.variation());
+    assert!(loaded.next_variation());
+    assert_eq!("better", loaded.current().comment);
+    assert_eq!(Some(false), loaded.game().board()[2][6]);
+}
+
+#[test]
+fn board_size_out_of_the_sgf_range_is_rejected() {
+    for size in ["0", "53", "100000", "-1"] {
+        let content = format!("(;GM[1]SZ[{}];B[aa])", size);
+        assert!(sgf::parse_sgf(&content).is_err(), "{}", size);
+        assert!(sgf::record_from_tree(&sgf::parse_tree(&content).unwrap()).is_err());
+    }
+    assert_eq!(52, sgf::parse_sgf("(;SZ[52])").unwrap().size);
+}
+
+#[test]
+fn tt_is_a_pass_up_to_19x19() {
+    let record = sgf::parse_sgf("(;FF[3]SZ[19];B[dd];W[tt];B[])").unwrap();
+    assert_eq!(
+        vec![
+            Move::Play {
+                point: (3, 3),
+                color: true
+            },
+            Move::Pass { color: false },
+            Move::Pass { color: true },
+        ],
+        record.moves
+    );
+    // On bigger boards "tt" is the point of the 20th line
+    assert_eq!(Ok(Some((19, 19))), sgf::point_from_sgf("tt", 21));
+    assert!(sgf::point_from_sgf("ua", 19).is_err());
+}
+
+#[test]
+fn big_board_coordinates_use_capital_letters() {
+    let point = (51, 26);
+    assert_eq!("AZ", sgf::point_to_sgf(point));
+    assert_eq!(Ok(Some(point)), sgf::point_from_sgf("AZ", 52));
+
+    let mut game = GoGame::new(30, 6.5);
+    game.play((28, 27)).unwrap();
+    game.play((0, 29)).unwrap();
+    let loaded = sgf::load_game(&sgf::parse_sgf(&sgf::to_sgf(&game)).unwrap()).unwrap();
+    assert_eq!(game.moves(), loaded.moves());
+}
+
+#[test]
+fn moves_of_the_wrong_color_are_rejected() {
+    let content = "(;GM[1]FF[4]SZ[9];B[cc];B[gg])";
+    let record = sgf::parse_sgf(content).unwrap();
+    assert_eq!(
+        Err(sgf::SgfError::IllegalMove(2, MoveError::WrongColor)),
+        sgf::load_game(&record).map(|_| ())
+    );
+    let root = sgf::parse_tree(content).unwrap();
+    assert_eq!(
+        Err(sgf::SgfError::IllegalMove(2, MoveError::WrongColor)),
+        GameTree::from_sgf(&root).map(|_| ())
+    );
+}
+
+#[test]
+fn review_tree_keeps_the_player_names() {
+    let root = sgf::parse_tree("(;GM[1]FF[4]SZ[9]PB[Honinbo Shusaku]PW[];B[cc])").unwrap();
+    let tree = GameTree::from_sgf(&root).unwrap();
+    assert_eq!(Some("Honinbo Shusaku"), tree.player_name(true));
+    assert_eq!(None, tree.player_name(false));
+    let reloaded = GameTree::from_sgf(&sgf::parse_tree(&tree.to_sgf()).unwrap()).unwrap();
+    assert_eq!(Some("Honinbo Shusaku"), reloaded.player_name(true));
+}
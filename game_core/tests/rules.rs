@@ -0,0 +1,97 @@
+use std::collections::HashSet;
+
+use game_core::{
+    handicap,
+    rules::{GoGame, Move, MoveError},
+    scoring,
+};
+
+fn play_all(game: &mut GoGame, points: &[(usize, usize)]) {
+    for point in points {
+        game.play(*point).unwrap();
+    }
+}
+
+#[test]
+fn captures_are_removed_and_counted() {
+    let mut game = GoGame::new(9, 6.5);
+    play_all(&mut game, &[(0, 1), (0, 0)]);
+    assert_eq!(vec![(0, 0)], game.play((1, 0)).unwrap());
+    assert_eq!(None, game.board()[0][0]);
+    assert_eq!(1, game.captures().black);
+}
+
+#[test]
+fn illegal_moves_are_rejected() {
+    let mut game = GoGame::new(9, 6.5);
+    play_all(&mut game, &[(0, 1), (8, 8), (1, 0)]);
+    assert_eq!(Err(MoveError::Suicide), game.is_legal((0, 0)));
+    assert_eq!(Err(MoveError::Occupied), game.is_legal((0, 1)));
+    assert_eq!(Err(MoveError::OutOfBoard), game.is_legal((9, 0)));
+}
+
+#[test]
+fn ko_cannot_be_retaken_at_once() {
+    let mut game = GoGame::new(9, 6.5);
+    play_all(
+        &mut game,
+        &[
+            (0, 1),
+            (0, 2),
+            (1, 0),
+            (1, 3),
+            (2, 1),
+            (2, 2),
+            (8, 8),
+            (1, 1),
+        ],
+    );
+    assert_eq!(vec![(1, 1)], game.play((1, 2)).unwrap());
+    assert_eq!(Err(MoveError::Ko), game.is_legal((1, 1)));
+}
+
+#[test]
+fn two_passes_end_the_game_and_undo_reopens_it() {
+    let mut game = GoGame::new(9, 6.5);
+    game.pass().unwrap();
+    game.pass().unwrap();
+    assert!(game.is_over());
+    assert_eq!(Err(MoveError::GameOver), game.pass());
+    assert_eq!(Some(Move::Pass { color: false }), game.undo());
+    assert!(!game.is_over());
+}
+
+#[test]
+fn handicap_stones_give_white_the_first_move() {
+    let points = handicap::fixed_handicap_points(19, 4).unwrap();
+    let game = GoGame::with_handicap(19, handicap::handicap_komi(6.5, 4), &points);
+    assert!(!game.to_move());
+    assert_eq!(0.5, game.komi());
+    assert!(points
+        .iter()
+        .all(|(row, col)| game.board()[*row][*col] == Some(true)));
+}
+
+#[test]
+fn score_counts_territory_and_dead_stones() {
+    let mut game = GoGame::new(5, 0.5);
+    // Black walls off the two left columns, white the two right ones
+    play_all(
+        &mut game,
+        &[
+            (0, 1),
+            (0, 3),
+            (1, 1),
+            (1, 3),
+            (2, 1),
+            (2, 3),
+            (3, 1),
+            (3, 3),
+            (4, 1),
+            (4, 3),
+        ],
+    );
+    assert_eq!((5.0, 5.5), game.score(&HashSet::new()));
+    let estimate = scoring::estimate(game.board(), &HashSet::from([(0, 3)]));
+    assert_eq!(1, estimate.black_prisoners);
+}
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use game_core::{
+    dto::{UserSessionDto, UserStatusDto, UserTokenDto},
+    session::{self, GameSession, WAITING_PLAYER},
+};
+
+fn user(user_id: i64, login: &str) -> UserStatusDto {
+    UserStatusDto {
+        login: login.to_string(),
+        is_online: true,
+        rank: None,
+        user_id: Some(user_id),
+        rating: None,
+    }
+}
+
+fn session(user2: Option<UserStatusDto>) -> UserSessionDto {
+    UserSessionDto {
+        user1: user(1, "alice"),
+        user2,
+        session_id: "s1".to_string(),
+        settings: None,
+        join_code: None,
+    }
+}
+
+fn token(user_id: i64, login: &str) -> UserTokenDto {
+    UserTokenDto {
+        user_id,
+        login: login.to_string(),
+        token: "t".to_string(),
+    }
+}
+
+#[test]
+fn player_names_follow_the_user_color() {
+    let mut game = GameSession::new(Some(session(None)), Some(token(1, "alice")));
+    assert_eq!(WAITING_PLAYER, game.player_name(true));
+    assert_eq!(WAITING_PLAYER, game.opponent_nick());
+
+    game.session = Some(session(Some(user(2, "bob"))));
+    game.set_colors(&HashMap::from([(1, false), (2, true)]));
+    assert_eq!(Some(false), game.user_color);
+    assert_eq!(
+        "bob: 3",
+        session::score_label_text(&game.player_name(true), 3)
+    );
+    assert_eq!(
+        "alice: 0",
+        session::score_label_text(&game.player_name(false), 0)
+    );
+}
+
+#[test]
+fn spectators_see_logins_by_color() {
+    let mut game = GameSession::new(Some(session(Some(user(2, "bob")))), None);
+    game.spectator = true;
+    assert_eq!("Black", game.player_name(true));
+    game.set_colors(&HashMap::from([(1, true), (2, false)]));
+    assert_eq!("alice", game.player_name(true));
+    assert_eq!("bob", game.player_name(false));
+    assert_eq!(None, game.user_color);
+}
+
+#[test]
+fn session_requests_carry_the_ids() {
+    let game = GameSession::new(Some(session(None)), Some(token(7, "carol")));
+    let step = game.session_step();
+    assert_eq!(("s1", 7), (step.session_id.as_str(), step.user_id));
+    assert_eq!(Some("s1".to_string()), game.session_request().session_id);
+    assert_eq!(-1, GameSession::default().user_id());
+}
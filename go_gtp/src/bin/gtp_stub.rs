@@ -0,0 +1,142 @@
+//! A tiny GTP engine used as a local stand-in for real engines in tests.
+//! It has no Go rules: `genmove` answers with the first empty point,
+//! "pass" on a full board and "resign" when the komi is negative.
+
+use std::{
+    collections::HashSet,
+    io::{self, BufRead, Write},
+};
+
+use go_gtp::vertex::Vertex;
+
+const COMMANDS: [&str; 13] = [
+    "protocol_version",
+    "name",
+    "version",
+    "known_command",
+    "list_commands",
+    "boardsize",
+    "clear_board",
+    "komi",
+    "play",
+    "genmove",
+    "set_free_handicap",
+    "undo",
+    "quit",
+];
+
+struct Stub {
+    size: usize,
+    komi: f32,
+    stones: Vec<(usize, usize)>,
+}
+
+impl Stub {
+    fn handle(&mut self, command: &str, args: &[&str]) -> Result<String, String> {
+        match command {
+            "protocol_version" => Ok("2".to_string()),
+            "name" => Ok("gtp_stub".to_string()),
+            "version" => Ok("0.1.0".to_string()),
+            "known_command" => Ok(COMMANDS.contains(args.first().unwrap_or(&"")).to_string()),
+            "list_commands" => Ok(COMMANDS.join("\n")),
+            "boardsize" => {
+                self.size = args
+                    .first()
+                    .and_then(|s| s.parse().ok())
+                    .filter(|s| (2..=25).contains(s))
+                    .ok_or("unacceptable size")?;
+                self.stones.clear();
+                Ok(String::new())
+            }
+            "clear_board" => {
+                self.stones.clear();
+                Ok(String::new())
+            }
+            "komi" => {
+                self.komi = args
+                    .first()
+                    .and_then(|s| s.parse().ok())
+                    .ok_or("syntax error")?;
+                Ok(String::new())
+            }
+            "play" => match Vertex::from_gtp(args.get(1).unwrap_or(&""), self.size) {
+                Ok(Vertex::Point(row, col)) if !self.stones.contains(&(row, col)) => {
+                    self.stones.push((row, col));
+                    Ok(String::new())
+                }
+                Ok(Vertex::Pass) => Ok(String::new()),
+                _ => Err("illegal move".to_string()),
+            },
+            "genmove" => {
+                if self.komi < 0.0 {
+                    return Ok("resign".to_string());
+                }
+                let occupied: HashSet<&(usize, usize)> = self.stones.iter().collect();
+                let vertex = (0..self.size)
+                    .flat_map(|row| (0..self.size).map(move |col| (row, col)))
+                    .find(|point| !occupied.contains(point))
+                    .map(|(row, col)| Vertex::Point(row, col))
+                    .unwrap_or(Vertex::Pass);
+                if let Vertex::Point(row, col) = vertex {
+                    self.stones.push((row, col));
+                }
+                vertex.to_gtp(self.size).map_err(|e| e.to_string())
+            }
+            "set_free_handicap" => {
+                if args.len() < 2 || !self.stones.is_empty() {
+                    return Err("bad handicap".to_string());
+                }
+                for arg in args {
+                    match Vertex::from_gtp(arg, self.size) {
+                        Ok(Vertex::Point(row, col)) if !self.stones.contains(&(row, col)) => {
+                            self.stones.push((row, col))
+                        }
+                        _ => return Err("bad vertex list".to_string()),
+                    }
+                }
+                Ok(String::new())
+            }
+            "undo" => self
+                .stones
+                .pop()
+                .map(|_| String::new())
+                .ok_or("cannot undo".to_string()),
+            "quit" => Ok(String::new()),
+            _ => Err("unknown command".to_string()),
+        }
+    }
+}
+
+fn main() {
+    let mut stub = Stub {
+        size: 19,
+        komi: 0.0,
+        stones: vec![],
+    };
+    let stdout = io::stdout();
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else { break };
+        let mut words: Vec<&str> = line.split_whitespace().collect();
+        if words.is_empty() {
+            continue;
+        }
+        let id = if words[0].chars().all(|c| c.is_ascii_digit()) {
+            words.remove(0)
+        } else {
+            ""
+        };
+        let Some((command, args)) = words.split_first() else {
+            continue;
+        };
+        let (status, text) = match stub.handle(command, args) {
+            Ok(text) => ("=", text),
+            Err(text) => ("?", text),
+        };
+        let mut out = stdout.lock();
+        let _ = write!(out, "{}{} {}\n\n", status, id, text);
+        let _ = out.flush();
+        if *command == "quit" {
+            break;
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use std::{
+    fmt::Display,
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use crate::vertex::{Vertex, VertexError};
+
+#[derive(Debug)]
+pub enum GtpError {
+    Io(io::Error),
+    /// The engine answered with `?` and this message
+    Failure(String),
+    Protocol(String),
+}
+
+impl Display for GtpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GtpError::Io(e) => write!(f, "GTP engine io error: {}", e),
+            GtpError::Failure(message) => write!(f, "GTP engine failure: {}", message),
+            GtpError::Protocol(message) => write!(f, "GTP protocol error: {}", message),
+        }
+    }
+}
+
+impl From<io::Error> for GtpError {
+    fn from(value: io::Error) -> Self {
+        GtpError::Io(value)
+    }
+}
+
+impl From<VertexError> for GtpError {
+    fn from(value: VertexError) -> Self {
+        GtpError::Protocol(value.to_string())
+    }
+}
+
+/// The answer to `genmove`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GtpMove {
+    Play(Vertex),
+    Resign,
+}
+
+fn color_to_gtp(color: bool) -> &'static str {
+    if color {
+        "black"
+    } else {
+        "white"
+    }
+}
+
+/// A GTP speaking engine process driven over its stdin/stdout.
+/// The process is asked to quit and then killed when the engine is dropped.
+pub struct GtpEngine {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    size: usize,
+    next_id: u32,
+}
+
+impl GtpEngine {
+    pub fn spawn(path: &Path, args: &[String]) -> Result<GtpEngine, GtpError> {
+        let mut child = Command::new(path)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or(GtpError::Protocol("engine stdin is closed".to_string()))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or(GtpError::Protocol("engine stdout is closed".to_string()))?;
+        Ok(GtpEngine {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout),
+            size: 19,
+            next_id: 1,
+        })
+    }
+
+    /// Send a command and wait for its response, the response text has no `=id` prefix
+    pub fn command(&mut self, command: &str) -> Result<String, GtpError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        writeln!(self.stdin, "{} {}", id, command)?;
+        self.stdin.flush()?;
+        let mut response = String::new();
+        loop {
+            let mut line = String::new();
+            if self.stdout.read_line(&mut line)? == 0 {
+                return Err(GtpError::Protocol("engine closed the output".to_string()));
+            }
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line.is_empty() {
+                if response.is_empty() {
+                    continue;
+                }
+                break;
+            }
+            if !response.is_empty() {
+                response.push('\n');
+            }
+            response.push_str(line);
+        }
+        let text = |rest: &str| {
+            rest.trim_start_matches(|c: char| c.is_ascii_digit())
+                .trim()
+                .to_string()
+        };
+        if let Some(rest) = response.strip_prefix('=') {
+            Ok(text(rest))
+        } else if let Some(rest) = response.strip_prefix('?') {
+            Err(GtpError::Failure(text(rest)))
+        } else {
+            Err(GtpError::Protocol(format!(
+                "unexpected response: {}",
+                response
+            )))
+        }
+    }
+
+    pub fn name(&mut self) -> Result<String, GtpError> {
+        self.command("name")
+    }
+
+    pub fn boardsize(&mut self, size: usize) -> Result<(), GtpError> {
+        self.command(&format!("boardsize {}", size))?;
+        self.size = size;
+        Ok(())
+    }
+
+    pub fn clear_board(&mut self) -> Result<(), GtpError> {
+        self.command("clear_board").map(|_| ())
+    }
+
+    pub fn komi(&mut self, komi: f32) -> Result<(), GtpError> {
+        self.command(&format!("komi {}", komi)).map(|_| ())
+    }
+
+    pub fn play(&mut self, color: bool, vertex: Vertex) -> Result<(), GtpError> {
+        let command = format!("play {} {}", color_to_gtp(color), vertex.to_gtp(self.size)?);
+        self.command(&command).map(|_| ())
+    }
+
+    pub fn genmove(&mut self, color: bool) -> Result<GtpMove, GtpError> {
+        let response = self.command(&format!("genmove {}", color_to_gtp(color)))?;
+        if response.eq_ignore_ascii_case("resign") {
+            return Ok(GtpMove::Resign);
+        }
+        Ok(GtpMove::Play(Vertex::from_gtp(&response, self.size)?))
+    }
+
+    /// Put black handicap stones on an empty board, white moves next
+    pub fn set_free_handicap(&mut self, stones: &[Vertex]) -> Result<(), GtpError> {
+        let vertices = stones
+            .iter()
+            .map(|v| v.to_gtp(self.size))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.command(&format!("set_free_handicap {}", vertices.join(" ")))
+            .map(|_| ())
+    }
+
+    pub fn undo(&mut self) -> Result<(), GtpError> {
+        self.command("undo").map(|_| ())
+    }
+
+    /// Reset the engine to a new position: size, komi, handicap stones and the moves played so far
+    pub fn setup(
+        &mut self,
+        size: usize,
+        komi: f32,
+        handicap: &[Vertex],
+        moves: &[(bool, Vertex)],
+    ) -> Result<(), GtpError> {
+        self.boardsize(size)?;
+        self.clear_board()?;
+        self.komi(komi)?;
+        if !handicap.is_empty() {
+            self.set_free_handicap(handicap)?;
+        }
+        for (color, vertex) in moves {
+            self.play(*color, *vertex)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for GtpEngine {
+    fn drop(&mut self) {
+        let _ = writeln!(self.stdin, "quit");
+        let _ = self.stdin.flush();
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,65 @@
+use std::fmt::Display;
+
+/// GTP column letters, "I" is skipped to avoid confusion with "J"
+pub const COLUMNS: &str = "ABCDEFGHJKLMNOPQRSTUVWXYZ";
+
+/// The biggest board GTP vertices can address
+pub const MAX_GTP_SIZE: usize = COLUMNS.len();
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Vertex {
+    /// `(row, col)` counted from the top left corner
+    Point(usize, usize),
+    Pass,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VertexError(pub String);
+
+impl Display for VertexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid vertex: {}", self.0)
+    }
+}
+
+impl Vertex {
+    /// GTP vertex like "D4" where row 1 is the bottom line, an error outside of the board
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use go_gtp::vertex::Vertex;
+    ///
+    /// assert_eq!(Ok("A19".to_string()), Vertex::Point(0, 0).to_gtp(19));
+    /// assert_eq!(Ok("J1".to_string()), Vertex::Point(18, 8).to_gtp(19));
+    /// assert_eq!(Ok("pass".to_string()), Vertex::Pass.to_gtp(19));
+    /// assert!(Vertex::Point(0, 25).to_gtp(26).is_err());
+    /// ```
+    pub fn to_gtp(&self, size: usize) -> Result<String, VertexError> {
+        match self {
+            Vertex::Point(row, col) => match COLUMNS.as_bytes().get(*col) {
+                Some(letter) if *row < size && *col < size => {
+                    Ok(format!("{}{}", *letter as char, size - row))
+                }
+                _ => Err(VertexError(format!("{:?} on {}x{}", self, size, size))),
+            },
+            Vertex::Pass => Ok("pass".to_string()),
+        }
+    }
+
+    pub fn from_gtp(value: &str, size: usize) -> Result<Vertex, VertexError> {
+        let value = value.trim().to_uppercase();
+        if value == "PASS" {
+            return Ok(Vertex::Pass);
+        }
+        let error = || VertexError(value.clone());
+        let mut chars = value.chars();
+        let letter = chars.next().ok_or_else(error)?;
+        let col = COLUMNS.find(letter).ok_or_else(error)?;
+        let number: usize = chars.as_str().parse().map_err(|_| error())?;
+        if col >= size || number == 0 || number > size {
+            return Err(error());
+        }
+        Ok(Vertex::Point(size - number, col))
+    }
+}
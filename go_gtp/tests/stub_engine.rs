@@ -0,0 +1,82 @@
+use std::path::Path;
+
+use go_gtp::{
+    engine::{GtpEngine, GtpError, GtpMove},
+    vertex::Vertex,
+};
+
+fn spawn_stub() -> GtpEngine {
+    GtpEngine::spawn(Path::new(env!("CARGO_BIN_EXE_gtp_stub")), &[]).unwrap()
+}
+
+#[test]
+fn stub_engine_answers_name_and_setup() {
+    let mut engine = spawn_stub();
+    assert_eq!("gtp_stub", engine.name().unwrap());
+    engine
+        .setup(
+            9,
+            6.5,
+            &[],
+            &[(true, Vertex::Point(0, 0)), (false, Vertex::Pass)],
+        )
+        .unwrap();
+    assert_eq!(
+        GtpMove::Play(Vertex::Point(0, 1)),
+        engine.genmove(true).unwrap()
+    );
+}
+
+#[test]
+fn stub_engine_reports_failures() {
+    let mut engine = spawn_stub();
+    engine.boardsize(9).unwrap();
+    engine.play(true, Vertex::Point(4, 4)).unwrap();
+    match engine.play(false, Vertex::Point(4, 4)) {
+        Err(GtpError::Failure(message)) => assert_eq!("illegal move", message),
+        other => panic!("unexpected response {:?}", other),
+    }
+    assert!(matches!(
+        engine.command("unknown_command"),
+        Err(GtpError::Failure(_))
+    ));
+}
+
+#[test]
+fn stub_engine_resigns_and_undoes() {
+    let mut engine = spawn_stub();
+    engine.setup(9, -1.0, &[], &[]).unwrap();
+    assert_eq!(GtpMove::Resign, engine.genmove(false).unwrap());
+    engine.play(true, Vertex::Point(2, 2)).unwrap();
+    engine.undo().unwrap();
+    assert!(engine.undo().is_err());
+}
+
+#[test]
+fn stub_engine_accepts_free_handicap() {
+    let mut engine = spawn_stub();
+    let stones = [Vertex::Point(0, 0), Vertex::Point(0, 1)];
+    engine.setup(9, 0.5, &stones, &[]).unwrap();
+    assert_eq!(
+        GtpMove::Play(Vertex::Point(0, 2)),
+        engine.genmove(false).unwrap()
+    );
+    assert!(engine.set_free_handicap(&[Vertex::Point(4, 4)]).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn non_ascii_response_is_a_protocol_error() {
+    let script = "read command; printf 'é name\\n\\n'; cat > /dev/null".to_string();
+    let mut engine = GtpEngine::spawn(Path::new("sh"), &["-c".to_string(), script]).unwrap();
+    assert!(matches!(engine.name(), Err(GtpError::Protocol(_))));
+}
+
+#[test]
+fn vertex_beyond_the_gtp_columns_is_an_error() {
+    let mut engine = spawn_stub();
+    assert!(matches!(
+        engine.play(true, Vertex::Point(0, 25)),
+        Err(GtpError::Protocol(_))
+    ));
+}
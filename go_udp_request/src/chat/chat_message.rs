@@ -0,0 +1,58 @@
+use std::fmt::Display;
+
+/// Longer texts are cut to keep a message in one datagram
+pub const MAX_TEXT_LEN: usize = 500;
+
+#[derive(Debug, PartialEq)]
+pub struct Author(String);
+
+impl From<&str> for Author {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl From<Author> for String {
+    fn from(value: Author) -> Self {
+        value.0
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MessageText(String);
+
+impl From<&str> for MessageText {
+    fn from(value: &str) -> Self {
+        Self(value.trim().chars().take(MAX_TEXT_LEN).collect())
+    }
+}
+
+impl From<MessageText> for String {
+    fn from(value: MessageText) -> Self {
+        value.0
+    }
+}
+
+/// Players talk to each other, spectators have their own channel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChatChannel {
+    Players,
+    Spectators,
+}
+
+impl Display for ChatChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let channel = match self {
+            ChatChannel::Players => "players",
+            ChatChannel::Spectators => "spectators",
+        };
+        write!(f, "{}", channel)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ChatMessage {
+    pub author: Author,
+    pub text: MessageText,
+    pub channel: ChatChannel,
+}
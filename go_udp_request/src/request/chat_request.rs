@@ -0,0 +1,59 @@
+use crate::{chat::chat_message::*, request::game_request::SessionId};
+
+#[derive(Debug, PartialEq)]
+pub struct ChatRequest {
+    pub message: ChatMessage,
+    pub session: SessionId,
+}
+
+impl ChatRequest {
+    pub fn builder() -> ChatRequestBuilder {
+        ChatRequestBuilder::default()
+    }
+}
+
+#[derive(Default)]
+pub struct ChatRequestBuilder {
+    message: Option<ChatMessage>,
+    session: SessionId,
+}
+
+/// A chat message sent to the players or the spectators of a game
+///
+/// # Examples
+///
+/// ```
+/// use go_udp_request::chat::chat_message::{ChatChannel, ChatMessage};
+/// use go_udp_request::request::chat_request::ChatRequest;
+///
+/// let message = ChatMessage {
+///     author: "alice".into(),
+///     text: "  good game  ".into(),
+///     channel: ChatChannel::Players,
+/// };
+/// let chat_request = ChatRequest::builder()
+///     .message(message)
+///     .session("8e2db1b1".into())
+///     .build();
+/// assert_eq!("alice", String::from(chat_request.message.author));
+/// assert_eq!("good game", String::from(chat_request.message.text));
+/// assert_eq!("players", chat_request.message.channel.to_string());
+/// ```
+impl ChatRequestBuilder {
+    pub fn message(mut self, message: ChatMessage) -> ChatRequestBuilder {
+        self.message = Some(message);
+        self
+    }
+
+    pub fn session(mut self, session: SessionId) -> ChatRequestBuilder {
+        self.session = session;
+        self
+    }
+
+    pub fn build(self) -> ChatRequest {
+        ChatRequest {
+            message: self.message.unwrap(),
+            session: self.session,
+        }
+    }
+}
@@ -9,6 +9,12 @@ impl Default for SessionId {
     }
 }
 
+impl From<&str> for SessionId {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct GameRequest {
     pub player_step: PlayerStep,
@@ -1 +1,3 @@
+pub mod chat_request;
 pub mod game_request;
+pub mod undo_request;
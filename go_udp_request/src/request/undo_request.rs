@@ -0,0 +1,80 @@
+use crate::{request::game_request::SessionId, step::game_step::PlayerColor};
+
+/// Takeback negotiation: one player asks, the opponent accepts or declines
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UndoAction {
+    Request,
+    Accept,
+    Decline,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UndoRequest {
+    pub action: UndoAction,
+    /// Color of the player sending the message
+    pub color: PlayerColor,
+    /// Number of moves kept on the board after the takeback
+    pub move_number: u32,
+    pub session: SessionId,
+}
+
+impl UndoRequest {
+    pub fn builder() -> UndoRequestBuilder {
+        UndoRequestBuilder::default()
+    }
+}
+
+#[derive(Default)]
+pub struct UndoRequestBuilder {
+    action: Option<UndoAction>,
+    color: Option<PlayerColor>,
+    move_number: u32,
+    session: SessionId,
+}
+
+/// A takeback request or answer
+///
+/// # Examples
+///
+/// ```
+/// use go_udp_request::request::undo_request::{UndoAction, UndoRequest};
+///
+/// let undo_request = UndoRequest::builder()
+///     .action(UndoAction::Request)
+///     .color(true.into())
+///     .move_number(41)
+///     .build();
+/// assert_eq!(UndoAction::Request, undo_request.action);
+/// assert_eq!("black", undo_request.color.to_string());
+/// assert_eq!(41, undo_request.move_number);
+/// ```
+impl UndoRequestBuilder {
+    pub fn action(mut self, action: UndoAction) -> UndoRequestBuilder {
+        self.action = Some(action);
+        self
+    }
+
+    pub fn color(mut self, color: PlayerColor) -> UndoRequestBuilder {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn move_number(mut self, move_number: u32) -> UndoRequestBuilder {
+        self.move_number = move_number;
+        self
+    }
+
+    pub fn session(mut self, session: SessionId) -> UndoRequestBuilder {
+        self.session = session;
+        self
+    }
+
+    pub fn build(self) -> UndoRequest {
+        UndoRequest {
+            action: self.action.unwrap(),
+            color: self.color.unwrap(),
+            move_number: self.move_number,
+            session: self.session,
+        }
+    }
+}
@@ -0,0 +1,6 @@
+//! An in-memory stand-in for the game backend, used to run the client
+//! and the integration tests without the real server.
+
+pub mod scenario;
+pub mod server;
+pub mod store;
@@ -0,0 +1,36 @@
+//! Usage: `mock_backend [--port 8080] [--scenario scenario.json]`
+
+use std::{env, path::Path, process};
+
+use mock_backend::{scenario::Scenario, server::MockServer};
+
+fn main() {
+    let mut port = "8080".to_string();
+    let mut scenario = Scenario::default();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--port", Some(value)) => port = value,
+            ("--scenario", Some(path)) => match Scenario::load(Path::new(&path)) {
+                Ok(loaded) => scenario = loaded,
+                Err(e) => {
+                    eprintln!("Failed to load the scenario {}: {}", path, e);
+                    process::exit(1);
+                }
+            },
+            _ => {
+                eprintln!("Usage: mock_backend [--port 8080] [--scenario scenario.json]");
+                process::exit(2);
+            }
+        }
+    }
+    let server = match MockServer::bind(&format!("127.0.0.1:{}", port), scenario) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("Failed to listen on port {}: {}", port, e);
+            process::exit(1);
+        }
+    };
+    println!("Mock backend listening on {:?}", server.local_addr());
+    server.run();
+}
@@ -0,0 +1,73 @@
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+/// Scripted behavior of the mock server, loaded from a JSON file.
+/// The default scenario is a plain backend: nobody joins and nothing fails.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scenario {
+    /// Delay before every response
+    pub latency_ms: u64,
+    /// A bot joining every new session and answering the moves
+    pub opponent: Option<OpponentScript>,
+    /// Responses replaced with errors, the first matching rule wins
+    pub failures: Vec<FailureRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpponentScript {
+    pub login: String,
+    /// Seconds after the session is created, checked on every request
+    pub join_after_secs: u64,
+    /// Points played in order, then the first legal point. The bot passes when none is left.
+    pub moves: Vec<(usize, usize)>,
+}
+
+impl Default for OpponentScript {
+    fn default() -> Self {
+        Self {
+            login: "mock_opponent".to_string(),
+            join_after_secs: 0,
+            moves: vec![],
+        }
+    }
+}
+
+/// Answer `status` to requests matching the method and the path prefix
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailureRule {
+    /// Any method when missing
+    #[serde(default)]
+    pub method: Option<String>,
+    pub path: String,
+    pub status: u16,
+    /// Number of matching requests answered normally before failing
+    #[serde(default)]
+    pub after: usize,
+    /// Number of failures, fails forever when missing
+    #[serde(default)]
+    pub times: Option<usize>,
+}
+
+impl FailureRule {
+    pub fn matches(&self, method: &str, path: &str) -> bool {
+        self.method
+            .as_ref()
+            .is_none_or(|m| m.eq_ignore_ascii_case(method))
+            && path.starts_with(&self.path)
+    }
+
+    /// Whether the n-th matching request, counted from zero, fails
+    pub fn fails(&self, n: usize) -> bool {
+        n >= self.after && self.times.is_none_or(|times| n < self.after + times)
+    }
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> io::Result<Scenario> {
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(io::Error::other)
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    io,
+    net::SocketAddr,
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tiny_http::{Header, Request, Response, Server};
+
+use crate::{
+    scenario::Scenario,
+    store::{ApiError, Store},
+};
+
+#[derive(Debug, Deserialize)]
+struct AuthRequest {
+    login: String,
+    pass: String,
+}
+
+/// The REST backend contract used by the client, served from memory on one thread
+pub struct MockServer {
+    server: Server,
+    store: Store,
+    scenario: Scenario,
+    /// Matching requests seen by each failure rule
+    failure_counts: Vec<usize>,
+}
+
+impl MockServer {
+    /// Listen on `addr`, port 0 picks a free one
+    pub fn bind(addr: &str, scenario: Scenario) -> io::Result<MockServer> {
+        let server = Server::http(addr).map_err(io::Error::other)?;
+        Ok(MockServer {
+            server,
+            store: Store::new(scenario.opponent.clone()),
+            failure_counts: vec![0; scenario.failures.len()],
+            scenario,
+        })
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    /// Answer requests until the process ends
+    pub fn run(mut self) {
+        while let Ok(request) = self.server.recv() {
+            self.handle(request);
+        }
+    }
+
+    /// Run on a background thread, returns the bound address
+    pub fn spawn(addr: &str, scenario: Scenario) -> io::Result<SocketAddr> {
+        let server = MockServer::bind(addr, scenario)?;
+        let local_addr = server
+            .local_addr()
+            .ok_or(io::Error::other("not an ip address"))?;
+        thread::spawn(move || server.run());
+        Ok(local_addr)
+    }
+
+    fn handle(&mut self, mut request: Request) {
+        let method = request.method().as_str().to_uppercase();
+        let url = request.url().to_string();
+        let path = url.split('?').next().unwrap_or_default().to_string();
+        let mut body = String::new();
+        let _ = request.as_reader().read_to_string(&mut body);
+        thread::sleep(Duration::from_millis(self.scenario.latency_ms));
+        let (status, body) = match self.respond(&method, &path, &body) {
+            Ok(body) => (200, body),
+            Err(e) => (e.status(), e.to_string()),
+        };
+        println!("{} {} -> {}", method, url, status);
+        let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+        let response = Response::from_string(body)
+            .with_status_code(status)
+            .with_header(content_type);
+        if let Err(e) = request.respond(response) {
+            eprintln!("Failed to respond: {}", e);
+        }
+    }
+
+    fn respond(&mut self, method: &str, path: &str, body: &str) -> Result<String, ApiError> {
+        self.check_failures(method, path)?;
+        self.store.tick(Instant::now());
+        match (method, path) {
+            ("POST", "/signup") => {
+                let auth: AuthRequest = parse(body)?;
+                to_json(&self.store.signup(&auth.login, &auth.pass)?)
+            }
+            ("POST", "/signin") => {
+                let auth: AuthRequest = parse(body)?;
+                to_json(&self.store.signin(&auth.login, &auth.pass)?)
+            }
+            ("POST", "/signin/token") => to_json(&self.store.check_signin(&parse(body)?)?),
+            ("GET", _) if path.starts_with("/user/") && path.ends_with("/profile") => {
+                let user_id = path
+                    .trim_start_matches("/user/")
+                    .trim_end_matches("/profile")
+                    .parse()
+                    .map_err(|_| ApiError::NotFound(path.to_string()))?;
+                to_json(&self.store.profile(user_id)?)
+            }
+            ("PATCH", "/user/profile") => to_json(&self.store.update_profile(&parse(body)?)?),
+            ("PATCH", "/user/password") => {
+                self.store.change_password(&parse(body)?)?;
+                Ok(String::new())
+            }
+            ("DELETE", "/user") => {
+                self.store.delete_account(&parse(body)?)?;
+                Ok(String::new())
+            }
+            ("GET", "/session") => to_json(&self.store.vacant_sessions(&parse(body)?)?),
+            ("POST", "/session") => to_json(&self.store.create_session(&parse(body)?)?),
+            ("PATCH", "/session") => to_json(&self.store.join_session(&parse(body)?)?),
+            ("GET", _) if path.starts_with("/session/") => {
+                to_json(&self.store.session(&path["/session/".len()..])?)
+            }
+            ("POST", "/matchmaking") => to_json(&self.store.find_game(&parse(body)?)?),
+            ("GET", "/matchmaking") => to_json(&self.store.matchmaking_ticket(&parse(body)?)?),
+            ("DELETE", "/matchmaking") => {
+                self.store.cancel_matchmaking(&parse(body)?)?;
+                Ok(String::new())
+            }
+            ("POST", "/game/state") => to_json(&self.store.game_state(&parse(body)?)?),
+            ("PATCH", "/game/state") => to_json(&self.store.play(&parse(body)?)?),
+            _ => Err(ApiError::NotFound(format!("{} {}", method, path))),
+        }
+    }
+
+    /// Count the request for every matching rule, the first failing one answers
+    fn check_failures(&mut self, method: &str, path: &str) -> Result<(), ApiError> {
+        let mut failure = None;
+        for (rule, count) in self
+            .scenario
+            .failures
+            .iter()
+            .zip(self.failure_counts.iter_mut())
+        {
+            if !rule.matches(method, path) {
+                continue;
+            }
+            if failure.is_none() && rule.fails(*count) {
+                failure = Some(ApiError::Scripted(rule.status));
+            }
+            *count += 1;
+        }
+        failure.map_or(Ok(()), Err)
+    }
+}
+
+fn parse<T: DeserializeOwned>(body: &str) -> Result<T, ApiError> {
+    serde_json::from_str(body).map_err(|e| ApiError::BadRequest(e.to_string()))
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, ApiError> {
+    serde_json::to_string(value).map_err(|e| ApiError::BadRequest(e.to_string()))
+}
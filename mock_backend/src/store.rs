@@ -0,0 +1,612 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use game_core::{
+    account,
+    dto::{
+        AccountDeleteDto, ColorPreference, FieldErrorDto, GamePhase, GameSettingsDto, GameState,
+        GameStateDto, HandicapPlacement, MatchmakingRequestDto, MatchmakingStatus,
+        MatchmakingTicketDto, PasswordChangeDto, ProfileDto, ProfileUpdateDto, UserSessionDto,
+        UserSessionRequestDto, UserStatusDto, UserStepRequestDto, UserTokenDto, ValidationErrorDto,
+    },
+    handicap,
+    matchmaking::{self, MatchQueue, QueueEntry},
+    rating::Rating,
+    rules::GoGame,
+};
+
+use crate::scenario::OpponentScript;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiError {
+    BadRequest(String),
+    Unauthorized,
+    Forbidden,
+    NotFound(String),
+    Conflict(String),
+    /// Refused fields, the body is a `ValidationErrorDto`
+    Validation(Vec<FieldErrorDto>),
+    /// Injected by a scenario failure rule
+    Scripted(u16),
+}
+
+impl ApiError {
+    pub fn status(&self) -> u16 {
+        match self {
+            ApiError::BadRequest(_) => 400,
+            ApiError::Unauthorized => 401,
+            ApiError::Forbidden => 403,
+            ApiError::NotFound(_) => 404,
+            ApiError::Conflict(_) => 409,
+            ApiError::Validation(_) => 400,
+            ApiError::Scripted(status) => *status,
+        }
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::BadRequest(message) => write!(f, "bad request: {}", message),
+            ApiError::Unauthorized => write!(f, "wrong login or password"),
+            ApiError::Forbidden => write!(f, "wrong session password"),
+            ApiError::NotFound(what) => write!(f, "{} not found", what),
+            ApiError::Conflict(message) => write!(f, "{}", message),
+            ApiError::Validation(errors) => {
+                let body = ValidationErrorDto {
+                    errors: errors.clone(),
+                };
+                write!(f, "{}", serde_json::to_string(&body).unwrap_or_default())
+            }
+            ApiError::Scripted(status) => write!(f, "scripted failure {}", status),
+        }
+    }
+}
+
+struct User {
+    login: String,
+    pass: String,
+    token: String,
+    display_name: Option<String>,
+    avatar: Option<String>,
+    rating: Rating,
+}
+
+struct MockGame {
+    game_state_id: i64,
+    game: GoGame,
+    colors: HashMap<i64, bool>,
+    handicap_left: usize,
+    /// Scripted moves the bot has used up
+    bot_moves_played: usize,
+}
+
+struct Session {
+    dto: UserSessionDto,
+    password: Option<String>,
+    created: Instant,
+    game: Option<MockGame>,
+}
+
+/// In-memory users, sessions and games. Ids start from 1 and never repeat.
+pub struct Store {
+    users: HashMap<i64, User>,
+    sessions: Vec<Session>,
+    opponent: Option<OpponentScript>,
+    /// User id of the scripted opponent, registered on its first join
+    bot_id: Option<i64>,
+    match_queue: MatchQueue,
+    /// Tickets of the paired players by user id, kept until they queue again
+    matched: HashMap<i64, MatchmakingTicketDto>,
+    next_id: i64,
+}
+
+impl Store {
+    pub fn new(opponent: Option<OpponentScript>) -> Self {
+        Self {
+            users: HashMap::new(),
+            sessions: vec![],
+            opponent,
+            bot_id: None,
+            match_queue: MatchQueue::default(),
+            matched: HashMap::new(),
+            next_id: 1,
+        }
+    }
+
+    fn next_id(&mut self) -> i64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    pub fn signup(&mut self, login: &str, pass: &str) -> Result<UserTokenDto, ApiError> {
+        let errors = account::validate_signup(login, pass, pass);
+        if !errors.is_empty() {
+            return Err(ApiError::Validation(errors));
+        }
+        if self.users.values().any(|u| u.login == login) {
+            return Err(ApiError::Conflict(format!("{} is already taken", login)));
+        }
+        let user_id = self.next_id();
+        self.users.insert(
+            user_id,
+            User {
+                login: login.to_string(),
+                pass: pass.to_string(),
+                token: format!("mock-token-{}", user_id),
+                display_name: None,
+                avatar: None,
+                rating: Rating::default(),
+            },
+        );
+        self.signin(login, pass)
+    }
+
+    pub fn signin(&self, login: &str, pass: &str) -> Result<UserTokenDto, ApiError> {
+        self.users
+            .iter()
+            .find(|(_, u)| u.login == login && u.pass == pass)
+            .map(|(user_id, u)| UserTokenDto {
+                user_id: *user_id,
+                login: u.login.clone(),
+                token: u.token.clone(),
+            })
+            .ok_or(ApiError::Unauthorized)
+    }
+
+    /// The same token while it's still valid
+    pub fn check_signin(&self, token: &UserTokenDto) -> Result<UserTokenDto, ApiError> {
+        self.check_token(token)?;
+        Ok(token.clone())
+    }
+
+    pub fn profile(&self, user_id: i64) -> Result<ProfileDto, ApiError> {
+        let user = self
+            .users
+            .get(&user_id)
+            .ok_or(ApiError::NotFound(format!("user {}", user_id)))?;
+        Ok(ProfileDto {
+            user_id,
+            login: user.login.clone(),
+            display_name: user.display_name.clone(),
+            avatar: user.avatar.clone(),
+        })
+    }
+
+    /// An empty display name clears it
+    pub fn update_profile(&mut self, request: &ProfileUpdateDto) -> Result<ProfileDto, ApiError> {
+        self.check_token(&request.token)?;
+        let mut errors = vec![];
+        let display_name = request.display_name.as_deref().map(str::trim);
+        if let Some(message) = display_name.and_then(account::validate_display_name) {
+            errors.push(account::field_error(account::FIELD_DISPLAY_NAME, message));
+        }
+        if let Some(message) = request.avatar.as_deref().and_then(account::validate_avatar) {
+            errors.push(account::field_error(account::FIELD_AVATAR, message));
+        }
+        if !errors.is_empty() {
+            return Err(ApiError::Validation(errors));
+        }
+        let user = self.users.get_mut(&request.token.user_id).unwrap();
+        if let Some(display_name) = display_name {
+            user.display_name = Some(display_name.to_string()).filter(|n| !n.is_empty());
+        }
+        if request.avatar.is_some() {
+            user.avatar = request.avatar.clone();
+        }
+        self.profile(request.token.user_id)
+    }
+
+    pub fn change_password(&mut self, request: &PasswordChangeDto) -> Result<(), ApiError> {
+        self.check_token(&request.token)?;
+        let user = self.users.get_mut(&request.token.user_id).unwrap();
+        if user.pass != request.old_pass {
+            let error = account::field_error(account::FIELD_OLD_PASS, "Wrong password");
+            return Err(ApiError::Validation(vec![error]));
+        }
+        if let Some(message) = account::validate_pass(&request.new_pass) {
+            let error = account::field_error(account::FIELD_NEW_PASS, message);
+            return Err(ApiError::Validation(vec![error]));
+        }
+        user.pass = request.new_pass.clone();
+        Ok(())
+    }
+
+    /// The user leaves the sessions waiting for an opponent, finished games keep the login
+    pub fn delete_account(&mut self, request: &AccountDeleteDto) -> Result<(), ApiError> {
+        self.check_token(&request.token)?;
+        let user_id = request.token.user_id;
+        if self.users[&user_id].pass != request.pass {
+            let error = account::field_error(account::FIELD_PASS, "Wrong password");
+            return Err(ApiError::Validation(vec![error]));
+        }
+        self.users.remove(&user_id);
+        self.sessions
+            .retain(|s| s.game.is_some() || s.dto.user1.user_id != Some(user_id));
+        Ok(())
+    }
+
+    fn check_token(&self, token: &UserTokenDto) -> Result<(), ApiError> {
+        match self.users.get(&token.user_id) {
+            Some(user) if user.token == token.token => Ok(()),
+            _ => Err(ApiError::Unauthorized),
+        }
+    }
+
+    fn user_status(&self, user_id: i64) -> Result<UserStatusDto, ApiError> {
+        let user = self
+            .users
+            .get(&user_id)
+            .ok_or(ApiError::NotFound(format!("user {}", user_id)))?;
+        Ok(UserStatusDto {
+            login: user.login.clone(),
+            is_online: true,
+            rank: None,
+            user_id: Some(user_id),
+            rating: Some(user.rating.into()),
+        })
+    }
+
+    pub fn find_game(
+        &mut self,
+        request: &MatchmakingRequestDto,
+    ) -> Result<MatchmakingTicketDto, ApiError> {
+        self.check_token(&request.token)?;
+        let user_id = request.token.user_id;
+        let rating = self.users[&user_id].rating;
+        self.matched.remove(&user_id);
+        self.match_queue.enqueue(QueueEntry {
+            user_id,
+            rating,
+            preferences: request.preferences.clone(),
+            queued_at: unix_time(),
+        });
+        while let Some((host, guest)) = self.match_queue.pop_match() {
+            self.start_match(&host, &guest)?;
+        }
+        self.matchmaking_ticket(&request.token)
+    }
+
+    /// Host a session for the earlier player and join the other one into it
+    fn start_match(&mut self, host: &QueueEntry, guest: &QueueEntry) -> Result<(), ApiError> {
+        let created = self.create_session(&UserSessionRequestDto {
+            user_id: host.user_id,
+            settings: Some(matchmaking::match_settings(host, guest)),
+            ..Default::default()
+        })?;
+        let session = self.join_session(&UserSessionRequestDto {
+            user_id: guest.user_id,
+            session_id: Some(created.session_id),
+            ..Default::default()
+        })?;
+        for entry in [host, guest] {
+            let ticket = MatchmakingTicketDto {
+                status: MatchmakingStatus::Matched,
+                preferences: entry.preferences.clone(),
+                queued_at: entry.queued_at,
+                session: Some(session.clone()),
+            };
+            self.matched.insert(entry.user_id, ticket);
+        }
+        Ok(())
+    }
+
+    pub fn matchmaking_ticket(
+        &self,
+        token: &UserTokenDto,
+    ) -> Result<MatchmakingTicketDto, ApiError> {
+        self.check_token(token)?;
+        if let Some(ticket) = self.matched.get(&token.user_id) {
+            return Ok(ticket.clone());
+        }
+        let entry = self
+            .match_queue
+            .get(token.user_id)
+            .ok_or(ApiError::NotFound("matchmaking ticket".to_string()))?;
+        Ok(MatchmakingTicketDto {
+            status: MatchmakingStatus::Searching,
+            preferences: entry.preferences.clone(),
+            queued_at: entry.queued_at,
+            session: None,
+        })
+    }
+
+    pub fn cancel_matchmaking(&mut self, token: &UserTokenDto) -> Result<(), ApiError> {
+        self.check_token(token)?;
+        if self.match_queue.cancel(token.user_id) {
+            Ok(())
+        } else if self.matched.contains_key(&token.user_id) {
+            Err(ApiError::Conflict("the game is already found".to_string()))
+        } else {
+            Err(ApiError::NotFound("matchmaking ticket".to_string()))
+        }
+    }
+
+    /// Public sessions waiting for the second player, join codes are hidden
+    pub fn vacant_sessions(&self, token: &UserTokenDto) -> Result<Vec<UserSessionDto>, ApiError> {
+        self.check_token(token)?;
+        Ok(self
+            .sessions
+            .iter()
+            .filter(|s| s.dto.user2.is_none() && s.dto.join_code.is_none())
+            .map(|s| UserSessionDto {
+                join_code: None,
+                ..s.dto.clone()
+            })
+            .collect())
+    }
+
+    pub fn create_session(
+        &mut self,
+        request: &UserSessionRequestDto,
+    ) -> Result<UserSessionDto, ApiError> {
+        let user1 = self.user_status(request.user_id)?;
+        let settings = request.settings.clone().unwrap_or_default();
+        let number = self.next_id();
+        let dto = UserSessionDto {
+            user1,
+            user2: None,
+            session_id: format!("mock-session-{}", number),
+            join_code: settings.is_private.then(|| format!("MOCK{}", number)),
+            settings: Some(settings),
+        };
+        self.sessions.push(Session {
+            dto: dto.clone(),
+            password: request.password.clone(),
+            created: Instant::now(),
+            game: None,
+        });
+        Ok(dto)
+    }
+
+    /// Join by the session id from the lobby or by the join code of a private session
+    pub fn join_session(
+        &mut self,
+        request: &UserSessionRequestDto,
+    ) -> Result<UserSessionDto, ApiError> {
+        let user2 = self.user_status(request.user_id)?;
+        let index = self
+            .sessions
+            .iter()
+            .position(|s| match (&request.session_id, &request.join_code) {
+                (Some(id), _) => &s.dto.session_id == id,
+                (None, Some(code)) => s.dto.join_code.as_ref() == Some(code),
+                (None, None) => false,
+            })
+            .ok_or(ApiError::NotFound("session".to_string()))?;
+        let session = &self.sessions[index];
+        if session.password.is_some() && session.password != request.password {
+            return Err(ApiError::Forbidden);
+        }
+        if session.dto.user2.is_some() {
+            return Err(ApiError::Conflict("the session is full".to_string()));
+        }
+        let game_state_id = self.next_id();
+        let session = &mut self.sessions[index];
+        session.dto.user2 = Some(user2);
+        session.game = Some(start_game(game_state_id, &session.dto, false));
+        Ok(UserSessionDto {
+            join_code: None,
+            ..session.dto.clone()
+        })
+    }
+
+    pub fn session(&self, session_id: &str) -> Result<UserSessionDto, ApiError> {
+        self.sessions
+            .iter()
+            .find(|s| s.dto.session_id == session_id)
+            .map(|s| s.dto.clone())
+            .ok_or(ApiError::NotFound(format!("session {}", session_id)))
+    }
+
+    fn game_mut(&mut self, session_id: &str) -> Result<(&UserSessionDto, &mut MockGame), ApiError> {
+        let session = self
+            .sessions
+            .iter_mut()
+            .find(|s| s.dto.session_id == session_id)
+            .ok_or(ApiError::NotFound(format!("session {}", session_id)))?;
+        match session.game.as_mut() {
+            Some(game) => Ok((&session.dto, game)),
+            None => Err(ApiError::NotFound("game".to_string())),
+        }
+    }
+
+    pub fn game_state(
+        &mut self,
+        request: &UserSessionRequestDto,
+    ) -> Result<GameStateDto, ApiError> {
+        let session_id = request
+            .session_id
+            .clone()
+            .ok_or(ApiError::BadRequest("missing session_id".to_string()))?;
+        let (dto, game) = self.game_mut(&session_id)?;
+        Ok(game.to_dto(&dto.session_id))
+    }
+
+    /// A move or, while black places free handicap stones, a handicap stone
+    pub fn play(&mut self, request: &UserStepRequestDto) -> Result<GameStateDto, ApiError> {
+        let user_id = request.session.user_id;
+        let point = (request.step.row as usize, request.step.col as usize);
+        let bot = self.bot();
+        let (dto, game) = self.game_mut(&request.session.session_id)?;
+        let color = *game.colors.get(&user_id).ok_or(ApiError::Forbidden)?;
+        if color != game.game.to_move() {
+            return Err(ApiError::Conflict("not your turn".to_string()));
+        }
+        game.play(point)
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        if let Some((bot_id, script)) = bot {
+            game.advance_bot(bot_id, &script);
+        }
+        Ok(game.to_dto(&dto.session_id))
+    }
+
+    fn bot(&self) -> Option<(i64, OpponentScript)> {
+        Some((self.bot_id?, self.opponent.clone()?))
+    }
+
+    /// Let the scripted opponent join sessions which waited long enough and play its turns
+    pub fn tick(&mut self, now: Instant) {
+        let script = match self.opponent.clone() {
+            Some(script) => script,
+            None => return,
+        };
+        let join_after = Duration::from_secs(script.join_after_secs);
+        let waiting: Vec<String> = self
+            .sessions
+            .iter()
+            .filter(|s| s.dto.user2.is_none() && now.duration_since(s.created) >= join_after)
+            .map(|s| s.dto.session_id.clone())
+            .collect();
+        if waiting.is_empty() && self.bot_id.is_none() {
+            return;
+        }
+        let bot_id = self.bot_user(&script.login);
+        for session_id in waiting {
+            let game_state_id = self.next_id();
+            let user2 = self.user_status(bot_id).ok();
+            let session = self
+                .sessions
+                .iter_mut()
+                .find(|s| s.dto.session_id == session_id)
+                .unwrap();
+            session.dto.user2 = user2;
+            session.game = Some(start_game(game_state_id, &session.dto, true));
+        }
+        for game in self.sessions.iter_mut().filter_map(|s| s.game.as_mut()) {
+            game.advance_bot(bot_id, &script);
+        }
+    }
+
+    fn bot_user(&mut self, login: &str) -> i64 {
+        if let Some(bot_id) = self.bot_id {
+            return bot_id;
+        }
+        let bot_id = self.next_id();
+        self.users.insert(
+            bot_id,
+            User {
+                login: login.to_string(),
+                pass: String::new(),
+                token: format!("mock-token-{}", bot_id),
+                display_name: None,
+                avatar: None,
+                rating: Rating::default(),
+            },
+        );
+        self.bot_id = Some(bot_id);
+        bot_id
+    }
+}
+
+/// Seconds since the Unix epoch, like the timestamps of the real server
+fn unix_time() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// The host gets the preferred color, nigiri always gives black to the host.
+/// A bot playing black doesn't place free handicap, its stones go to the star points.
+fn start_game(game_state_id: i64, session: &UserSessionDto, bot_joined: bool) -> MockGame {
+    let settings: GameSettingsDto = session.settings.clone().unwrap_or_default();
+    let host_color = settings.color != ColorPreference::White;
+    let guest_id = session.user2.as_ref().and_then(|u| u.user_id).unwrap_or(0);
+    let colors = HashMap::from([
+        (session.user1.user_id.unwrap_or(0), host_color),
+        (guest_id, !host_color),
+    ]);
+    let size = settings.board_size as usize;
+    let stones = settings.handicap.max(0) as usize;
+    let bot_is_black = bot_joined && !host_color;
+    let free = settings.handicap_placement == HandicapPlacement::Free && !bot_is_black;
+    let (game, handicap_left) = match handicap::fixed_handicap_points(size, stones) {
+        Some(_) if free => (GoGame::new(size, settings.komi), stones),
+        Some(points) => (GoGame::with_handicap(size, settings.komi, &points), 0),
+        None => (GoGame::new(size, settings.komi), 0),
+    };
+    MockGame {
+        game_state_id,
+        game,
+        colors,
+        handicap_left,
+        bot_moves_played: 0,
+    }
+}
+
+impl MockGame {
+    fn play(&mut self, point: (usize, usize)) -> Result<(), game_core::rules::MoveError> {
+        if self.handicap_left == 0 {
+            return self.game.play(point).map(|_| ());
+        }
+        self.game.place_handicap(point)?;
+        self.handicap_left -= 1;
+        if self.handicap_left == 0 {
+            self.game.start_after_handicap();
+        }
+        Ok(())
+    }
+
+    /// Play the bot's turn: the next legal scripted point, the first legal point or a pass
+    fn advance_bot(&mut self, bot_id: i64, script: &OpponentScript) {
+        let bot_color = match self.colors.get(&bot_id) {
+            Some(color) => *color,
+            None => return,
+        };
+        if self.game.is_over() || self.game.to_move() != bot_color {
+            return;
+        }
+        while let Some(point) = script.moves.get(self.bot_moves_played) {
+            self.bot_moves_played += 1;
+            if self.play(*point).is_ok() {
+                return;
+            }
+        }
+        match self.game.legal_moves().first() {
+            Some(point) => {
+                let _ = self.play(*point);
+            }
+            None => {
+                let _ = self.game.pass();
+            }
+        }
+    }
+
+    fn to_dto(&self, session_id: &str) -> GameStateDto {
+        let to_move = self.game.to_move();
+        let active_user_id = self
+            .colors
+            .iter()
+            .find(|(_, color)| **color == to_move)
+            .map(|(user_id, _)| *user_id)
+            .unwrap_or(0);
+        let is_over = self.game.is_over();
+        GameStateDto {
+            game_state_id: self.game_state_id,
+            user_session_id: session_id.to_string(),
+            active_user_id,
+            game_state: GameState {
+                score: self.game.captures().clone(),
+                board: self.game.board().clone(),
+                colors: self.colors.clone(),
+                phase: if is_over {
+                    GamePhase::Finished
+                } else {
+                    GamePhase::Play
+                },
+                scoring: None,
+                clock: None,
+                result: None,
+                move_number: self.game.moves().len(),
+                handicap_left: self.handicap_left as u32,
+                undo: None,
+            },
+            is_active: !is_over,
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use game_core::{
+    account::{
+        error_for, FIELD_CONFIRM, FIELD_DISPLAY_NAME, FIELD_LOGIN, FIELD_OLD_PASS, FIELD_PASS,
+    },
+    api::{self, ApiClient, ApiError},
+    dto::{
+        AccountDeleteDto, FieldErrorDto, GameSettingsDto, MatchPreferencesDto,
+        MatchmakingRequestDto, MatchmakingStatus, PasswordChangeDto, ProfileUpdateDto, StepDto,
+        UserSessionRequestDto, UserSessionStepDto, UserStepRequestDto, UserTokenDto,
+    },
+};
+use mock_backend::{
+    scenario::{FailureRule, OpponentScript, Scenario},
+    server::MockServer,
+};
+use reqwest::StatusCode;
+
+fn start(scenario: Scenario) -> ApiClient {
+    let addr = MockServer::spawn("127.0.0.1:0", scenario).unwrap();
+    ApiClient::new(&format!("http://{}", addr))
+}
+
+fn status<T: std::fmt::Debug>(result: Result<T, ApiError>) -> StatusCode {
+    match result {
+        Err(ApiError::Status(status)) => status,
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+fn session_request(user_id: i64, session_id: &str) -> UserSessionRequestDto {
+    UserSessionRequestDto {
+        user_id,
+        session_id: Some(session_id.to_string()),
+        ..Default::default()
+    }
+}
+
+fn step(user_id: i64, session_id: &str, row: i32, col: i32) -> UserStepRequestDto {
+    UserStepRequestDto {
+        session: UserSessionStepDto {
+            session_id: session_id.to_string(),
+            user_id,
+        },
+        step: StepDto { row, col },
+    }
+}
+
+fn create_small_session(api: &ApiClient, user_id: i64) -> String {
+    let request = UserSessionRequestDto {
+        user_id,
+        settings: Some(GameSettingsDto {
+            board_size: 9,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    api.create_session(&request).unwrap().session_id
+}
+
+#[test]
+fn two_users_sign_in_join_and_play() {
+    let api = start(Scenario::default());
+    let host = api.signup("alice", "secret1").unwrap();
+    let guest = api.signup("bob", "secret1").unwrap();
+    assert_eq!(StatusCode::CONFLICT, status(api.signup("alice", "other1")));
+    assert_eq!(
+        StatusCode::UNAUTHORIZED,
+        status(api.signin("alice", "wrong"))
+    );
+    let session_id = create_small_session(&api, host.user_id);
+    let vacant: Vec<String> = api
+        .vacant_sessions(&guest)
+        .unwrap()
+        .into_iter()
+        .map(|s| s.session_id)
+        .collect();
+    assert_eq!(vec![session_id.clone()], vacant);
+    assert_eq!(
+        StatusCode::NOT_FOUND,
+        status(api.game_state(&session_request(host.user_id, &session_id)))
+    );
+
+    let joined = api
+        .join_session(&session_request(guest.user_id, &session_id))
+        .unwrap();
+    assert_eq!("bob", joined.user2.unwrap().login);
+    assert!(api.session(&session_id).unwrap().user2.is_some());
+
+    // Nigiri gives black to the host
+    assert_eq!(
+        StatusCode::CONFLICT,
+        status(api.play(&step(guest.user_id, &session_id, 0, 0)))
+    );
+    api.play(&step(host.user_id, &session_id, 2, 3)).unwrap();
+    let state = api
+        .game_state(&session_request(guest.user_id, &session_id))
+        .unwrap();
+    assert_eq!(Some(true), state.game_state.board[2][3]);
+    assert_eq!(guest.user_id, state.active_user_id);
+    assert_eq!(1, state.game_state.move_number);
+    assert_eq!(
+        StatusCode::BAD_REQUEST,
+        status(api.play(&step(guest.user_id, &session_id, 2, 3)))
+    );
+}
+
+#[test]
+fn remembered_token_signs_in_until_it_is_forged() {
+    let api = start(Scenario::default());
+    let token = api.signup("alice", "secret1").unwrap();
+    assert_eq!("alice", api.signin_with_token(&token).unwrap().login);
+    let forged = UserTokenDto {
+        token: "stolen".to_string(),
+        ..token
+    };
+    assert_eq!(
+        StatusCode::UNAUTHORIZED,
+        status(api.signin_with_token(&forged))
+    );
+}
+
+fn field_errors<T: std::fmt::Debug>(result: Result<T, ApiError>) -> Vec<FieldErrorDto> {
+    match result {
+        Err(ApiError::Validation(errors)) => errors,
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn account_is_validated_updated_and_deleted() {
+    let api = start(Scenario::default());
+    let errors = field_errors(api.signup("al", "short"));
+    assert!(error_for(&errors, FIELD_LOGIN).is_some());
+    assert!(error_for(&errors, FIELD_PASS).is_some());
+    assert!(error_for(&errors, FIELD_CONFIRM).is_none());
+
+    let token = api.signup("alice", "secret1").unwrap();
+    let update = ProfileUpdateDto {
+        token: token.clone(),
+        display_name: Some(" Alice ".to_string()),
+        avatar: Some("crane".to_string()),
+    };
+    let profile = api.update_profile(&update).unwrap();
+    assert_eq!(Some("Alice".to_string()), profile.display_name);
+    assert_eq!(
+        Some("crane".to_string()),
+        api.profile(token.user_id).unwrap().avatar
+    );
+    let long_name = ProfileUpdateDto {
+        display_name: Some("x".repeat(40)),
+        ..update
+    };
+    let errors = field_errors(api.update_profile(&long_name));
+    assert!(error_for(&errors, FIELD_DISPLAY_NAME).is_some());
+
+    let change = PasswordChangeDto {
+        token: token.clone(),
+        old_pass: "wrong1".to_string(),
+        new_pass: "better2".to_string(),
+    };
+    let errors = field_errors(api.change_password(&change));
+    assert_eq!(Some("Wrong password"), error_for(&errors, FIELD_OLD_PASS));
+    api.change_password(&PasswordChangeDto {
+        old_pass: "secret1".to_string(),
+        ..change
+    })
+    .unwrap();
+    assert!(api.signin("alice", "better2").is_ok());
+
+    let delete = AccountDeleteDto {
+        token: token.clone(),
+        pass: "better2".to_string(),
+    };
+    api.delete_account(&delete).unwrap();
+    assert_eq!(
+        StatusCode::UNAUTHORIZED,
+        status(api.signin("alice", "better2"))
+    );
+    assert_eq!(StatusCode::NOT_FOUND, status(api.profile(token.user_id)));
+}
+
+fn find_game(api: &ApiClient, token: &UserTokenDto, board_size: i32) -> MatchmakingStatus {
+    let request = MatchmakingRequestDto {
+        token: token.clone(),
+        preferences: MatchPreferencesDto {
+            board_size,
+            ..Default::default()
+        },
+    };
+    api.find_game(&request).unwrap().status
+}
+
+#[test]
+fn matchmaking_pairs_players_into_one_session() {
+    let api = start(Scenario::default());
+    let alice = api.signup("alice", "secret1").unwrap();
+    let bob = api.signup("bob", "secret1").unwrap();
+    let carol = api.signup("carol", "secret1").unwrap();
+
+    assert_eq!(
+        StatusCode::NOT_FOUND,
+        status(api.matchmaking_ticket(&alice))
+    );
+    assert_eq!(MatchmakingStatus::Searching, find_game(&api, &alice, 9));
+    assert_eq!(MatchmakingStatus::Searching, find_game(&api, &carol, 19));
+    api.cancel_matchmaking(&carol).unwrap();
+    assert_eq!(
+        StatusCode::NOT_FOUND,
+        status(api.cancel_matchmaking(&carol))
+    );
+
+    assert_eq!(MatchmakingStatus::Matched, find_game(&api, &bob, 9));
+    let ticket = api.matchmaking_ticket(&alice).unwrap();
+    assert_eq!(MatchmakingStatus::Matched, ticket.status);
+    let session = ticket.session.unwrap();
+    assert_eq!(Some(alice.user_id), session.user1.user_id);
+    assert_eq!(
+        Some(bob.user_id),
+        session.user2.as_ref().and_then(|u| u.user_id)
+    );
+    assert_eq!(9, session.settings.unwrap().board_size);
+    assert_eq!(StatusCode::CONFLICT, status(api.cancel_matchmaking(&bob)));
+
+    // Both players get the started game
+    let state = api
+        .game_state(&session_request(bob.user_id, &session.session_id))
+        .unwrap();
+    assert_eq!(alice.user_id, state.active_user_id);
+    assert!(api.vacant_sessions(&alice).unwrap().is_empty());
+}
+
+#[test]
+fn scripted_opponent_joins_and_answers() {
+    let api = start(Scenario {
+        opponent: Some(OpponentScript {
+            moves: vec![(4, 4)],
+            ..Default::default()
+        }),
+        ..Default::default()
+    });
+    let user = api.signup("alice", "secret1").unwrap();
+    let session_id = create_small_session(&api, user.user_id);
+    let request = session_request(user.user_id, &session_id);
+    assert_eq!(
+        user.user_id,
+        api.game_state(&request).unwrap().active_user_id
+    );
+    api.play(&step(user.user_id, &session_id, 0, 0)).unwrap();
+    let state = api.game_state(&request).unwrap();
+    assert_eq!(Some(false), state.game_state.board[4][4]);
+    // The script is used up, the bot takes the first legal point
+    api.play(&step(user.user_id, &session_id, 8, 8)).unwrap();
+    let state = api.game_state(&request).unwrap();
+    assert_eq!(Some(false), state.game_state.board[0][1]);
+    assert_eq!(4, state.game_state.move_number);
+}
+
+#[test]
+fn failure_rules_answer_with_scripted_status() {
+    let api = start(Scenario {
+        failures: vec![FailureRule {
+            method: Some("POST".to_string()),
+            path: "/signin".to_string(),
+            status: 503,
+            after: 1,
+            times: Some(1),
+        }],
+        ..Default::default()
+    });
+    api.signup("alice", "secret1").unwrap();
+    assert!(api.signin("alice", "secret1").is_ok());
+    assert_eq!(
+        StatusCode::SERVICE_UNAVAILABLE,
+        status(api.signin("alice", "secret1"))
+    );
+    assert!(
+        api::recent_responses().contains(&"POST /signin -> 503 Service Unavailable".to_string())
+    );
+    assert!(api.signin("alice", "secret1").is_ok());
+}